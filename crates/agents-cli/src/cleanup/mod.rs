@@ -5,17 +5,18 @@ use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions};
 use agents_core::model::ConfirmationType;
 use agents_core::resolv::{ResolutionRequest, Resolver};
 
+use crate::confrm::{self, ConfirmOptions, Gate};
 use crate::{AppError, ErrorCategory};
 
 #[derive(Debug, Clone)]
 pub struct CleanOptions {
     pub agent: Option<String>,
     pub dry_run: bool,
-    pub yes: bool,
+    pub confirm: ConfirmOptions,
 }
 
 pub fn cmd_clean(repo_root: &Path, opts: CleanOptions) -> Result<(), AppError> {
-    let (repo, _report) = load_repo_config(
+    let (mut repo, _report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -29,6 +30,7 @@ pub fn cmd_clean(repo_root: &Path, opts: CleanOptions) -> Result<(), AppError> {
             context: vec![],
         },
     })?;
+    crate::apply_user_overlay(&mut repo)?;
 
     // Validate schemas best-effort.
     let _ = agents_core::schemas::validate_repo(repo_root);
@@ -45,34 +47,6 @@ pub fn cmd_clean(repo_root: &Path, opts: CleanOptions) -> Result<(), AppError> {
         context: vec![],
     })?;
 
-    // Safety: require confirmation if policy requests it.
-    if !opts.dry_run {
-        let policy = repo
-            .policies
-            .get(&effective.policy_id)
-            .ok_or_else(|| AppError {
-                category: ErrorCategory::Io,
-                message: format!("missing policy {}", effective.policy_id),
-                context: vec![],
-            })?;
-
-        let requires_confirm = policy
-            .confirmations
-            .required_for
-            .contains(&ConfirmationType::Delete);
-
-        if requires_confirm && !opts.yes {
-            return Err(AppError {
-                category: ErrorCategory::PolicyDenied,
-                message: "delete requires confirmation by policy".to_string(),
-                context: vec![
-                    "hint: rerun with `--yes` to confirm delete".to_string(),
-                    "hint: or use `--dry-run` to preview deletions".to_string(),
-                ],
-            });
-        }
-    }
-
     let mut agent_ids: Vec<String> = if let Some(a) = &opts.agent {
         vec![a.clone()]
     } else {
@@ -91,6 +65,31 @@ pub fn cmd_clean(repo_root: &Path, opts: CleanOptions) -> Result<(), AppError> {
         .skipped
         .sort_by(|a, b| a.path.as_str().cmp(b.path.as_str()));
 
+    // Safety: require confirmation if policy requests it.
+    if !opts.dry_run && !identify.eligible.is_empty() {
+        let policy = effective.policy(&repo).ok_or_else(|| AppError {
+            category: ErrorCategory::Io,
+            message: format!("missing policy {}", effective.policy_id),
+            context: vec![],
+        })?;
+
+        confrm::require(
+            repo_root,
+            policy,
+            &Gate {
+                command: "clean",
+                action: format!("delete {} generated files", identify.eligible.len()),
+                kinds: vec![ConfirmationType::Delete],
+            },
+            opts.confirm,
+        )
+        .map_err(|mut e| {
+            e.context
+                .push("hint: or use `--dry-run` to preview deletions".to_string());
+            e
+        })?;
+    }
+
     let delete =
         delete_paths(repo_root, &identify.eligible, opts.dry_run).map_err(|e| AppError {
             category: ErrorCategory::Io,
@@ -0,0 +1,157 @@
+//! Gates for actions listed in the effective policy's `confirmations.requiredFor`.
+
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use agents_core::model::{ConfirmationType, Policy};
+
+use crate::{AppError, ErrorCategory};
+
+/// Relative to the repo root; kept next to `state.yaml` and git-ignored by `agents init`.
+const LOG_PATH: &str = ".agents/state/confirmations.jsonl";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfirmOptions {
+    /// `--yes`: grant every confirmation without prompting.
+    pub yes: bool,
+
+    /// `--ci`: never prompt.
+    pub ci: bool,
+}
+
+/// One confirmation point, e.g. `agents clean` deleting generated files.
+#[derive(Debug, Clone)]
+pub struct Gate<'a> {
+    /// Command name recorded in the log (e.g. `clean`).
+    pub command: &'a str,
+
+    /// Shown in the prompt, e.g. `delete 3 generated files`.
+    pub action: String,
+
+    /// Confirmation types this action falls under; only those the policy requires are asked for.
+    pub kinds: Vec<ConfirmationType>,
+}
+
+pub fn confirmation_name(kind: ConfirmationType) -> &'static str {
+    match kind {
+        ConfirmationType::Delete => "delete",
+        ConfirmationType::Overwrite => "overwrite",
+        ConfirmationType::Publish => "publish",
+        ConfirmationType::Deploy => "deploy",
+        ConfirmationType::Push => "push",
+        ConfirmationType::Rebase => "rebase",
+    }
+}
+
+/// Ask for the confirmations `policy` requires for `gate`.
+///
+/// Returns `Ok(())` when nothing is required or everything was granted. Without a TTY (or with
+/// `--ci`) the gate fails with `PolicyDenied` unless `--yes` was given. Granted confirmations are
+/// appended to `.agents/state/confirmations.jsonl`.
+pub fn require(
+    repo_root: &Path,
+    policy: &Policy,
+    gate: &Gate<'_>,
+    opts: ConfirmOptions,
+) -> Result<(), AppError> {
+    let required: Vec<ConfirmationType> = gate
+        .kinds
+        .iter()
+        .copied()
+        .filter(|k| policy.confirmations.required_for.contains(k))
+        .collect();
+    if required.is_empty() {
+        return Ok(());
+    }
+
+    let names: Vec<&str> = required.iter().map(|k| confirmation_name(*k)).collect();
+    let names = names.join(", ");
+
+    let via = if opts.yes {
+        "flag"
+    } else if opts.ci || !std::io::stdin().is_terminal() {
+        return Err(AppError {
+            category: ErrorCategory::PolicyDenied,
+            message: format!("{names} requires confirmation by policy"),
+            context: vec![
+                format!("policy: {}", policy.id),
+                format!("action: {}", gate.action),
+                format!("hint: rerun with `--yes` to confirm {names}"),
+            ],
+        });
+    } else if prompt(&format!(
+        "{} ({names} required by policy {})",
+        gate.action, policy.id
+    ))? {
+        "prompt"
+    } else {
+        return Err(AppError {
+            category: ErrorCategory::PolicyDenied,
+            message: format!("{names} not confirmed"),
+            context: vec![format!("action: {}", gate.action)],
+        });
+    };
+
+    eprintln!("confirm: {names} ({via})");
+    record(repo_root, policy, gate, &required, via);
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal; anything but `y`/`yes` is a no.
+pub fn prompt(question: &str) -> Result<bool, AppError> {
+    eprint!("{question}? [y/N] ");
+    let _ = std::io::stderr().flush();
+
+    let mut input = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut input)
+        .map_err(|e| AppError {
+            category: ErrorCategory::Io,
+            message: e.to_string(),
+            context: vec!["while reading confirmation".to_string()],
+        })?;
+    Ok(matches!(
+        input.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Best-effort: a failure to log never blocks the confirmed action.
+fn record(
+    repo_root: &Path,
+    policy: &Policy,
+    gate: &Gate<'_>,
+    granted: &[ConfirmationType],
+    via: &str,
+) {
+    if !repo_root.join(".agents").is_dir() {
+        return;
+    }
+    let path = repo_root.join(LOG_PATH);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    let at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let line = serde_json::json!({
+        "at": at,
+        "command": gate.command,
+        "action": gate.action,
+        "policy": policy.id,
+        "granted": granted.iter().map(|k| confirmation_name(*k)).collect::<Vec<_>>(),
+        "via": via,
+    });
+
+    let Ok(mut f) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    else {
+        return;
+    };
+    let _ = writeln!(f, "{line}");
+}
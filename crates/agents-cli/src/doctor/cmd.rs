@@ -1,10 +1,12 @@
 use std::path::Path;
+use std::sync::Arc;
 
 use agents_core::cleanup;
 use agents_core::fsutil;
 use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions};
 use agents_core::model::BackendKind;
-use agents_core::outputs::{plan_outputs, PlanError};
+use agents_core::outputs::PlanError;
+use agents_core::pipeline::{Pipeline, PipelineError};
 use agents_core::resolv::{ResolutionRequest, Resolver};
 use agents_core::{driftx, driftx::DiffKind};
 use std::collections::BTreeSet;
@@ -22,7 +24,7 @@ pub struct DoctorOptions {
 }
 
 pub fn cmd_doctor(repo_root: &Path, opts: DoctorOptions) -> Result<(), AppError> {
-    let (repo, _report) = load_repo_config(
+    let (mut repo, _report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -36,8 +38,10 @@ pub fn cmd_doctor(repo_root: &Path, opts: DoctorOptions) -> Result<(), AppError>
             context: vec![],
         },
     })?;
+    crate::apply_user_overlay(&mut repo)?;
 
-    let resolver = Resolver::new(repo.clone());
+    let repo = Arc::new(repo);
+    let resolver = Resolver::new(Arc::clone(&repo));
     let req = ResolutionRequest {
         repo_root: repo_root.to_path_buf(),
         ..Default::default()
@@ -242,14 +246,15 @@ fn apply_fixes(ctx: &DoctorContext) -> Vec<DoctorItem> {
     }
 
     // Fix (optional): remove stale generated outputs that are no longer planned.
-    if let (Some(repo), Some(effective)) = (&ctx.repo, &ctx.effective) {
+    if let Some(repo) = &ctx.repo {
         let mut agent_ids = repo.manifest.enabled.adapters.clone();
         agent_ids.sort();
 
         let mut deleted_paths: Vec<fsutil::RepoPath> = vec![];
         for agent_id in &agent_ids {
-            let plan = match plan_outputs(&ctx.repo_root, repo.clone(), effective, agent_id) {
-                Ok(p) => p.plan,
+            let plan = match Pipeline::for_agent(&ctx.repo_root, Arc::clone(repo), agent_id).plan()
+            {
+                Ok((_, p)) => p.plan,
                 Err(_) => continue,
             };
 
@@ -318,7 +323,13 @@ fn fix_state_gitignore(repo_root: &Path) -> Result<bool, fsutil::FsError> {
         String::new()
     };
 
-    for rule in ["state.yaml", "explain/"] {
+    for rule in [
+        "state.yaml",
+        "explain/",
+        "cache/",
+        "confirmations.jsonl",
+        "shims/",
+    ] {
         let rooted = format!("/{rule}");
         let has = content.lines().any(|l| {
             let t = l.trim();
@@ -386,21 +397,20 @@ fn collision_check(ctx: &DoctorContext) -> Vec<DoctorItem> {
     let Some(repo) = &ctx.repo else {
         return vec![];
     };
-    let Some(effective) = &ctx.effective else {
-        return vec![];
-    };
 
     let mut agent_ids = repo.manifest.enabled.adapters.clone();
     agent_ids.sort();
 
     let mut items: Vec<DoctorItem> = vec![];
     for agent_id in agent_ids {
-        let res = plan_outputs(&ctx.repo_root, repo.clone(), effective, &agent_id);
+        let res = Pipeline::for_agent(&ctx.repo_root, Arc::clone(repo), &agent_id).plan();
         if let Err(err) = res {
             let (level, msg) = match &err {
-                PlanError::PathCollision { .. }
-                | PlanError::SurfaceCollision { .. }
-                | PlanError::SharedOwnerViolation { .. } => (
+                PipelineError::Plan(
+                    PlanError::PathCollision { .. }
+                    | PlanError::SurfaceCollision { .. }
+                    | PlanError::SharedOwnerViolation { .. },
+                ) => (
                     if ctx.ci {
                         DoctorLevel::Error
                     } else {
@@ -436,9 +446,6 @@ fn drift_check(ctx: &DoctorContext) -> Vec<DoctorItem> {
     let Some(repo) = &ctx.repo else {
         return vec![];
     };
-    let Some(effective) = &ctx.effective else {
-        return vec![];
-    };
 
     let mut agent_ids = repo.manifest.enabled.adapters.clone();
     agent_ids.sort();
@@ -449,15 +456,10 @@ fn drift_check(ctx: &DoctorContext) -> Vec<DoctorItem> {
     let mut errors: Vec<String> = vec![];
 
     for agent_id in &agent_ids {
-        let plan = match plan_outputs(&ctx.repo_root, repo.clone(), effective, agent_id) {
-            Ok(p) => p.plan,
-            Err(e) => {
-                errors.push(format!("{agent_id}: {e}"));
-                continue;
-            }
-        };
-
-        let report = match driftx::diff_plan(&ctx.repo_root, &plan) {
+        let report = match Pipeline::for_agent(&ctx.repo_root, Arc::clone(repo), agent_id)
+            .render()
+            .and_then(|r| r.diff())
+        {
             Ok(r) => r,
             Err(e) => {
                 errors.push(format!("{agent_id}: {e}"));
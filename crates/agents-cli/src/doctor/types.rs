@@ -1,4 +1,5 @@
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DoctorLevel {
@@ -76,7 +77,7 @@ pub trait DoctorCheck {
 #[derive(Debug, Clone)]
 pub struct DoctorContext {
     pub repo_root: PathBuf,
-    pub repo: Option<RepoConfig>,
+    pub repo: Option<Arc<RepoConfig>>,
     pub effective: Option<EffectiveConfig>,
     pub ci: bool,
     pub fix: bool,
@@ -187,6 +187,9 @@ fn print_stamp_explain(repo_rel: &str, stamp: &agents_core::stamps::Stamp, outpu
                 "profile: {}",
                 stamp.meta.profile.as_deref().unwrap_or("<none>")
             );
+            if let Some(scope) = &stamp.meta.scope {
+                println!("scope: {scope}");
+            }
         }
     }
 }
@@ -216,6 +219,15 @@ fn explain_record_path(repo_root: &Path, repo_rel: &str) -> PathBuf {
     agents_core::fsutil::agents_explain_dir(repo_root).join(format!("{hash}.json"))
 }
 
+/// `value` as YAML, indented two spaces.
+fn indent_yaml(value: &serde_json::Value) -> String {
+    serde_yaml::to_string(value)
+        .unwrap_or_default()
+        .lines()
+        .map(|l| format!("  {l}\n"))
+        .collect()
+}
+
 fn print_explain_human(m: &agents_core::explain::ExplainSourceMap) {
     println!("path: {}", m.output_path);
     println!("adapter: {}", m.adapter_id);
@@ -236,8 +248,32 @@ fn print_explain_human(m: &agents_core::explain::ExplainSourceMap) {
         }
     }
 
+    if let Some(agent) = &m.effective.agent_id {
+        println!("resolved_for: {agent}");
+    }
+    if let Some(scope) = &m.effective.scope_id {
+        println!("scope: {scope}");
+    }
     println!("mode: {}", m.effective.mode_id);
     println!("policy: {}", m.effective.policy_id);
+    if !m.effective.policy_merged_from.is_empty() {
+        println!(
+            "policy_merged_from: {}",
+            m.effective.policy_merged_from.join(", ")
+        );
+        for c in &m.effective.policy_conflicts {
+            println!("policy_conflict: {c}");
+        }
+    }
+    if !m.effective.policy_extends.is_empty() || !m.effective.policy_merged_from.is_empty() {
+        if !m.effective.policy_extends.is_empty() {
+            println!("policy_extends: {}", m.effective.policy_extends.join(", "));
+        }
+        if let Some(policy) = &m.effective.policy {
+            println!("policy_flattened:");
+            print!("{}", indent_yaml(policy));
+        }
+    }
     println!("backend: {:?}", m.effective.backend);
     println!(
         "profile: {}",
@@ -254,6 +290,13 @@ fn print_explain_human(m: &agents_core::explain::ExplainSourceMap) {
         println!("snippets: {}", m.effective.snippet_ids.join(", "));
     }
 
+    if !m.effective.mode_source_paths.is_empty() {
+        println!("mode_sources:");
+        for p in &m.effective.mode_source_paths {
+            println!("- {p}");
+        }
+    }
+
     if !m.effective.prompt_source_paths.is_empty() {
         println!("prompt_sources:");
         for p in &m.effective.prompt_source_paths {
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use super::{
+    file_name, import_manifest, import_mode, parse_json_source, preset_files, read_source,
+    snippet_file, split_rule, string_list, CanonicalArtifacts, ImportInputs, Importer, PolicyPatch,
+};
+
+/// Imports `CLAUDE.md` and `.claude/settings.json`.
+pub(super) struct ClaudeImporter;
+
+impl Importer for ClaudeImporter {
+    fn agent_id(&self) -> &'static str {
+        "claude"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
+        let sources: Vec<_> = [
+            repo_root.join("CLAUDE.md"),
+            repo_root.join(".claude/settings.json"),
+        ]
+        .iter()
+        .filter_map(|p| read_source(p))
+        .collect();
+
+        if sources.is_empty() {
+            return None;
+        }
+
+        Some(ImportInputs {
+            source_path: sources[0].path.clone(),
+            sources,
+        })
+    }
+
+    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
+        let mut instructions: Vec<&str> = vec![];
+        let mut patch = PolicyPatch::default();
+        let mut notes = vec![];
+
+        for src in &inputs.sources {
+            if file_name(&src.path).ends_with(".json") {
+                let settings = parse_json_source(src)?;
+                map_settings(&settings, &mut patch, &mut notes);
+            } else {
+                instructions.push(src.content.as_str());
+            }
+        }
+
+        let mut files = preset_files("claude");
+
+        let policy_id = (!patch.is_empty()).then_some("claude-import");
+        if let Some(id) = policy_id {
+            files.push(patch.to_file(id, "Imported from .claude/settings.json")?);
+        }
+
+        files.push(import_manifest("claude", "claude-import", policy_id));
+
+        let mut snippets = vec![];
+        if !instructions.is_empty() {
+            files.push(snippet_file("claude", &instructions.join("\n")));
+            snippets.push("claude".to_string());
+        }
+
+        files.push(import_mode(
+            "claude-import",
+            "Claude Import",
+            policy_id,
+            &snippets,
+            "This mode includes imported Claude Code instructions and permissions.\n",
+        ));
+
+        Ok(CanonicalArtifacts { files, notes })
+    }
+}
+
+fn map_settings(settings: &serde_json::Value, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    let Some(obj) = settings.as_object() else {
+        notes.push("claude settings: expected a JSON object".to_string());
+        return;
+    };
+
+    for (key, value) in obj {
+        match key.as_str() {
+            "$schema" => {}
+            "permissions" => {
+                for rule in string_list(value.get("deny")) {
+                    map_deny(&rule, patch, notes);
+                }
+                for rule in string_list(value.get("allow")) {
+                    map_allow(&rule, patch, notes);
+                }
+            }
+            other => notes.push(format!("claude settings: ignored key: {other}")),
+        }
+    }
+}
+
+/// Map one `permissions.deny` rule, e.g. `Read(./.env)` or `Bash(rm -rf:*)`.
+fn map_deny(rule: &str, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    match split_rule(rule) {
+        ("Read", Some(p)) => patch.paths_redact.push(normalize_path(p)),
+        ("Edit" | "Write" | "MultiEdit", Some(p)) => patch.paths_deny.push(normalize_path(p)),
+        ("Edit" | "Write" | "MultiEdit", None) => patch.fs_write = Some(false),
+        ("Bash", Some(cmd)) => patch.exec_deny.push(normalize_command(cmd)),
+        ("Bash", None) => patch.exec_enabled = Some(false),
+        ("WebFetch", Some(spec)) if spec.starts_with("domain:") => {
+            patch.deny_hosts.push(spec["domain:".len()..].to_string())
+        }
+        ("WebFetch", None) => patch.network_enabled = Some(false),
+        _ => notes.push(format!("claude permissions.deny: unmapped rule: {rule}")),
+    }
+}
+
+/// Map one `permissions.allow` rule.
+fn map_allow(rule: &str, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    match split_rule(rule) {
+        ("Read" | "Edit" | "Write" | "MultiEdit", Some(p)) => {
+            patch.paths_allow.push(normalize_path(p))
+        }
+        ("Bash", Some(cmd)) => patch.exec_allow.push(normalize_command(cmd)),
+        ("WebFetch", Some(spec)) if spec.starts_with("domain:") => {
+            patch.network_enabled = Some(true);
+            patch.allow_hosts.push(spec["domain:".len()..].to_string());
+        }
+        _ => notes.push(format!("claude permissions.allow: unmapped rule: {rule}")),
+    }
+}
+
+fn normalize_path(p: &str) -> String {
+    p.trim_start_matches("./").to_string()
+}
+
+fn normalize_command(cmd: &str) -> String {
+    cmd.trim_end_matches(":*").trim().to_string()
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use super::{
+    import_manifest, import_mode, preset_files, read_source, snippet_file, CanonicalArtifacts,
+    ImportInputs, Importer,
+};
+
+/// Imports a hand-written `AGENTS.md` (Codex and other AGENTS.md-aware tools).
+pub(super) struct CodexImporter;
+
+impl Importer for CodexImporter {
+    fn agent_id(&self) -> &'static str {
+        "codex"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
+        let path = repo_root.join("AGENTS.md");
+        let source = read_source(&path)?;
+        Some(ImportInputs {
+            source_path: path,
+            sources: vec![source],
+        })
+    }
+
+    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
+        let mut files = preset_files("codex");
+        files.push(import_manifest("codex", "codex-import", None));
+
+        let content: Vec<&str> = inputs.sources.iter().map(|s| s.content.as_str()).collect();
+        files.push(snippet_file("codex", &content.join("\n")));
+
+        files.push(import_mode(
+            "codex-import",
+            "AGENTS.md Import",
+            None,
+            &["codex".to_string()],
+            "This mode includes the imported AGENTS.md as a snippet (codex).\n",
+        ));
+
+        Ok(CanonicalArtifacts {
+            files,
+            notes: vec![
+                "AGENTS.md is now generated; remove or rename the original before `agents sync`"
+                    .to_string(),
+            ],
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use super::{
+    import_manifest, import_mode, preset_files, snippet_file, CanonicalArtifacts, ImportInputs,
+    ImportSource, Importer,
+};
+
+pub(super) struct CopilotImporter;
+
+impl Importer for CopilotImporter {
+    fn agent_id(&self) -> &'static str {
+        "copilot"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
+        let path = repo_root.join(".github/copilot-instructions.md");
+        if !path.is_file() {
+            return None;
+        }
+
+        let content = agents_core::fsutil::read_to_string(&path).ok()?;
+        Some(ImportInputs {
+            source_path: path.clone(),
+            sources: vec![ImportSource { path, content }],
+        })
+    }
+
+    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
+        let mut files = preset_files("copilot");
+
+        // Replace manifest with one that enables the copilot adapter.
+        files.push(import_manifest("copilot", "copilot-import", None));
+
+        // Import content as a snippet and provide an opt-in mode.
+        let content: Vec<&str> = inputs.sources.iter().map(|s| s.content.as_str()).collect();
+        files.push(snippet_file("copilot", &content.join("\n")));
+
+        files.push(import_mode(
+            "copilot-import",
+            "Copilot Import",
+            None,
+            &["copilot".to_string()],
+            "This mode includes imported Copilot instructions as a snippet (copilot).\n",
+        ));
+
+        Ok(CanonicalArtifacts {
+            files,
+            notes: vec![],
+        })
+    }
+}
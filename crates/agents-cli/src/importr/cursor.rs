@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use super::{
+    file_name, import_manifest, import_mode, preset_files, read_source, scope_file, snippet_file,
+    snippet_id, unique_id, CanonicalArtifacts, ImportInputs, Importer,
+};
+
+/// Imports `.cursor/rules/*.md(c)` and a legacy `.cursorrules` file.
+///
+/// Rules with `globs` become scopes that include the rule's snippet; `alwaysApply` rules and
+/// rules without globs are included by the import mode.
+pub(super) struct CursorImporter;
+
+impl Importer for CursorImporter {
+    fn agent_id(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
+        let rules_dir = repo_root.join(".cursor/rules");
+
+        let mut paths = vec![];
+        if let Ok(entries) = std::fs::read_dir(&rules_dir) {
+            for e in entries.flatten() {
+                let p = e.path();
+                let ext = p.extension().and_then(|x| x.to_str()).unwrap_or_default();
+                if ext == "md" || ext == "mdc" {
+                    paths.push(p);
+                }
+            }
+        }
+        paths.sort();
+        paths.push(repo_root.join(".cursorrules"));
+
+        let sources: Vec<_> = paths.iter().filter_map(|p| read_source(p)).collect();
+        if sources.is_empty() {
+            return None;
+        }
+
+        let source_path = if rules_dir.is_dir() {
+            rules_dir
+        } else {
+            sources[0].path.clone()
+        };
+
+        Some(ImportInputs {
+            source_path,
+            sources,
+        })
+    }
+
+    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
+        let mut files = preset_files("cursor");
+        files.push(import_manifest("cursor", "cursor-import", None));
+
+        let mut mode_snippets = vec![];
+        let mut taken: Vec<String> = vec![];
+        let mut notes = vec![];
+        for src in &inputs.sources {
+            let base = if file_name(&src.path) == ".cursorrules" {
+                "cursor-rules".to_string()
+            } else {
+                snippet_id("cursor", &src.path)
+            };
+            // `foo.md` and `foo.mdc` become `cursor-foo` and `cursor-foo-mdc`.
+            let ext = src
+                .path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase());
+            let id = unique_id(&base, ext.as_deref(), |id| !taken.iter().any(|t| t == id));
+            if id != base {
+                notes.push(format!(
+                    "cursor: {} imported as {id} ({base} is already taken)",
+                    file_name(&src.path)
+                ));
+            }
+            taken.push(id.clone());
+
+            let rule = parse_rule(&src.content);
+            files.push(snippet_file(&id, &rule.body));
+
+            if rule.globs.is_empty() || rule.always_apply {
+                mode_snippets.push(id);
+            } else {
+                files.push(scope_file(&id, &rule.globs, std::slice::from_ref(&id)));
+            }
+        }
+
+        files.push(import_mode(
+            "cursor-import",
+            "Cursor Import",
+            None,
+            &mode_snippets,
+            "This mode includes imported Cursor rules; glob-scoped rules are imported as scopes.\n",
+        ));
+
+        Ok(CanonicalArtifacts { files, notes })
+    }
+}
+
+#[derive(Debug, Default)]
+struct CursorRule {
+    globs: Vec<String>,
+    always_apply: bool,
+    body: String,
+}
+
+/// Parse Cursor rule frontmatter by line.
+///
+/// Cursor writes values like `globs: *.ts, src/**` unquoted, which is not valid YAML, so this
+/// does not go through serde_yaml.
+fn parse_rule(text: &str) -> CursorRule {
+    let text = text.replace("\r\n", "\n");
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return CursorRule {
+            body: text,
+            ..Default::default()
+        };
+    };
+    let Some((front, body)) = rest.split_once("\n---") else {
+        return CursorRule {
+            body: text.clone(),
+            ..Default::default()
+        };
+    };
+
+    let mut rule = CursorRule {
+        body: body.trim_start_matches('\n').to_string(),
+        ..Default::default()
+    };
+
+    for line in front.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "globs" => {
+                rule.globs = value
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .split(',')
+                    .map(|g| g.trim().trim_matches('"').trim_matches('\'').to_string())
+                    .filter(|g| !g.is_empty())
+                    .collect();
+            }
+            "alwaysApply" => rule.always_apply = value == "true",
+            _ => {}
+        }
+    }
+
+    rule
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use super::{
+    file_name, import_manifest, import_mode, parse_json_source, preset_files, read_source,
+    snippet_file, snippet_id, split_rule, string_list, CanonicalArtifacts, ImportInputs, Importer,
+    PolicyPatch,
+};
+
+/// Imports `.gemini/settings.json` and its context files (`GEMINI.md` by default).
+pub(super) struct GeminiImporter;
+
+impl Importer for GeminiImporter {
+    fn agent_id(&self) -> &'static str {
+        "gemini-cli"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
+        let settings = read_source(&repo_root.join(".gemini/settings.json"));
+
+        let mut context_files = settings
+            .as_ref()
+            .and_then(|s| parse_json_source(s).ok())
+            .map(|v| context_file_names(&v))
+            .unwrap_or_default();
+        if context_files.is_empty() {
+            context_files.push("GEMINI.md".to_string());
+        }
+
+        let mut sources = vec![];
+        sources.extend(settings);
+        for name in &context_files {
+            sources.extend(read_source(&repo_root.join(name)));
+        }
+
+        if sources.is_empty() {
+            return None;
+        }
+
+        Some(ImportInputs {
+            source_path: sources[0].path.clone(),
+            sources,
+        })
+    }
+
+    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
+        let mut patch = PolicyPatch::default();
+        let mut notes = vec![];
+        let mut snippets: Vec<(String, String)> = vec![];
+
+        for src in &inputs.sources {
+            if file_name(&src.path).ends_with(".json") {
+                let settings = parse_json_source(src)?;
+                map_settings(&settings, &mut patch, &mut notes);
+            } else {
+                snippets.push((snippet_id("gemini", &src.path), src.content.clone()));
+            }
+        }
+
+        let mut files = preset_files("gemini-cli");
+
+        let policy_id = (!patch.is_empty()).then_some("gemini-import");
+        if let Some(id) = policy_id {
+            files.push(patch.to_file(id, "Imported from .gemini/settings.json")?);
+        }
+
+        files.push(import_manifest("gemini-cli", "gemini-import", policy_id));
+
+        for (id, content) in &snippets {
+            files.push(snippet_file(id, content));
+        }
+        let ids: Vec<String> = snippets.into_iter().map(|(id, _)| id).collect();
+
+        files.push(import_mode(
+            "gemini-import",
+            "Gemini Import",
+            policy_id,
+            &ids,
+            "This mode includes imported Gemini CLI context and tool restrictions.\n",
+        ));
+
+        Ok(CanonicalArtifacts { files, notes })
+    }
+}
+
+/// `context.fileName` may be a string or a list of strings.
+fn context_file_names(settings: &serde_json::Value) -> Vec<String> {
+    match settings.get("context").and_then(|c| c.get("fileName")) {
+        Some(serde_json::Value::String(s)) => vec![s.clone()],
+        other => string_list(other),
+    }
+}
+
+fn map_settings(settings: &serde_json::Value, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    let Some(obj) = settings.as_object() else {
+        notes.push("gemini settings: expected a JSON object".to_string());
+        return;
+    };
+
+    for (key, value) in obj {
+        match key.as_str() {
+            "$schema" | "context" => {}
+            "excludeTools" => {
+                for tool in string_list(Some(value)) {
+                    map_excluded_tool(&tool, patch, notes);
+                }
+            }
+            "coreTools" => {
+                for tool in string_list(Some(value)) {
+                    if let ("run_shell_command" | "ShellTool", Some(cmd)) = split_rule(&tool) {
+                        patch.exec_allow.push(cmd.to_string());
+                    }
+                }
+            }
+            other => notes.push(format!("gemini settings: ignored key: {other}")),
+        }
+    }
+}
+
+/// Map one `excludeTools` entry, e.g. `run_shell_command(rm -rf)` or `web_fetch`.
+fn map_excluded_tool(tool: &str, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    match split_rule(tool) {
+        ("run_shell_command" | "ShellTool", Some(cmd)) => patch.exec_deny.push(cmd.to_string()),
+        ("run_shell_command" | "ShellTool", None) => patch.exec_enabled = Some(false),
+        ("write_file" | "replace" | "WriteFileTool" | "EditTool", None) => {
+            patch.fs_write = Some(false)
+        }
+        ("web_fetch" | "WebFetchTool", None) => patch.network_enabled = Some(false),
+        _ => notes.push(format!("gemini excludeTools: unmapped tool: {tool}")),
+    }
+}
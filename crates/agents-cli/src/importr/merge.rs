@@ -0,0 +1,423 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use agents_core::loadag::{load_repo_config, LoaderOptions, RepoConfig};
+
+use crate::{AppError, ErrorCategory};
+
+use super::{normalize_lf, unique_id, write_file, CanonicalArtifacts, CanonicalFile};
+
+const SNIPPETS_DIR: &str = ".agents/prompts/snippets/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeAction {
+    /// New file; written as-is.
+    Add,
+    /// Existing mode whose `includeSnippets` gains the imported snippets.
+    Extend,
+    /// Existing file with identical content.
+    Unchanged,
+    /// Existing file or id with different content; left untouched.
+    Keep,
+}
+
+impl MergeAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            MergeAction::Add => "add",
+            MergeAction::Extend => "extend",
+            MergeAction::Unchanged => "unchanged",
+            MergeAction::Keep => "keep",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeEntry {
+    pub action: MergeAction,
+    pub file: CanonicalFile,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MergePlan {
+    pub entries: Vec<MergeEntry>,
+
+    /// Manifest changes the user has to make by hand (the manifest is never rewritten).
+    pub hints: Vec<String>,
+}
+
+impl MergePlan {
+    pub fn writes(&self) -> impl Iterator<Item = &CanonicalFile> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.action, MergeAction::Add | MergeAction::Extend))
+            .map(|e| &e.file)
+    }
+
+    fn count(&self, action: MergeAction) -> usize {
+        self.entries.iter().filter(|e| e.action == action).count()
+    }
+
+    pub fn print(&self) {
+        println!(
+            "merge: add={} extend={} unchanged={} keep={}",
+            self.count(MergeAction::Add),
+            self.count(MergeAction::Extend),
+            self.count(MergeAction::Unchanged),
+            self.count(MergeAction::Keep),
+        );
+        for e in &self.entries {
+            match &e.detail {
+                Some(d) => println!("{}: {} ({d})", e.action.as_str(), e.file.rel_path),
+                None => println!("{}: {}", e.action.as_str(), e.file.rel_path),
+            }
+        }
+        for h in &self.hints {
+            println!("hint: {h}");
+        }
+    }
+}
+
+/// Compare imported artifacts against an existing `.agents/`.
+///
+/// Only imported content is considered (snippets, modes, scopes, policies and adapter packs);
+/// preset files and the manifest of the greenfield import are ignored.
+pub fn plan_merge(
+    repo_root: &Path,
+    repo: &RepoConfig,
+    artifacts: &CanonicalArtifacts,
+) -> MergePlan {
+    let preset: Vec<&str> =
+        crate::initpr::assets::files_for_preset(crate::initpr::assets::InitPreset::Standard)
+            .into_iter()
+            .map(|f| f.rel_path)
+            .collect();
+
+    let mut plan = MergePlan::default();
+    let mut files: Vec<CanonicalFile> = artifacts
+        .files
+        .iter()
+        .filter(|f| is_import_content(&f.rel_path) && !preset.contains(&f.rel_path.as_str()))
+        .cloned()
+        .collect();
+    files.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    files.dedup_by(|a, b| a.rel_path == b.rel_path);
+
+    let renames = rename_conflicting_snippets(repo_root, repo, &mut files);
+    for f in &mut files {
+        rename_snippet_refs(f, &renames);
+    }
+
+    for f in &files {
+        let mut entry = plan_file(repo_root, repo, f);
+        let id = id_under(&f.rel_path, SNIPPETS_DIR, ".md");
+        if let Some((old, _)) = renames.iter().find(|(_, new)| Some(new.as_str()) == id) {
+            entry.detail = Some(format!("snippet `{old}` exists with different content"));
+        }
+        plan.entries.push(entry);
+    }
+
+    let enabled = &repo.manifest.enabled;
+    for e in &plan.entries {
+        if e.action != MergeAction::Add {
+            continue;
+        }
+        let rel = e.file.rel_path.as_str();
+        if let Some(id) = id_under(rel, ".agents/modes/", ".md") {
+            if !enabled.modes.iter().any(|m| m == id) {
+                plan.hints.push(format!(
+                    "add mode `{id}` to enabled.modes in .agents/manifest.yaml"
+                ));
+            }
+        } else if let Some(id) = id_under(rel, ".agents/policies/", ".yaml") {
+            if !enabled.policies.iter().any(|p| p == id) {
+                plan.hints.push(format!(
+                    "add policy `{id}` to enabled.policies in .agents/manifest.yaml"
+                ));
+            }
+        } else if let Some(id) = id_under(rel, ".agents/adapters/", "/adapter.yaml") {
+            if !enabled.adapters.iter().any(|a| a == id) {
+                plan.hints.push(format!(
+                    "add adapter `{id}` to enabled.adapters in .agents/manifest.yaml"
+                ));
+            }
+        }
+    }
+
+    plan
+}
+
+fn is_import_content(rel_path: &str) -> bool {
+    [
+        ".agents/prompts/snippets/",
+        ".agents/modes/",
+        ".agents/scopes/",
+        ".agents/policies/",
+        ".agents/adapters/",
+    ]
+    .iter()
+    .any(|p| rel_path.starts_with(p))
+}
+
+fn id_under<'a>(rel_path: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    rel_path.strip_prefix(prefix)?.strip_suffix(suffix)
+}
+
+fn plan_file(repo_root: &Path, repo: &RepoConfig, f: &CanonicalFile) -> MergeEntry {
+    let rel = f.rel_path.as_str();
+    let dest = repo_root.join(rel);
+
+    let entry = |action, detail: Option<String>| MergeEntry {
+        action,
+        file: f.clone(),
+        detail,
+    };
+
+    if dest.is_file() {
+        let existing = agents_core::fsutil::read_to_string(&dest).unwrap_or_default();
+        if normalize_lf(&existing).as_bytes() == f.contents.as_slice() {
+            return entry(MergeAction::Unchanged, None);
+        }
+
+        if rel.starts_with(".agents/modes/") {
+            if let Some(e) = extend_mode(&existing, f) {
+                return e;
+            }
+        }
+
+        return entry(
+            MergeAction::Keep,
+            Some("exists with different content".to_string()),
+        );
+    }
+
+    // The same id may already be defined in a differently named file.
+    let taken = if let Some(id) = id_under(rel, SNIPPETS_DIR, ".md") {
+        repo.prompts.snippets.contains_key(id).then_some("snippet")
+    } else if let Some(id) = id_under(rel, ".agents/modes/", ".md") {
+        repo.modes.contains_key(id).then_some("mode")
+    } else if let Some(id) = id_under(rel, ".agents/scopes/", ".yaml") {
+        repo.scopes.contains_key(id).then_some("scope")
+    } else if let Some(id) = id_under(rel, ".agents/policies/", ".yaml") {
+        repo.policies.contains_key(id).then_some("policy")
+    } else {
+        None
+    };
+    if let Some(kind) = taken {
+        return entry(
+            MergeAction::Keep,
+            Some(format!("{kind} id already defined")),
+        );
+    }
+
+    entry(MergeAction::Add, None)
+}
+
+/// Imported snippets whose id already holds different content get a fresh id (an existing
+/// snippet with the same content is reused), so imported modes and scopes keep pointing at the
+/// imported text. Returns old id -> new id.
+fn rename_conflicting_snippets(
+    repo_root: &Path,
+    repo: &RepoConfig,
+    files: &mut [CanonicalFile],
+) -> BTreeMap<String, String> {
+    let imported: Vec<String> = files
+        .iter()
+        .filter_map(|f| id_under(&f.rel_path, SNIPPETS_DIR, ".md"))
+        .map(str::to_string)
+        .collect();
+
+    let mut renames = BTreeMap::new();
+    for f in files.iter_mut() {
+        let Some(id) = id_under(&f.rel_path, SNIPPETS_DIR, ".md").map(str::to_string) else {
+            continue;
+        };
+        let usable = |candidate: &str| {
+            if candidate != id && imported.iter().any(|i| i == candidate) {
+                return false;
+            }
+            let dest = repo_root.join(format!("{SNIPPETS_DIR}{candidate}.md"));
+            if dest.is_file() {
+                let existing = agents_core::fsutil::read_to_string(&dest).unwrap_or_default();
+                return normalize_lf(&existing).as_bytes() == f.contents.as_slice();
+            }
+            !repo.prompts.snippets.contains_key(candidate)
+        };
+        let fresh = unique_id(&id, None, usable);
+        if fresh != id {
+            f.rel_path = format!("{SNIPPETS_DIR}{fresh}.md");
+            renames.insert(id, fresh);
+        }
+    }
+    renames
+}
+
+/// Point an imported mode's or scope's `includeSnippets` at renamed snippets.
+fn rename_snippet_refs(f: &mut CanonicalFile, renames: &BTreeMap<String, String>) {
+    if renames.is_empty() {
+        return;
+    }
+
+    let rename = |list: Option<&mut serde_yaml::Value>| {
+        for item in list.and_then(|l| l.as_sequence_mut()).into_iter().flatten() {
+            if let Some(new) = item.as_str().and_then(|id| renames.get(id)) {
+                *item = serde_yaml::Value::String(new.clone());
+            }
+        }
+    };
+
+    let text = String::from_utf8_lossy(&f.contents).into_owned();
+    let rewritten = if f.rel_path.starts_with(".agents/modes/") {
+        edit_frontmatter(&text, |fm| rename(fm.get_mut("includeSnippets")))
+    } else if f.rel_path.starts_with(".agents/scopes/") {
+        serde_yaml::from_str::<serde_yaml::Value>(&text)
+            .ok()
+            .and_then(|mut scope| {
+                rename(
+                    scope
+                        .get_mut("overrides")
+                        .and_then(|o| o.get_mut("includeSnippets")),
+                );
+                let by_agent = scope.get_mut("byAgent").and_then(|b| b.as_mapping_mut());
+                for o in by_agent.into_iter().flat_map(|b| b.values_mut()) {
+                    rename(o.get_mut("includeSnippets"));
+                }
+                serde_yaml::to_string(&scope).ok()
+            })
+    } else {
+        None
+    };
+    if let Some(text) = rewritten {
+        f.contents = text.into_bytes();
+    }
+}
+
+/// `text` with its YAML frontmatter parsed, edited and serialized again; the body is kept as
+/// written. `None` when there is no frontmatter mapping.
+fn edit_frontmatter(text: &str, edit: impl FnOnce(&mut serde_yaml::Mapping)) -> Option<String> {
+    let text = normalize_lf(text);
+    let rest = text.strip_prefix("---\n")?;
+    let end = rest.find("\n---\n")?;
+    let mut fm: serde_yaml::Mapping = serde_yaml::from_str(&rest[..end]).ok()?;
+    edit(&mut fm);
+    let yaml = serde_yaml::to_string(&fm).ok()?;
+    Some(format!("---\n{yaml}---\n{}", &rest[end + 5..]))
+}
+
+/// Add imported snippets to an existing mode's `includeSnippets`, leaving the body as written.
+/// Returns `None` when there is nothing to add.
+fn extend_mode(existing: &str, imported: &CanonicalFile) -> Option<MergeEntry> {
+    let imported_text = String::from_utf8_lossy(&imported.contents);
+    let (imported_fm, _) = agents_core::model::parse_frontmatter_markdown(&imported_text).ok()?;
+    let (existing_fm, _) = agents_core::model::parse_frontmatter_markdown(existing).ok()?;
+    let existing_fm = existing_fm?;
+
+    let mut snippets = existing_fm.include_snippets.clone();
+    let mut added = vec![];
+    for s in imported_fm?.include_snippets {
+        if !snippets.contains(&s) {
+            snippets.push(s.clone());
+            added.push(s);
+        }
+    }
+    if added.is_empty() {
+        return None;
+    }
+
+    let text = edit_frontmatter(existing, |fm| {
+        let list = snippets
+            .into_iter()
+            .map(serde_yaml::Value::String)
+            .collect();
+        fm.insert(
+            serde_yaml::Value::String("includeSnippets".to_string()),
+            serde_yaml::Value::Sequence(list),
+        );
+    })?;
+
+    Some(MergeEntry {
+        action: MergeAction::Extend,
+        file: CanonicalFile {
+            rel_path: imported.rel_path.clone(),
+            contents: text.into_bytes(),
+        },
+        detail: Some(format!("includeSnippets += {}", added.join(", "))),
+    })
+}
+
+/// Apply `plan` to a scratch copy of `.agents/` and validate it, so nothing is written to the
+/// repo unless the merged result loads and passes schema validation.
+pub fn validate_merge(repo_root: &Path, plan: &MergePlan) -> Result<(), AppError> {
+    let tmp = tempfile::tempdir().map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec!["while staging merge".to_string()],
+    })?;
+    let stage = tmp.path();
+
+    let agents_dir = repo_root.join(".agents");
+    for entry in walkdir::WalkDir::new(&agents_dir) {
+        let entry = entry.map_err(|e| AppError {
+            category: ErrorCategory::Io,
+            message: e.to_string(),
+            context: vec![format!("path: {}", agents_dir.display())],
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(repo_root).unwrap_or(entry.path());
+        let bytes = agents_core::fsutil::read_bytes(entry.path()).map_err(|e| AppError {
+            category: ErrorCategory::Io,
+            message: e.to_string(),
+            context: vec![format!("path: {}", entry.path().display())],
+        })?;
+        write_file(stage, &rel.to_string_lossy(), &bytes)?;
+    }
+
+    // Validate against the bundled schemas when the repo does not carry its own.
+    for f in crate::initpr::assets::files_for_preset(crate::initpr::assets::InitPreset::Standard) {
+        if f.rel_path.starts_with(".agents/schemas/") && !stage.join(f.rel_path).is_file() {
+            write_file(stage, f.rel_path, f.contents.as_bytes())?;
+        }
+    }
+
+    for f in plan.writes() {
+        write_file(stage, &f.rel_path, &f.contents)?;
+    }
+
+    let (cfg, _report) = load_repo_config(
+        stage,
+        &LoaderOptions {
+            require_schemas_dir: true,
+        },
+    )
+    .map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: format!("merged config invalid: {e}"),
+        context: vec!["hint: nothing was written".to_string()],
+    })?;
+
+    agents_core::schemas::validate_repo_config(stage, &cfg).map_err(|err| {
+        let rel = err
+            .path
+            .strip_prefix(stage)
+            .unwrap_or(&err.path)
+            .display()
+            .to_string();
+        AppError {
+            category: ErrorCategory::SchemaInvalid,
+            message: format!("schema invalid: {rel} ({})", err.schema),
+            context: {
+                let mut c = vec![format!("pointer: {}", err.pointer), err.message];
+                if let Some(h) = err.hint {
+                    c.push(h);
+                }
+                c.push("hint: nothing was written".to_string());
+                c
+            },
+        }
+    })?;
+
+    Ok(())
+}
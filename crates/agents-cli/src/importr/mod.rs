@@ -1,11 +1,37 @@
 use std::path::{Path, PathBuf};
 
+use agents_core::model::ConfirmationType;
+use agents_core::resolv::{ResolutionRequest, Resolver};
+
+use crate::confrm::{self, ConfirmOptions, Gate};
 use crate::{AppError, ErrorCategory};
 
+mod claude;
+mod codex;
+mod copilot;
+mod cursor;
+mod gemini;
+mod merge;
+mod opencode;
+
+use claude::ClaudeImporter;
+use codex::CodexImporter;
+use copilot::CopilotImporter;
+use cursor::CursorImporter;
+use gemini::GeminiImporter;
+use opencode::OpenCodeImporter;
+
+#[derive(Debug, Clone)]
+pub struct ImportSource {
+    pub path: PathBuf,
+    pub content: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ImportInputs {
+    /// File or directory reported as the import source.
     pub source_path: PathBuf,
-    pub content: String,
+    pub sources: Vec<ImportSource>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,6 +43,9 @@ pub struct CanonicalFile {
 #[derive(Debug, Clone)]
 pub struct CanonicalArtifacts {
     pub files: Vec<CanonicalFile>,
+
+    /// Settings that were recognized but could not be mapped.
+    pub notes: Vec<String>,
 }
 
 pub trait Importer {
@@ -32,33 +61,60 @@ pub struct ImportOptions {
     pub from_agent: String,
     pub path: Option<PathBuf>,
     pub dry_run: bool,
+
+    /// Add new content to an existing `.agents/` instead of requiring an empty one.
+    pub merge: bool,
+
+    /// Policy confirmations for `--merge` rewriting existing mode files (`overwrite`).
+    pub confirm: ConfirmOptions,
+}
+
+const SUPPORTED_SOURCES: &str = "claude, codex, copilot, cursor, gemini, opencode";
+
+fn importer_for(from: &str) -> Option<Box<dyn Importer>> {
+    match from {
+        "claude" => Some(Box::new(ClaudeImporter)),
+        "codex" | "agents-md" => Some(Box::new(CodexImporter)),
+        "copilot" => Some(Box::new(CopilotImporter)),
+        "cursor" => Some(Box::new(CursorImporter)),
+        "gemini" | "gemini-cli" => Some(Box::new(GeminiImporter)),
+        "opencode" => Some(Box::new(OpenCodeImporter)),
+        _ => None,
+    }
 }
 
 pub fn cmd_import(repo_root: &Path, opts: ImportOptions) -> Result<(), AppError> {
     let from = opts.from_agent.trim();
 
-    let importer: Box<dyn Importer> = match from {
-        "copilot" => Box::new(CopilotImporter),
-        _ => {
-            return Err(AppError {
-                category: ErrorCategory::InvalidArgs,
-                message: "unsupported import source".to_string(),
-                context: vec![
-                    format!("from: {from}"),
-                    "hint: supported: copilot".to_string(),
-                ],
-            })
-        }
+    let Some(importer) = importer_for(from) else {
+        return Err(AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: "unsupported import source".to_string(),
+            context: vec![
+                format!("from: {from}"),
+                format!("hint: supported: {SUPPORTED_SOURCES}"),
+            ],
+        });
     };
 
     let inputs = resolve_import_inputs(repo_root, importer.as_ref(), opts.path.as_deref())?;
 
+    if opts.merge {
+        return cmd_import_merge(
+            repo_root,
+            importer.as_ref(),
+            inputs,
+            opts.dry_run,
+            opts.confirm,
+        );
+    }
+
     ensure_agents_not_initialized(repo_root)?;
 
     let artifacts = importer.convert(inputs.clone()).map_err(|e| AppError {
         category: ErrorCategory::Io,
         message: e,
-        context: vec![],
+        context: vec![format!("source: {}", inputs.source_path.display())],
     })?;
 
     if opts.dry_run {
@@ -68,11 +124,13 @@ pub fn cmd_import(repo_root: &Path, opts: ImportOptions) -> Result<(), AppError>
             .map(|f| f.rel_path.as_str())
             .collect();
         paths.sort();
+        paths.dedup();
         println!("source: {}", inputs.source_path.display());
         println!("dry-run: would write {} files", paths.len());
         for p in paths {
             println!("write: {p}");
         }
+        print_notes(&artifacts.notes);
         return Ok(());
     }
 
@@ -108,104 +166,358 @@ pub fn cmd_import(repo_root: &Path, opts: ImportOptions) -> Result<(), AppError>
     println!("ok: imported into .agents/ (from: {})", importer.agent_id());
     println!("source: {}", inputs.source_path.display());
     println!("ok: schemas valid");
-    println!("next: run `agents status` and `agents preview --agent copilot`");
+    print_notes(&artifacts.notes);
+    println!(
+        "next: run `agents status` and `agents preview --agent {}`",
+        importer.agent_id()
+    );
 
     Ok(())
 }
 
-struct CopilotImporter;
+fn cmd_import_merge(
+    repo_root: &Path,
+    importer: &dyn Importer,
+    inputs: ImportInputs,
+    dry_run: bool,
+    confirm: ConfirmOptions,
+) -> Result<(), AppError> {
+    let (repo, _report) = agents_core::loadag::load_repo_config(
+        repo_root,
+        &agents_core::loadag::LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .map_err(|e| match e {
+        agents_core::loadag::LoadError::NotInitialized { .. } => AppError {
+            category: ErrorCategory::NotInitialized,
+            message: "cannot merge: .agents is not initialized".to_string(),
+            context: vec![
+                "hint: run without --merge to create .agents/ from the import".to_string(),
+            ],
+        },
+        other => AppError {
+            category: ErrorCategory::Io,
+            message: other.to_string(),
+            context: vec![],
+        },
+    })?;
+
+    let artifacts = importer.convert(inputs.clone()).map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: e,
+        context: vec![format!("source: {}", inputs.source_path.display())],
+    })?;
+
+    let plan = merge::plan_merge(repo_root, &repo, &artifacts);
 
-impl Importer for CopilotImporter {
-    fn agent_id(&self) -> &'static str {
-        "copilot"
+    println!("source: {}", inputs.source_path.display());
+    if dry_run {
+        println!("dry-run: would write {} files", plan.writes().count());
+    }
+    plan.print();
+    print_notes(&artifacts.notes);
+    if dry_run {
+        return Ok(());
     }
 
-    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
-        let path = repo_root.join(".github/copilot-instructions.md");
-        if !path.is_file() {
-            return None;
+    merge::validate_merge(repo_root, &plan)?;
+
+    // Extending a mode rewrites a file the user owns.
+    let extended: Vec<&str> = plan
+        .entries
+        .iter()
+        .filter(|e| e.action == merge::MergeAction::Extend)
+        .map(|e| e.file.rel_path.as_str())
+        .collect();
+    if !extended.is_empty() {
+        let effective = Resolver::new(repo.clone())
+            .resolve(&ResolutionRequest {
+                repo_root: repo_root.to_path_buf(),
+                ..Default::default()
+            })
+            .map_err(|e| AppError {
+                category: ErrorCategory::Io,
+                message: e.to_string(),
+                context: vec![],
+            })?;
+        if let Some(policy) = effective.policy(&repo) {
+            confrm::require(
+                repo_root,
+                policy,
+                &Gate {
+                    command: "import",
+                    action: format!("overwrite {}", extended.join(", ")),
+                    kinds: vec![ConfirmationType::Overwrite],
+                },
+                confirm,
+            )?;
         }
+    }
 
-        let content = agents_core::fsutil::read_to_string(&path).ok()?;
-        Some(ImportInputs {
-            source_path: path,
-            content,
-        })
+    for f in plan.writes() {
+        write_file(repo_root, &f.rel_path, &f.contents)?;
     }
 
-    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
-        let mut files: Vec<CanonicalFile> = vec![];
+    println!("ok: merged into .agents/ (from: {})", importer.agent_id());
+    println!("ok: schemas valid");
+
+    Ok(())
+}
 
-        // Start from the standard preset.
-        let base =
-            crate::initpr::assets::files_for_preset(crate::initpr::assets::InitPreset::Standard);
-        for f in base {
-            files.push(CanonicalFile {
-                rel_path: f.rel_path.to_string(),
-                contents: normalize_lf(f.contents).into_bytes(),
-            });
-        }
+fn print_notes(notes: &[String]) {
+    for n in notes {
+        println!("note: {n}");
+    }
+}
 
-        // Ensure Copilot adapter is present.
-        files.push(CanonicalFile {
-            rel_path: ".agents/adapters/copilot/adapter.yaml".to_string(),
-            contents: normalize_lf(include_str!(
-                "../initpr/assets/agent-pack/adapters/copilot/adapter.yaml"
-            ))
-            .into_bytes(),
-        });
+/// Canonical files shared by every importer: the standard preset plus one adapter pack.
+fn preset_files(adapter: &str) -> Vec<CanonicalFile> {
+    let mut files = vec![];
+    let base = crate::initpr::assets::files_for_preset(crate::initpr::assets::InitPreset::Standard);
+    for f in base
+        .into_iter()
+        .chain(crate::initpr::assets::adapter_files(adapter))
+    {
         files.push(CanonicalFile {
-            rel_path: ".agents/adapters/copilot/templates/copilot-instructions.md.hbs".to_string(),
-            contents: normalize_lf(include_str!(
-                "../initpr/assets/agent-pack/adapters/copilot/templates/copilot-instructions.md.hbs"
-            ))
-            .into_bytes(),
-        });
-        files.push(CanonicalFile {
-            rel_path: ".agents/adapters/copilot/templates/scope.instructions.md.hbs".to_string(),
-            contents: normalize_lf(include_str!(
-                "../initpr/assets/agent-pack/adapters/copilot/templates/scope.instructions.md.hbs"
-            ))
-            .into_bytes(),
+            rel_path: f.rel_path.to_string(),
+            contents: normalize_lf(f.contents).into_bytes(),
         });
+    }
+    files
+}
 
-        // Replace manifest with one that enables the copilot adapter.
-        files.push(CanonicalFile {
-            rel_path: ".agents/manifest.yaml".to_string(),
-            contents: normalize_lf(COPILOT_IMPORT_MANIFEST).into_bytes(),
-        });
+/// Manifest based on the standard preset that also enables the imported adapter, mode and policy.
+fn import_manifest(adapter: &str, mode_id: &str, policy_id: Option<&str>) -> CanonicalFile {
+    let mut policies = vec!["safe", "conservative", "ci-safe"];
+    policies.extend(policy_id);
+
+    let text = format!(
+        "specVersion: '0.1'\n\
+defaults: {{ mode: default, policy: safe, backend: materialize, sharedSurfacesOwner: core }}\n\
+enabled: {{ modes: [default, readonly-audit, {mode_id}], policies: [{}], skills: [], adapters: [core, {adapter}] }}\n",
+        policies.join(", ")
+    );
+
+    CanonicalFile {
+        rel_path: ".agents/manifest.yaml".to_string(),
+        contents: text.into_bytes(),
+    }
+}
 
-        // Import content as a snippet and provide an opt-in mode.
-        let mut snippet_md = inputs.content;
-        if !snippet_md.ends_with('\n') {
-            snippet_md.push('\n');
+/// Opt-in mode that pulls in the imported snippets (and policy, when one was derived).
+fn import_mode(
+    mode_id: &str,
+    title: &str,
+    policy_id: Option<&str>,
+    snippets: &[String],
+    body: &str,
+) -> CanonicalFile {
+    let mut text = format!("---\nid: {mode_id}\ntitle: {title}\n");
+    if let Some(p) = policy_id {
+        text.push_str(&format!("policy: {p}\n"));
+    }
+    text.push_str(&format!(
+        "includeSnippets: [{}]\n---\n\n{body}",
+        snippets.join(", ")
+    ));
+
+    CanonicalFile {
+        rel_path: format!(".agents/modes/{mode_id}.md"),
+        contents: text.into_bytes(),
+    }
+}
+
+fn snippet_file(id: &str, content: &str) -> CanonicalFile {
+    let mut md = normalize_lf(content);
+    if !md.ends_with('\n') {
+        md.push('\n');
+    }
+
+    CanonicalFile {
+        rel_path: format!(".agents/prompts/snippets/{id}.md"),
+        contents: md.into_bytes(),
+    }
+}
+
+fn scope_file(id: &str, apply_to: &[String], snippets: &[String]) -> CanonicalFile {
+    // JSON string arrays are valid YAML flow sequences and quote globs safely.
+    let apply_to = serde_json::to_string(apply_to).unwrap_or_else(|_| "[]".to_string());
+    let snippets = serde_json::to_string(snippets).unwrap_or_else(|_| "[]".to_string());
+
+    CanonicalFile {
+        rel_path: format!(".agents/scopes/{id}.yaml"),
+        contents: format!(
+            "id: {id}\napplyTo: {apply_to}\npriority: 0\noverrides:\n  includeSnippets: {snippets}\n"
+        )
+        .into_bytes(),
+    }
+}
+
+/// Lowercase kebab-case id derived from a file name.
+fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
         }
-        files.push(CanonicalFile {
-            rel_path: ".agents/prompts/snippets/copilot.md".to_string(),
-            contents: normalize_lf(&snippet_md).into_bytes(),
-        });
+    }
+    out.trim_matches('-').to_string()
+}
 
-        files.push(CanonicalFile {
-            rel_path: ".agents/modes/copilot-import.md".to_string(),
-            contents: normalize_lf(COPILOT_IMPORT_MODE).into_bytes(),
-        });
+/// `base` if `usable`, else the first usable of `base-<hint>` and `base-2`, `base-3`, ...
+fn unique_id(base: &str, hint: Option<&str>, usable: impl Fn(&str) -> bool) -> String {
+    std::iter::once(base.to_string())
+        .chain(hint.map(|h| format!("{base}-{h}")))
+        .chain((2..).map(|n| format!("{base}-{n}")))
+        .find(|id| usable(id))
+        .expect("numbered ids are unbounded")
+}
+
+/// Snippet id for a source file: `<prefix>` when the stem matches it, else `<prefix>-<stem>`.
+fn snippet_id(prefix: &str, path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| slug(&s.to_string_lossy()))
+        .unwrap_or_default();
+    if stem.is_empty() || stem == prefix {
+        prefix.to_string()
+    } else {
+        format!("{prefix}-{stem}")
+    }
+}
+
+/// Read a discovered source, skipping files that agents generated itself.
+fn read_source(path: &Path) -> Option<ImportSource> {
+    if !path.is_file() {
+        return None;
+    }
 
-        Ok(CanonicalArtifacts { files })
+    let content = agents_core::fsutil::read_to_string(path).ok()?;
+    if agents_core::stamps::parse_stamp(&content).is_some() {
+        return None;
     }
+
+    Some(ImportSource {
+        path: path.to_path_buf(),
+        content,
+    })
 }
 
-const COPILOT_IMPORT_MANIFEST: &str = "specVersion: '0.1'\n\
-defaults: { mode: default, policy: safe, backend: materialize, sharedSurfacesOwner: core }\n\
-enabled: { modes: [default, readonly-audit, copilot-import], policies: [safe, conservative, ci-safe], skills: [], adapters: [core, copilot] }\n";
+fn parse_json_source(src: &ImportSource) -> Result<serde_json::Value, String> {
+    let text = agents_core::outputs::strip_jsonc(&src.content);
+    serde_json::from_str(&text).map_err(|e| format!("{}: invalid json: {e}", src.path.display()))
+}
 
-const COPILOT_IMPORT_MODE: &str = "---\n\
-id: copilot-import\n\
-title: Copilot Import\n\
-includeSnippets: [copilot]\n\
----\n\
-\n\
-This mode includes imported Copilot instructions as a snippet (copilot).\n\
-";
+fn string_list(v: Option<&serde_json::Value>) -> Vec<String> {
+    v.and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|x| x.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Split `Tool(spec)` into `("Tool", Some("spec"))`; a bare `Tool` has no spec.
+fn split_rule(rule: &str) -> (&str, Option<&str>) {
+    let rule = rule.trim();
+    match rule.split_once('(') {
+        Some((tool, rest)) if rest.ends_with(')') => {
+            let spec = rest[..rest.len() - 1].trim();
+            (tool.trim(), (!spec.is_empty()).then_some(spec))
+        }
+        _ => (rule, None),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Structured agent settings mapped onto a policy derived from the `safe` preset policy.
+#[derive(Debug, Default)]
+struct PolicyPatch {
+    paths_allow: Vec<String>,
+    paths_deny: Vec<String>,
+    paths_redact: Vec<String>,
+    fs_write: Option<bool>,
+    exec_enabled: Option<bool>,
+    exec_allow: Vec<String>,
+    exec_deny: Vec<String>,
+    network_enabled: Option<bool>,
+    allow_hosts: Vec<String>,
+    deny_hosts: Vec<String>,
+}
+
+impl PolicyPatch {
+    fn is_empty(&self) -> bool {
+        self.paths_allow.is_empty()
+            && self.paths_deny.is_empty()
+            && self.paths_redact.is_empty()
+            && self.fs_write.is_none()
+            && self.exec_enabled.is_none()
+            && self.exec_allow.is_empty()
+            && self.exec_deny.is_empty()
+            && self.network_enabled.is_none()
+            && self.allow_hosts.is_empty()
+            && self.deny_hosts.is_empty()
+    }
+
+    fn to_file(&self, id: &str, description: &str) -> Result<CanonicalFile, String> {
+        let mut v: serde_yaml::Value =
+            serde_yaml::from_str(include_str!("../initpr/assets/common/policies/safe.yaml"))
+                .map_err(|e| format!("invalid base policy: {e}"))?;
+
+        v["id"] = id.into();
+        v["description"] = description.into();
+
+        union_list(&mut v["paths"]["allow"], &self.paths_allow);
+        union_list(&mut v["paths"]["deny"], &self.paths_deny);
+        union_list(&mut v["paths"]["redact"], &self.paths_redact);
+
+        let caps = &mut v["capabilities"];
+        if let Some(b) = self.fs_write {
+            caps["filesystem"]["write"] = b.into();
+        }
+        if let Some(b) = self.exec_enabled {
+            caps["exec"]["enabled"] = b.into();
+        }
+        union_list(&mut caps["exec"]["allow"], &self.exec_allow);
+        union_list(&mut caps["exec"]["deny"], &self.exec_deny);
+        if let Some(b) = self.network_enabled {
+            caps["network"]["enabled"] = b.into();
+        }
+        union_list(&mut caps["network"]["allowHosts"], &self.allow_hosts);
+        union_list(&mut caps["network"]["denyHosts"], &self.deny_hosts);
+
+        let text = serde_yaml::to_string(&v).map_err(|e| format!("policy serialize error: {e}"))?;
+
+        Ok(CanonicalFile {
+            rel_path: format!(".agents/policies/{id}.yaml"),
+            contents: text.into_bytes(),
+        })
+    }
+}
+
+fn union_list(target: &mut serde_yaml::Value, items: &[String]) {
+    if !target.is_sequence() {
+        *target = serde_yaml::Value::Sequence(vec![]);
+    }
+    let Some(seq) = target.as_sequence_mut() else {
+        return;
+    };
+    for i in items {
+        let item = serde_yaml::Value::from(i.as_str());
+        if !seq.contains(&item) {
+            seq.push(item);
+        }
+    }
+}
 
 fn ensure_agents_not_initialized(repo_root: &Path) -> Result<(), AppError> {
     let manifest = repo_root.join(".agents/manifest.yaml");
@@ -215,7 +527,7 @@ fn ensure_agents_not_initialized(repo_root: &Path) -> Result<(), AppError> {
             message: "refusing to import: .agents already exists".to_string(),
             context: vec![
                 format!("path: {}", manifest.display()),
-                "hint: edit .agents manually or remove it before importing".to_string(),
+                "hint: pass --merge to add imported content to the existing .agents/".to_string(),
             ],
         });
     }
@@ -295,7 +607,7 @@ fn resolve_import_inputs(
     path_override: Option<&Path>,
 ) -> Result<ImportInputs, AppError> {
     if let Some(path) = path_override {
-        return read_import_path(repo_root, importer.agent_id(), path);
+        return read_import_path(repo_root, importer, path);
     }
 
     if let Some(inputs) = importer.discover(repo_root) {
@@ -314,15 +626,25 @@ fn resolve_import_inputs(
 
 fn read_import_path(
     repo_root: &Path,
-    agent_id: &str,
+    importer: &dyn Importer,
     path: &Path,
 ) -> Result<ImportInputs, AppError> {
+    let agent_id = importer.agent_id();
     let p = if path.is_absolute() {
         path.to_path_buf()
     } else {
         repo_root.join(path)
     };
 
+    // A directory is treated as the root to discover sources under.
+    if p.is_dir() {
+        return importer.discover(&p).ok_or_else(|| AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: format!("{} import source not found", agent_id),
+            context: vec![format!("path: {}", p.display())],
+        });
+    }
+
     if !p.is_file() {
         return Err(AppError {
             category: ErrorCategory::InvalidArgs,
@@ -344,7 +666,7 @@ fn read_import_path(
     })?;
 
     Ok(ImportInputs {
-        source_path: p,
-        content,
+        source_path: p.clone(),
+        sources: vec![ImportSource { path: p, content }],
     })
 }
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use super::{
+    file_name, import_manifest, import_mode, parse_json_source, preset_files, read_source,
+    snippet_file, snippet_id, string_list, CanonicalArtifacts, ImportInputs, Importer, PolicyPatch,
+};
+
+/// Imports `opencode.jsonc`/`opencode.json` and the instruction files it references.
+pub(super) struct OpenCodeImporter;
+
+impl Importer for OpenCodeImporter {
+    fn agent_id(&self) -> &'static str {
+        "opencode"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Option<ImportInputs> {
+        let config = ["opencode.jsonc", "opencode.json"]
+            .iter()
+            .find_map(|n| read_source(&repo_root.join(n)))?;
+
+        let mut sources = vec![config.clone()];
+
+        // Pull in instruction files that exist as plain paths; glob entries are left alone.
+        if let Ok(v) = parse_json_source(&config) {
+            for rel in string_list(v.get("instructions")) {
+                if rel.contains('*') {
+                    continue;
+                }
+                if let Some(src) = read_source(&repo_root.join(&rel)) {
+                    sources.push(src);
+                }
+            }
+        }
+
+        Some(ImportInputs {
+            source_path: config.path,
+            sources,
+        })
+    }
+
+    fn convert(&self, inputs: ImportInputs) -> Result<CanonicalArtifacts, String> {
+        let mut patch = PolicyPatch::default();
+        let mut notes = vec![];
+        let mut snippets: Vec<(String, String)> = vec![];
+
+        for src in &inputs.sources {
+            let name = file_name(&src.path);
+            if name.starts_with("opencode.json") {
+                let config = parse_json_source(src)?;
+                map_config(&config, &mut patch, &mut notes);
+            } else {
+                snippets.push((snippet_id("opencode", &src.path), src.content.clone()));
+            }
+        }
+
+        let mut files = preset_files("opencode");
+
+        let policy_id = (!patch.is_empty()).then_some("opencode-import");
+        if let Some(id) = policy_id {
+            files.push(patch.to_file(id, "Imported from opencode.jsonc")?);
+        }
+
+        files.push(import_manifest("opencode", "opencode-import", policy_id));
+
+        for (id, content) in &snippets {
+            files.push(snippet_file(id, content));
+        }
+        let ids: Vec<String> = snippets.into_iter().map(|(id, _)| id).collect();
+
+        files.push(import_mode(
+            "opencode-import",
+            "OpenCode Import",
+            policy_id,
+            &ids,
+            "This mode includes imported OpenCode instructions and permissions.\n",
+        ));
+
+        Ok(CanonicalArtifacts { files, notes })
+    }
+}
+
+fn map_config(config: &serde_json::Value, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    let Some(obj) = config.as_object() else {
+        notes.push("opencode config: expected a JSON object".to_string());
+        return;
+    };
+
+    for (key, value) in obj {
+        match key.as_str() {
+            "$schema" | "instructions" => {}
+            "permission" => map_permission(value, patch, notes),
+            other => notes.push(format!("opencode config: ignored key: {other}")),
+        }
+    }
+}
+
+/// Map the `permission` block (`edit`, `bash`, `webfetch`; each `allow`/`ask`/`deny`).
+fn map_permission(value: &serde_json::Value, patch: &mut PolicyPatch, notes: &mut Vec<String>) {
+    let Some(obj) = value.as_object() else {
+        return;
+    };
+
+    for (tool, action) in obj {
+        match (tool.as_str(), action) {
+            ("edit", serde_json::Value::String(a)) if a == "deny" => patch.fs_write = Some(false),
+            ("bash", serde_json::Value::String(a)) if a == "deny" => {
+                patch.exec_enabled = Some(false)
+            }
+            ("bash", serde_json::Value::Object(rules)) => {
+                for (pattern, a) in rules {
+                    let cmd = pattern.trim_end_matches('*').trim().to_string();
+                    match a.as_str() {
+                        Some("deny") if cmd.is_empty() => patch.exec_enabled = Some(false),
+                        Some("deny") => patch.exec_deny.push(cmd),
+                        Some("allow") if !cmd.is_empty() => patch.exec_allow.push(cmd),
+                        _ => {}
+                    }
+                }
+            }
+            ("webfetch", serde_json::Value::String(a)) if a == "deny" => {
+                patch.network_enabled = Some(false)
+            }
+            ("webfetch", serde_json::Value::String(a)) if a == "allow" => {
+                patch.network_enabled = Some(true)
+            }
+            // `ask` maps onto the default confirmations; `allow` is the default otherwise.
+            (_, serde_json::Value::String(a)) if a == "ask" || a == "allow" => {}
+            _ => notes.push(format!("opencode permission: unmapped entry: {tool}")),
+        }
+    }
+}
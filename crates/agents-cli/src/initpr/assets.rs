@@ -81,6 +81,15 @@ pub fn files_for_preset(preset: InitPreset) -> Vec<EmbeddedFile> {
     out
 }
 
+/// Files for a single adapter from the agent pack (empty for unknown ids).
+pub fn adapter_files(agent_id: &str) -> Vec<EmbeddedFile> {
+    let prefix = format!(".agents/adapters/{agent_id}/");
+    agent_pack_adapters()
+        .into_iter()
+        .filter(|f| f.rel_path.starts_with(&prefix))
+        .collect()
+}
+
 fn file(rel_path: &'static str, contents: &'static str) -> EmbeddedFile {
     EmbeddedFile { rel_path, contents }
 }
@@ -139,6 +148,10 @@ fn common_files() -> Vec<EmbeddedFile> {
             ".agents/schemas/scope.schema.json",
             include_str!("assets/common/schemas/scope.schema.json"),
         ),
+        file(
+            ".agents/schemas/profile.schema.json",
+            include_str!("assets/common/schemas/profile.schema.json"),
+        ),
         file(
             ".agents/schemas/skill.schema.json",
             include_str!("assets/common/schemas/skill.schema.json"),
@@ -178,6 +191,10 @@ fn agent_pack_adapters() -> Vec<EmbeddedFile> {
             ".agents/adapters/cursor/templates/20-policy.md.hbs",
             include_str!("assets/agent-pack/adapters/cursor/templates/20-policy.md.hbs"),
         ),
+        file(
+            ".agents/adapters/cursor/templates/mcp.json.hbs",
+            include_str!("assets/agent-pack/adapters/cursor/templates/mcp.json.hbs"),
+        ),
         // Copilot
         file(
             ".agents/adapters/copilot/adapter.yaml",
@@ -233,6 +250,10 @@ fn agent_pack_adapters() -> Vec<EmbeddedFile> {
             ".agents/adapters/claude/templates/CLAUDE.md.hbs",
             include_str!("assets/agent-pack/adapters/claude/templates/CLAUDE.md.hbs"),
         ),
+        file(
+            ".agents/adapters/claude/templates/mcp.json.hbs",
+            include_str!("assets/agent-pack/adapters/claude/templates/mcp.json.hbs"),
+        ),
         // Codex (AGENTS.md)
         file(
             ".agents/adapters/codex/adapter.yaml",
@@ -10,14 +10,17 @@ mod main_tests;
 mod adtest;
 mod cleanup;
 mod compat;
+mod confrm;
 mod doctor;
 mod explnx;
 mod importr;
 mod initpr;
 mod prevdf;
 mod runner;
+mod statex;
 mod status;
 mod syncer;
+mod watchr;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum Backend {
@@ -68,6 +71,10 @@ struct Cli {
     #[arg(short, long, global = true, conflicts_with = "verbose")]
     quiet: bool,
 
+    /// Non-interactive: never prompt; policy confirmations fail unless `--yes` is given
+    #[arg(long, global = true)]
+    ci: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -82,11 +89,29 @@ enum Commands {
         #[arg(long)]
         profile: Option<String>,
     },
-    Status,
+    Status {
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Show which resolution layer set each value and what it overrode
+        #[arg(long)]
+        trace: bool,
+    },
+    /// Pin mode/profile/backend/scopes in .agents/state/state.yaml
     SetMode {
         mode: String,
         #[arg(long)]
         profile: Option<String>,
+        #[arg(long)]
+        backend: Option<Backend>,
+
+        /// Scope id to force (repeatable; replaces previously pinned scopes)
+        #[arg(long = "scope")]
+        scope: Vec<String>,
+    },
+    State {
+        #[command(subcommand)]
+        command: StateCommands,
     },
     Preview {
         #[arg(long)]
@@ -99,18 +124,40 @@ enum Commands {
         profile: Option<String>,
         #[arg(long, default_value_t = false)]
         keep_temp: bool,
+
+        /// Re-render into the same temp dir whenever `.agents/` or a renderer source changes
+        #[arg(long, default_value_t = false)]
+        watch: bool,
     },
     Diff {
         #[arg(long)]
         agent: Option<String>,
+        #[arg(long)]
+        profile: Option<String>,
         #[arg(long, default_value_t = false)]
         show: bool,
     },
     Sync {
+        /// Adapter to sync (repeatable; defaults to `core`)
         #[arg(long)]
-        agent: Option<String>,
+        agent: Vec<String>,
+
+        /// Sync every adapter in manifest.enabled.adapters in one transaction
+        #[arg(long, conflicts_with = "agent")]
+        all: bool,
+
         #[arg(long)]
         backend: Option<Backend>,
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Grant confirmations required by policy (e.g. overwrite) without prompting
+        #[arg(long, default_value_t = false)]
+        yes: bool,
+
+        /// Re-sync whenever `.agents/` or a renderer source changes
+        #[arg(long, default_value_t = false)]
+        watch: bool,
     },
     Run {
         agent: String,
@@ -125,14 +172,29 @@ enum Commands {
         profile: Option<String>,
         #[arg(long)]
         backend: Option<Backend>,
+
+        /// Print a unified diff of the agent's changes (vfs backends)
+        #[arg(long, default_value_t = false)]
+        diff: bool,
+
+        /// Write the agent's changes to a patch file (vfs backends)
+        #[arg(long)]
+        patch: Option<PathBuf>,
+
+        /// Apply the agent's changes back to the repo after confirmation (vfs backends)
+        #[arg(long, default_value_t = false)]
+        apply: bool,
+
+        /// Apply without prompting
+        #[arg(long, default_value_t = false)]
+        yes: bool,
+
         #[arg(trailing_var_arg = true)]
         passthrough: Vec<String>,
     },
     Doctor {
         #[arg(long)]
         fix: bool,
-        #[arg(long)]
-        ci: bool,
     },
     Clean {
         #[arg(long)]
@@ -152,6 +214,14 @@ enum Commands {
 
         #[arg(long, default_value_t = false)]
         dry_run: bool,
+
+        /// Merge into an existing .agents/ (adds new snippets, modes, scopes; never overwrites)
+        #[arg(long, default_value_t = false)]
+        merge: bool,
+
+        /// Grant confirmations required by policy without prompting
+        #[arg(long, default_value_t = false)]
+        yes: bool,
     },
     Explain {
         path: PathBuf,
@@ -161,6 +231,14 @@ enum Commands {
         #[command(subcommand)]
         command: TestCommands,
     },
+    /// Run a `cli_shim` skill (called by the generated `.agents-bin/` wrappers)
+    #[command(hide = true)]
+    ShimExec {
+        spec: PathBuf,
+
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -174,6 +252,14 @@ enum TestCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum StateCommands {
+    /// Print the persisted state
+    Show,
+    /// Remove .agents/state/state.yaml
+    Clear,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum OutputMode {
     Human,
@@ -186,6 +272,9 @@ struct AppContext {
     output: OutputMode,
     verbose: bool,
     quiet: bool,
+
+    /// `--ci`: never prompt.
+    ci: bool,
 }
 
 #[derive(Debug)]
@@ -198,6 +287,9 @@ pub(crate) enum ErrorCategory {
     PolicyDenied,
     ExternalToolMissing,
 
+    /// A policy `limits` entry was exceeded during `agents run`.
+    LimitExceeded,
+
     /// Used to propagate an external process exit code (e.g., `agents run`).
     AgentExit {
         code: i32,
@@ -226,11 +318,45 @@ impl AppError {
         }
     }
 
+    fn schema_invalid(err: agents_core::schemas::SchemaInvalid) -> Self {
+        AppError {
+            category: ErrorCategory::SchemaInvalid,
+            message: format!("schema invalid: {} ({})", err.path.display(), err.schema),
+            context: {
+                let mut c = vec![format!("pointer: {}", err.pointer), err.message];
+                if let Some(h) = err.hint {
+                    c.push(h);
+                }
+                c
+            },
+        }
+    }
+
+    fn pipeline(err: agents_core::pipeline::PipelineError) -> Self {
+        use agents_core::pipeline::PipelineError;
+
+        let category = match err {
+            PipelineError::CrossAdapterPathCollision { .. }
+            | PipelineError::CrossAdapterSurfaceCollision { .. } => ErrorCategory::Conflict,
+            _ => ErrorCategory::Io,
+        };
+
+        AppError {
+            category,
+            context: err
+                .path()
+                .map(|p| vec![format!("path: {p}")])
+                .unwrap_or_default(),
+            message: err.to_string(),
+        }
+    }
+
     pub(crate) fn exit_code(&self) -> i32 {
         match self.category {
             ErrorCategory::InvalidArgs => 2,
             ErrorCategory::NotInitialized => 3,
             ErrorCategory::SchemaInvalid => 4,
+            ErrorCategory::LimitExceeded => 6,
 
             ErrorCategory::AgentExit { code } => code,
 
@@ -256,6 +382,22 @@ impl std::error::Error for AppError {}
 
 type AppResult<T> = Result<T, AppError>;
 
+/// Merge the user overlay (`$HOME/.agents`) into `repo` when `resolution.enableUserOverlay`
+/// allows it, so every command resolves, plans and validates against the same ids.
+pub(crate) fn apply_user_overlay(repo: &mut agents_core::loadag::RepoConfig) -> AppResult<()> {
+    if !repo.user_overlay_enabled() {
+        return Ok(());
+    }
+    let Some(dir) = agents_core::loadag::user_overlay_dir(None) else {
+        return Ok(());
+    };
+    agents_core::loadag::apply_user_overlay(repo, &dir).map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: format!("user overlay: {e}"),
+        context: vec![format!("path: {}", dir.display())],
+    })
+}
+
 fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
     match cmd {
         Commands::Init { preset } => {
@@ -266,17 +408,21 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
             from_agent,
             path,
             dry_run,
+            merge,
+            yes,
         } => crate::importr::cmd_import(
             &ctx.repo_root,
             crate::importr::ImportOptions {
                 from_agent,
                 path,
                 dry_run,
+                merge,
+                confirm: crate::confrm::ConfirmOptions { yes, ci: ctx.ci },
             },
         ),
 
         Commands::Validate { .. } => cmd_validate(ctx),
-        Commands::Status => cmd_status(ctx),
+        Commands::Status { profile, trace } => cmd_status(ctx, profile, trace),
 
         Commands::Preview {
             agent,
@@ -284,6 +430,7 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
             mode,
             profile,
             keep_temp,
+            watch,
         } => {
             let agent = agent.unwrap_or_else(|| "core".to_string());
             let backend = backend.map(|b| map_backend(b, ctx.verbose));
@@ -296,24 +443,46 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
                     mode,
                     profile,
                     keep_temp,
+                    watch,
                 },
             )
         }
 
-        Commands::Diff { agent, show } => {
+        Commands::Diff {
+            agent,
+            profile,
+            show,
+        } => {
             let agent = agent.unwrap_or_else(|| "core".to_string());
-            crate::prevdf::cmd_diff(&ctx.repo_root, crate::prevdf::DiffOptions { agent, show })
+            crate::prevdf::cmd_diff(
+                &ctx.repo_root,
+                crate::prevdf::DiffOptions {
+                    agent,
+                    profile,
+                    show,
+                },
+            )
         }
 
-        Commands::Sync { agent, backend } => {
-            let agent = agent.unwrap_or_else(|| "core".to_string());
+        Commands::Sync {
+            agent,
+            all,
+            backend,
+            profile,
+            yes,
+            watch,
+        } => {
             let backend = backend.map(|b| map_backend(b, ctx.verbose));
 
             crate::syncer::cmd_sync(
                 &ctx.repo_root,
                 crate::syncer::SyncOptions {
-                    agent,
+                    agents: agent,
+                    all,
                     backend,
+                    profile,
+                    confirm: crate::confrm::ConfirmOptions { yes, ci: ctx.ci },
+                    watch,
                     verbose: ctx.verbose,
                 },
             )
@@ -325,6 +494,10 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
             mode,
             profile,
             backend,
+            diff,
+            patch,
+            apply,
+            yes,
             passthrough,
         } => {
             let backend = backend.map(|b| map_backend(b, ctx.verbose));
@@ -338,6 +511,12 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
                     mode,
                     profile,
                     passthrough,
+                    collect: crate::runner::collect::CollectOptions {
+                        diff,
+                        patch,
+                        apply,
+                        confirm: crate::confrm::ConfirmOptions { yes, ci: ctx.ci },
+                    },
                     verbose: ctx.verbose,
                 },
             )
@@ -352,13 +531,14 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
             crate::cleanup::CleanOptions {
                 agent,
                 dry_run,
-                yes,
+                confirm: crate::confrm::ConfirmOptions { yes, ci: ctx.ci },
             },
         ),
 
-        Commands::Doctor { fix, ci } => {
-            crate::doctor::cmd_doctor(&ctx.repo_root, crate::doctor::DoctorOptions { fix, ci })
-        }
+        Commands::Doctor { fix } => crate::doctor::cmd_doctor(
+            &ctx.repo_root,
+            crate::doctor::DoctorOptions { fix, ci: ctx.ci },
+        ),
 
         Commands::Test { command } => match command {
             TestCommands::Adapters { agent, update } => {
@@ -369,13 +549,36 @@ fn dispatch(ctx: &AppContext, cmd: Commands) -> AppResult<()> {
         Commands::Explain { path } => crate::explnx::cmd_explain(&ctx.repo_root, &path, ctx.output),
 
         Commands::Compat => crate::compat::cmd_compat(&ctx.repo_root, ctx.output),
+        Commands::ShimExec { spec, args } => crate::runner::shims::cmd_shim_exec(&spec, &args),
+
+        Commands::SetMode {
+            mode,
+            profile,
+            backend,
+            scope,
+        } => {
+            let backend = backend.map(|b| map_backend(b, ctx.verbose));
 
-        _ => Err(AppError::not_initialized(&ctx.repo_root)),
+            crate::statex::cmd_set_mode(
+                &ctx.repo_root,
+                crate::statex::SetModeOptions {
+                    mode,
+                    profile,
+                    backend,
+                    scopes: scope,
+                },
+            )
+        }
+
+        Commands::State { command } => match command {
+            StateCommands::Show => crate::statex::cmd_state_show(&ctx.repo_root, ctx.output),
+            StateCommands::Clear => crate::statex::cmd_state_clear(&ctx.repo_root),
+        },
     }
 }
 
-fn cmd_status(ctx: &AppContext) -> AppResult<()> {
-    crate::status::cmd_status(&ctx.repo_root, ctx.output)
+fn cmd_status(ctx: &AppContext, profile: Option<String>, trace: bool) -> AppResult<()> {
+    crate::status::cmd_status(&ctx.repo_root, profile, trace, ctx.output)
 }
 
 fn cmd_validate(ctx: &AppContext) -> AppResult<()> {
@@ -407,17 +610,7 @@ fn cmd_validate(ctx: &AppContext) -> AppResult<()> {
                     }
                     Ok(())
                 }
-                Err(err) => Err(AppError {
-                    category: ErrorCategory::SchemaInvalid,
-                    message: format!("schema invalid: {} ({})", err.path.display(), err.schema),
-                    context: {
-                        let mut c = vec![format!("pointer: {}", err.pointer), err.message];
-                        if let Some(h) = err.hint {
-                            c.push(h);
-                        }
-                        c
-                    },
-                }),
+                Err(err) => Err(AppError::schema_invalid(err)),
             }
         }
         Err(agents_core::loadag::LoadError::NotInitialized { .. }) => {
@@ -463,6 +656,7 @@ fn main() {
         output,
         verbose: cli.verbose,
         quiet: cli.quiet,
+        ci: cli.ci,
     };
 
     match dispatch(&ctx, cli.command) {
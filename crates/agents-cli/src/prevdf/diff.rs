@@ -1,20 +1,21 @@
 use std::path::Path;
 
-use agents_core::driftx::{diff_plan, DiffKind};
+use agents_core::driftx::DiffKind;
 use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions};
-use agents_core::outputs::{plan_outputs, render_planned_output};
-use agents_core::resolv::{ResolutionRequest, Resolver};
+use agents_core::pipeline::{Pipeline, RenderCacheMode};
+use agents_core::resolv::ResolutionRequest;
 
 use crate::{AppError, ErrorCategory};
 
 #[derive(Debug, Clone)]
 pub struct DiffOptions {
     pub agent: String,
+    pub profile: Option<String>,
     pub show: bool,
 }
 
 pub fn cmd_diff(repo_root: &Path, opts: DiffOptions) -> Result<(), AppError> {
-    let (repo, _report) = load_repo_config(
+    let (mut repo, _report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -28,43 +29,27 @@ pub fn cmd_diff(repo_root: &Path, opts: DiffOptions) -> Result<(), AppError> {
             context: vec![],
         },
     })?;
+    crate::apply_user_overlay(&mut repo)?;
 
     // Validate schemas best-effort.
     let _ = agents_core::schemas::validate_repo(repo_root);
 
-    let resolver = Resolver::new(repo.clone());
-    let req = ResolutionRequest {
-        repo_root: repo_root.to_path_buf(),
-        ..Default::default()
-    };
-
-    let effective = resolver.resolve(&req).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
-    })?;
-
-    let plan_res =
-        plan_outputs(repo_root, repo.clone(), &effective, &opts.agent).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![],
-        })?;
-
-    // Ensure planned content can be rendered now (forces renderer errors early).
-    for out in &plan_res.plan.outputs {
-        let _ = render_planned_output(repo_root, out).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![format!("path: {}", out.path.as_str())],
-        })?;
-    }
-
-    let report = diff_plan(repo_root, &plan_res.plan).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
-    })?;
+    // Diff against what `agents sync` would write, which depends on the selected backend.
+    let backend = crate::syncer::select_backend(&repo, &opts.agent, opts.profile.as_deref(), None);
+
+    // Rendering up front surfaces renderer errors before any diffing.
+    let rendered = Pipeline::for_agent(repo_root, repo, &opts.agent)
+        .with_request(ResolutionRequest {
+            repo_root: repo_root.to_path_buf(),
+            override_backend: Some(backend),
+            override_profile: opts.profile.clone(),
+            ..Default::default()
+        })
+        .with_render_cache(RenderCacheMode::ReadOnly)
+        .render()
+        .map_err(AppError::pipeline)?;
+
+    let report = rendered.diff().map_err(AppError::pipeline)?;
 
     let mut creates = 0usize;
     let mut updates = 0usize;
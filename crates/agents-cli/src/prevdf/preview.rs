@@ -1,8 +1,9 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions};
-use agents_core::outputs::{plan_outputs, render_planned_output};
-use agents_core::resolv::{ResolutionRequest, Resolver};
+use agents_core::pipeline::{Pipeline, PipelineResult, RenderCacheMode};
+use agents_core::resolv::ResolutionRequest;
 
 use crate::{AppError, ErrorCategory};
 
@@ -13,11 +14,55 @@ pub struct PreviewOptions {
     pub mode: Option<String>,
     pub profile: Option<String>,
     pub keep_temp: bool,
+
+    /// Keep running and re-render changed outputs into the same temp dir.
+    pub watch: bool,
 }
 
 pub fn cmd_preview(repo_root: &Path, opts: PreviewOptions) -> Result<(), AppError> {
+    if opts.watch {
+        return watch_preview(repo_root, &opts);
+    }
+
+    let (rendered, _) = render_preview(repo_root, &opts)?;
+
+    let tmp = agents_core::fsutil::temp_generation_dir("agents-preview").map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec![],
+    })?;
+
+    let tmp_path = tmp.path().to_path_buf();
+
+    // Render all planned outputs into temp dir.
+    for out in &rendered.outputs {
+        let dest = write_preview(
+            &tmp_path,
+            out.planned.path.as_str(),
+            &out.content_with_stamp,
+        )?;
+        println!(
+            "preview: {} -> {}",
+            out.planned.path.as_str(),
+            dest.display()
+        );
+    }
+
+    if opts.keep_temp {
+        println!("temp: {}", tmp_path.display());
+        std::mem::forget(tmp);
+    }
+
+    Ok(())
+}
+
+/// Load, resolve, plan and render. Also returns the renderer source files read, for `--watch`.
+fn render_preview(
+    repo_root: &Path,
+    opts: &PreviewOptions,
+) -> Result<(PipelineResult, Vec<PathBuf>), AppError> {
     // Load repo config
-    let (repo, _report) = load_repo_config(
+    let (mut repo, _report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -31,69 +76,92 @@ pub fn cmd_preview(repo_root: &Path, opts: PreviewOptions) -> Result<(), AppErro
             context: vec![],
         },
     })?;
+    crate::apply_user_overlay(&mut repo)?;
 
-    // Validate schemas best-effort.
-    let _ = agents_core::schemas::validate_repo(repo_root);
+    if opts.watch {
+        crate::watchr::validate_schemas(repo_root, &repo)?;
+    } else {
+        // Validate schemas best-effort.
+        let _ = agents_core::schemas::validate_repo(repo_root);
+    }
 
-    // Resolve effective config.
-    let resolver = Resolver::new(repo.clone());
-    let req = ResolutionRequest {
-        repo_root: repo_root.to_path_buf(),
-        override_mode: opts.mode.clone(),
-        override_profile: opts.profile.clone(),
-        override_backend: opts.backend,
-        ..Default::default()
-    };
+    let sources =
+        crate::watchr::renderer_sources(repo_root, &repo, std::slice::from_ref(&opts.agent));
+
+    // Resolve, plan and render.
+    let rendered = Pipeline::for_agent(repo_root, repo, &opts.agent)
+        .with_request(ResolutionRequest {
+            repo_root: repo_root.to_path_buf(),
+            override_mode: opts.mode.clone(),
+            override_profile: opts.profile.clone(),
+            override_backend: opts.backend,
+            ..Default::default()
+        })
+        .with_render_cache(RenderCacheMode::ReadOnly)
+        .render()
+        .map_err(AppError::pipeline)?;
 
-    let effective = resolver.resolve(&req).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
-    })?;
+    // Persist source maps for `agents explain`.
+    crate::explnx::persist_source_maps(repo_root, &rendered.plan)?;
 
-    // Plan outputs.
-    let plan_res =
-        plan_outputs(repo_root, repo.clone(), &effective, &opts.agent).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![],
-        })?;
+    Ok((rendered, sources))
+}
 
-    // Persist source maps for `agents explain`.
-    crate::explnx::persist_source_maps(repo_root, &plan_res)?;
+/// Render into one temp dir for the whole session, printing only outputs that changed (or
+/// disappeared) since the previous render.
+fn watch_preview(repo_root: &Path, opts: &PreviewOptions) -> Result<(), AppError> {
+    if !repo_root.join(".agents/manifest.yaml").is_file() {
+        return Err(AppError::not_initialized(repo_root));
+    }
 
     let tmp = agents_core::fsutil::temp_generation_dir("agents-preview").map_err(|e| AppError {
         category: ErrorCategory::Io,
         message: e.to_string(),
         context: vec![],
     })?;
-
     let tmp_path = tmp.path().to_path_buf();
-
-    // Render all planned outputs into temp dir.
-    for out in &plan_res.plan.outputs {
-        let rendered = render_planned_output(repo_root, out).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![format!("path: {}", out.path.as_str())],
-        })?;
-
-        let dest: PathBuf = tmp_path.join(out.path.as_str());
-        agents_core::fsutil::atomic_write(&dest, rendered.content_with_stamp.as_bytes()).map_err(
-            |e| AppError {
-                category: ErrorCategory::Io,
-                message: e.to_string(),
-                context: vec![],
-            },
-        )?;
-
-        println!("preview: {} -> {}", out.path.as_str(), dest.display());
-    }
+    println!("temp: {}", tmp_path.display());
+
+    let mut previous: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    let res = crate::watchr::watch(repo_root, || {
+        let (rendered, sources) = render_preview(repo_root, opts)?;
+
+        let mut current: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        let mut changed = 0usize;
+        for out in &rendered.outputs {
+            let path = out.planned.path.as_str().to_string();
+            if previous.get(&path) != Some(&out.content_with_stamp) {
+                let dest = write_preview(&tmp_path, &path, &out.content_with_stamp)?;
+                println!("preview: {path} -> {}", dest.display());
+                changed += 1;
+            }
+            current.insert(path, out.content_with_stamp.clone());
+        }
+        for path in previous.keys().filter(|p| !current.contains_key(*p)) {
+            let _ = std::fs::remove_file(tmp_path.join(path));
+            println!("remove: {path}");
+            changed += 1;
+        }
+        if changed == 0 {
+            println!("preview: unchanged");
+        }
+
+        previous = current;
+        Ok(sources)
+    });
 
     if opts.keep_temp {
-        println!("temp: {}", tmp_path.display());
         std::mem::forget(tmp);
     }
+    res
+}
 
-    Ok(())
+fn write_preview(tmp_path: &Path, rel: &str, content: &[u8]) -> Result<PathBuf, AppError> {
+    let dest = tmp_path.join(rel);
+    agents_core::fsutil::atomic_write(&dest, content).map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec![],
+    })?;
+    Ok(dest)
 }
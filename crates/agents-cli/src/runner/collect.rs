@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use agents_core::model::{ConfirmationType, Policy};
+use agents_core::wsdiff::{
+    apply_workspace_changes, render_patch, ChangeKind, WorkspaceBaseline, WorkspaceDiff,
+};
+
+use crate::confrm::{self, ConfirmOptions, Gate};
+use crate::{AppError, ErrorCategory};
+
+/// What to do with agent changes left in a vfs workspace.
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Print the unified diff to stdout.
+    pub diff: bool,
+
+    /// Write the unified diff to this file (relative paths are resolved against the repo root).
+    pub patch: Option<PathBuf>,
+
+    /// Copy the changes back into the repo.
+    pub apply: bool,
+
+    /// `--yes` applies without prompting; `--ci` never prompts.
+    pub confirm: ConfirmOptions,
+}
+
+impl CollectOptions {
+    pub fn is_requested(&self) -> bool {
+        self.diff || self.patch.is_some() || self.apply
+    }
+}
+
+/// Report, export and optionally apply workspace changes. `agent_ok` is false when the agent
+/// exited non-zero; changes are still reported but never applied in that case. Files also
+/// edited in the repo since `baseline` are never overwritten.
+pub fn collect_changes(
+    repo_root: &Path,
+    workspace: &Path,
+    diff: &WorkspaceDiff,
+    baseline: &WorkspaceBaseline,
+    policy: &Policy,
+    opts: &CollectOptions,
+    agent_ok: bool,
+) -> Result<(), AppError> {
+    if diff.is_empty() {
+        println!("changes: none");
+        return Ok(());
+    }
+
+    println!(
+        "changes: files_changed={} patch_lines={}",
+        diff.files_changed(),
+        diff.patch_lines()
+    );
+    for c in &diff.changes {
+        if c.conflict {
+            println!(
+                "{}: {} (conflict: also changed in the repo)",
+                c.kind.as_str(),
+                c.path
+            );
+        } else {
+            println!("{}: {}", c.kind.as_str(), c.path);
+        }
+    }
+
+    let patch_error = |e: agents_core::wsdiff::WsDiffError| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec![format!("workspace: {}", workspace.display())],
+    };
+
+    if opts.diff || opts.patch.is_some() {
+        let patch = render_patch(repo_root, workspace, diff).map_err(patch_error)?;
+        if opts.diff {
+            print!("{patch}");
+        }
+        if let Some(path) = &opts.patch {
+            let dest = if path.is_absolute() {
+                path.clone()
+            } else {
+                repo_root.join(path)
+            };
+            agents_core::fsutil::atomic_write(&dest, patch.as_bytes()).map_err(|e| AppError {
+                category: ErrorCategory::Io,
+                message: e.to_string(),
+                context: vec![format!("path: {}", dest.display())],
+            })?;
+            println!("patch: {}", dest.display());
+        }
+    }
+
+    if !opts.apply {
+        return Ok(());
+    }
+
+    if !agent_ok {
+        println!("apply: skipped (agent exited with an error)");
+        return Ok(());
+    }
+
+    // Writing back modified files is an overwrite, removing deleted ones a delete. When the
+    // policy gates either, its confirmation replaces the plain apply prompt.
+    let mut kinds = vec![];
+    if diff.changes.iter().any(|c| c.kind == ChangeKind::Modified) {
+        kinds.push(ConfirmationType::Overwrite);
+    }
+    if diff.changes.iter().any(|c| c.kind == ChangeKind::Deleted) {
+        kinds.push(ConfirmationType::Delete);
+    }
+    let action = format!("apply {} changed files to the repo", diff.files_changed());
+    if kinds
+        .iter()
+        .any(|k| policy.confirmations.required_for.contains(k))
+    {
+        confrm::require(
+            repo_root,
+            policy,
+            &Gate {
+                command: "run",
+                action,
+                kinds,
+            },
+            opts.confirm,
+        )?;
+    } else if !opts.confirm.yes && !confrm::prompt(&action)? {
+        println!("apply: skipped");
+        return Ok(());
+    }
+
+    let conflicts =
+        apply_workspace_changes(repo_root, workspace, diff, Some(baseline)).map_err(patch_error)?;
+    for path in &conflicts {
+        println!("apply: skipped {path} (changed in the repo during the run)");
+    }
+    println!("apply: {} files", diff.files_changed() - conflicts.len());
+    Ok(())
+}
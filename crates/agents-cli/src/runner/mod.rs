@@ -1,18 +1,28 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant};
 
 use agents_core::fsutil;
 use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions};
 use agents_core::matwiz::{Backend as MatwizBackend, MaterializeBackend};
-use agents_core::model::BackendKind;
-use agents_core::outputs::{plan_outputs, render_planned_output};
-use agents_core::resolv::{ResolutionRequest, Resolver};
-use agents_core::stamps::{classify as classify_drift, parse_stamp};
-use agents_core::vfsmnt::{OverlayFile, VfsMountOptions};
+use agents_core::model::{BackendKind, Limits, Policy};
+use agents_core::pipeline::{Pipeline, PipelineResult, RenderCacheMode};
+use agents_core::resolv::ResolutionRequest;
+use agents_core::vfsmnt::VfsMountOptions;
+use agents_core::wsdiff::{
+    check_diff_limits, diff_workspace, has_diff_limits, runtime_limit, LimitKind,
+    WorkspaceBaseline, WorkspaceDiffOptions,
+};
 
 use crate::{AppError, ErrorCategory};
 
+pub mod collect;
 pub mod registry;
+pub mod shims;
+
+use collect::{collect_changes, CollectOptions};
 
 #[derive(Debug, Clone)]
 pub struct RunOptions {
@@ -29,6 +39,9 @@ pub struct RunOptions {
     /// Args after `--`.
     pub passthrough: Vec<String>,
 
+    /// Write-back of agent changes for the vfs backends.
+    pub collect: CollectOptions,
+
     pub verbose: bool,
 }
 
@@ -46,7 +59,7 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
             .unwrap_or_else(|| opts.agent_cmd.clone())
     });
 
-    let (repo, _report) = load_repo_config(
+    let (mut repo, _report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -60,6 +73,7 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
             context: vec![],
         },
     })?;
+    crate::apply_user_overlay(&mut repo)?;
 
     // Validate schemas if present.
     if repo_root.join(".agents/schemas").is_dir() {
@@ -76,6 +90,15 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
         })?;
     }
 
+    let confirm = opts.collect.confirm;
+    if opts.collect.apply && !confirm.yes && (confirm.ci || !std::io::stdin().is_terminal()) {
+        return Err(AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: "`--apply` requires confirmation".to_string(),
+            context: vec!["hint: rerun with `--yes` to apply without prompting".to_string()],
+        });
+    }
+
     let state_backend = repo.state.as_ref().and_then(|s| s.backend);
     let backend_override = if opts.backend.is_none()
         && repo.manifest.defaults.backend.is_none()
@@ -85,49 +108,24 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
     } else {
         None
     };
-    let resolver = Resolver::new(repo.clone());
-    let req = ResolutionRequest {
-        repo_root: repo_root.to_path_buf(),
-        override_mode: opts.mode.clone(),
-        override_profile: opts.profile.clone(),
-        override_backend: opts.backend.or(backend_override),
-        ..Default::default()
-    };
-    let effective = resolver.resolve(&req).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
-    })?;
+    let pipeline = Pipeline::for_agent(repo_root, repo.clone(), &adapter_id)
+        .with_request(ResolutionRequest {
+            repo_root: repo_root.to_path_buf(),
+            override_mode: opts.mode.clone(),
+            override_profile: opts.profile.clone(),
+            override_backend: opts.backend.or(backend_override),
+            ..Default::default()
+        })
+        .with_render_cache(RenderCacheMode::ReadWrite);
+    let effective = pipeline.resolve().map_err(AppError::pipeline)?;
 
     warn_policy_risks(&repo, &effective);
 
-    let plan_res =
-        plan_outputs(repo_root, repo.clone(), &effective, &adapter_id).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![],
-        })?;
-
-    // Persist explain maps so `agents explain` works for generated outputs.
-    crate::explnx::persist_source_maps(repo_root, &plan_res)?;
-
     // Render all planned outputs once.
-    let mut rendered: Vec<RenderedItem> = vec![];
-    for planned in &plan_res.plan.outputs {
-        let r = render_planned_output(repo_root, planned).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![format!("path: {}", planned.path.as_str())],
-        })?;
+    let rendered = pipeline.render().map_err(AppError::pipeline)?;
 
-        rendered.push(RenderedItem {
-            path: planned.path.as_str().to_string(),
-            content_without_stamp: r.content_without_stamp,
-            content_with_stamp: r.content_with_stamp,
-        });
-    }
-
-    rendered.sort_by(|a, b| a.path.cmp(&b.path));
+    // Persist explain maps so `agents explain` works for generated outputs.
+    crate::explnx::persist_source_maps(repo_root, &rendered.plan)?;
 
     if opts.verbose {
         eprintln!(
@@ -140,17 +138,28 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
                 spec.id, spec.exec, spec.preferred_backend
             );
         }
-        for p in &rendered {
-            eprintln!("run: output: {}", p.path);
+        for p in &rendered.outputs {
+            eprintln!("run: output: {}", p.planned.path.as_str());
         }
     }
 
     match effective.backend {
         BackendKind::Materialize => {
-            apply_materialize(repo_root, &plan_res.plan.outputs, &rendered)?;
+            apply_materialize(repo_root, &rendered)?;
 
-            let status = run_host_agent(repo_root, &agent_cmd, &opts.passthrough)?;
-            exit_with_status(status)
+            if !shims::plan(repo_root, &repo, &effective, &adapter_id)?
+                .shims
+                .is_empty()
+            {
+                eprintln!("warning: cli_shim skills are only exposed on the vfs backends");
+            }
+
+            if opts.collect.is_requested() {
+                eprintln!("warning: materialize runs in the repo; nothing to collect");
+            }
+
+            let outcome = run_host_agent(repo_root, &agent_cmd, &opts.passthrough, None, false)?;
+            exit_with_outcome(outcome, None)
         }
 
         BackendKind::VfsContainer => {
@@ -161,8 +170,50 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
             })?;
             let outputs_dir: PathBuf = tmp.path().to_path_buf();
 
-            for item in &rendered {
-                let dest = outputs_dir.join(&item.path);
+            // The image may not be able to run the host `agents` binary, so shim wrappers are
+            // plain shell scripts.
+            let shim_plan = shims::plan(repo_root, &repo, &effective, &adapter_id)?;
+            let shim_files = shims::container_shim_files(
+                &shim_plan,
+                Path::new(agents_core::vfsctr::run::SHIM_LOG_DIR),
+            )?;
+
+            // Mounted skill assets are bind-mounted read-only instead of copied in.
+            let mut ro_mounts: Vec<(PathBuf, String)> = vec![];
+            let mut shim_log_dir = None;
+            let extra_paths: Vec<String> = shim_files.iter().map(|f| f.rel_path.clone()).collect();
+            if !shim_files.is_empty() {
+                let dir = shims::shim_log_dir(repo_root);
+                std::fs::create_dir_all(&dir).map_err(|e| AppError {
+                    category: ErrorCategory::Io,
+                    message: e.to_string(),
+                    context: vec![format!("path: {}", dir.display())],
+                })?;
+                shim_log_dir = Some(dir);
+            }
+            for shim in &shim_files {
+                let dest = outputs_dir.join(&shim.rel_path);
+                fsutil::atomic_write(&dest, &shim.bytes)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| {
+                        if shim.executable {
+                            agents_core::vfsmnt::set_executable(&dest)
+                                .map_err(|e| e.to_string())?;
+                        }
+                        Ok(())
+                    })
+                    .map_err(|e| AppError {
+                        category: ErrorCategory::Io,
+                        message: e,
+                        context: vec![format!("path: {}", dest.display())],
+                    })?;
+            }
+            for item in &rendered.outputs {
+                if let Some(asset) = item.planned.skill_asset.as_ref().filter(|a| a.mount) {
+                    ro_mounts.push((asset.source.clone(), asset.dest.as_str().to_string()));
+                    continue;
+                }
+                let dest = outputs_dir.join(item.planned.path.as_str());
                 if let Some(parent) = dest.parent() {
                     std::fs::create_dir_all(parent).map_err(|e| AppError {
                         category: ErrorCategory::Io,
@@ -170,23 +221,18 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
                         context: vec![format!("path: {}", parent.display())],
                     })?;
                 }
-                fsutil::atomic_write(&dest, item.content_with_stamp.as_bytes()).map_err(|e| {
-                    AppError {
-                        category: ErrorCategory::Io,
-                        message: e.to_string(),
-                        context: vec![format!("path: {}", dest.display())],
-                    }
+                fsutil::atomic_write(&dest, &item.content_with_stamp).map_err(|e| AppError {
+                    category: ErrorCategory::Io,
+                    message: e.to_string(),
+                    context: vec![format!("path: {}", dest.display())],
                 })?;
             }
 
-            let policy = repo
-                .policies
-                .get(&effective.policy_id)
-                .ok_or_else(|| AppError {
-                    category: ErrorCategory::Io,
-                    message: "missing effective policy".to_string(),
-                    context: vec![format!("policy: {}", effective.policy_id)],
-                })?;
+            let policy = effective.policy(&repo).ok_or_else(|| AppError {
+                category: ErrorCategory::Io,
+                message: "missing effective policy".to_string(),
+                context: vec![format!("policy: {}", effective.policy_id)],
+            })?;
 
             let network_enabled = policy
                 .capabilities
@@ -201,6 +247,27 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
                 .map(|f| f.write)
                 .unwrap_or(true);
 
+            let limits = policy.limits.clone().unwrap_or_default();
+            let timeout = runtime_limit(&limits);
+
+            // The workspace is bind-mounted from the host when it has to be diffed afterwards.
+            let workspace_tmp = if has_diff_limits(&limits) || opts.collect.is_requested() {
+                Some(
+                    fsutil::temp_generation_dir("agents-run-ws").map_err(|e| AppError {
+                        category: ErrorCategory::Io,
+                        message: e.to_string(),
+                        context: vec![],
+                    })?,
+                )
+            } else {
+                None
+            };
+            let baseline = workspace_tmp
+                .as_ref()
+                .map(|_| capture_baseline(repo_root, &rendered, &extra_paths))
+                .transpose()?;
+            let container_name = format!("agents-run-{}", std::process::id());
+
             let cmd = build_agent_cmd(&agent_cmd, &opts.passthrough);
 
             let inv = agents_core::vfsctr::run::VfsContainerInvocation {
@@ -212,29 +279,52 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
                 verbose: opts.verbose,
                 deny_network: !network_enabled,
                 deny_writes: !fs_write_enabled,
+                workspace_dir: workspace_tmp.as_ref().map(|t| t.path().to_path_buf()),
+                name: timeout.map(|_| container_name.clone()),
+                ro_mounts,
+                shim_log_dir,
+                user: workspace_tmp
+                    .as_ref()
+                    .and_then(|t| agents_core::vfsctr::run::owner_of(t.path())),
             };
 
             let docker = agents_core::vfsctr::docker::DockerRuntime::new();
-            let status = inv.run_interactive(&docker).map_err(|e| AppError {
+            let docker_error = |e: agents_core::vfsctr::docker::DockerError| AppError {
                 category: ErrorCategory::ExternalToolMissing,
                 message: e.to_string(),
                 context: vec!["hint: ensure docker is installed and running".to_string()],
+            };
+            let mut child = inv.spawn(&docker).map_err(docker_error)?;
+            let outcome = wait_with_timeout(&mut child, timeout, || {
+                let _ = docker.kill(&container_name);
             })?;
 
-            // Keep temp dir alive until container exits.
+            // Keep temp dirs alive until the container exits and the workspace is checked.
             let _tmp = tmp;
-            exit_with_status(status)
+            exit_with_outcome(
+                outcome,
+                workspace_tmp
+                    .as_ref()
+                    .zip(baseline.as_ref())
+                    .map(|(ws, baseline)| WorkspaceCheck {
+                        repo_root,
+                        workspace: ws.path(),
+                        rendered: &rendered,
+                        extra_paths: &extra_paths,
+                        baseline,
+                        limits: &limits,
+                        policy,
+                        collect: &opts.collect,
+                    }),
+            )
         }
 
         BackendKind::VfsMount => {
-            let policy = repo
-                .policies
-                .get(&effective.policy_id)
-                .ok_or_else(|| AppError {
-                    category: ErrorCategory::Io,
-                    message: "missing effective policy".to_string(),
-                    context: vec![format!("policy: {}", effective.policy_id)],
-                })?;
+            let policy = effective.policy(&repo).ok_or_else(|| AppError {
+                category: ErrorCategory::Io,
+                message: "missing effective policy".to_string(),
+                context: vec![format!("policy: {}", effective.policy_id)],
+            })?;
 
             let fs_write_enabled = policy
                 .capabilities
@@ -243,14 +333,17 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
                 .map(|f| f.write)
                 .unwrap_or(true);
 
-            let overlays: Vec<OverlayFile> = rendered
-                .iter()
-                .map(|item| OverlayFile {
-                    rel_path: item.path.clone(),
-                    bytes: item.content_with_stamp.as_bytes().to_vec(),
-                })
-                .collect();
+            let shim_plan = shims::plan(repo_root, &repo, &effective, &adapter_id)?;
+            let shim_files = if shim_plan.shims.is_empty() {
+                vec![]
+            } else {
+                shims::shim_files(&shim_plan, &shims::shim_log_dir(repo_root), &current_exe()?)
+            };
+            let extra_paths: Vec<String> = shim_files.iter().map(|f| f.rel_path.clone()).collect();
+            let mut overlays = rendered.overlay_files();
+            overlays.extend(shim_files.iter().cloned());
 
+            let baseline = capture_baseline(repo_root, &rendered, &extra_paths)?;
             let workspace = agents_core::vfsmnt::create_workspace(
                 repo_root,
                 &overlays,
@@ -267,24 +360,39 @@ pub fn cmd_run(repo_root: &Path, opts: RunOptions) -> Result<(), AppError> {
 
             println!("mount: {}", workspace.path().display());
 
-            let status = run_host_agent(workspace.path(), &agent_cmd, &opts.passthrough)?;
-            exit_with_status(status)
+            let limits = policy.limits.clone().unwrap_or_default();
+            let outcome = run_host_agent(
+                workspace.path(),
+                &agent_cmd,
+                &opts.passthrough,
+                runtime_limit(&limits),
+                !shim_files.is_empty(),
+            )?;
+
+            exit_with_outcome(
+                outcome,
+                (has_diff_limits(&limits) || opts.collect.is_requested()).then_some(
+                    WorkspaceCheck {
+                        repo_root,
+                        workspace: workspace.path(),
+                        rendered: &rendered,
+                        extra_paths: &extra_paths,
+                        baseline: &baseline,
+                        limits: &limits,
+                        policy,
+                        collect: &opts.collect,
+                    },
+                ),
+            )
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct RenderedItem {
-    path: String,
-    content_without_stamp: String,
-    content_with_stamp: String,
-}
-
 fn warn_policy_risks(
     repo: &agents_core::loadag::RepoConfig,
     effective: &agents_core::resolv::EffectiveConfig,
 ) {
-    let Some(policy) = repo.policies.get(&effective.policy_id) else {
+    let Some(policy) = effective.policy(repo) else {
         return;
     };
 
@@ -310,54 +418,18 @@ fn warn_policy_risks(
     }
 }
 
-fn apply_materialize(
-    repo_root: &Path,
-    planned: &[agents_core::outputs::PlannedOutput],
-    rendered: &[RenderedItem],
-) -> Result<(), AppError> {
+fn apply_materialize(repo_root: &Path, rendered: &PipelineResult) -> Result<(), AppError> {
     let backend = MaterializeBackend;
 
-    let plan = agents_core::outputs::OutputPlan {
-        agent_id: "<run>".to_string(),
-        backend: BackendKind::Materialize,
-        outputs: planned.to_vec(),
-    };
-
-    let mut session = backend.prepare(repo_root, &plan).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
-    })?;
-
-    let mut outs: Vec<agents_core::matwiz::RenderedOutput> = vec![];
-    for p in planned {
-        let item = rendered
-            .iter()
-            .find(|x| x.path == p.path.as_str())
-            .expect("rendered output present");
-
-        let dest = repo_root.join(p.path.as_str());
-        let drift_status = classify_drift(&dest, &item.content_without_stamp, &p.drift_detection)
-            .map_err(|e| AppError {
+    let mut session = backend
+        .prepare(repo_root, &rendered.plan.plan)
+        .map_err(|e| AppError {
             category: ErrorCategory::Io,
             message: e.to_string(),
-            context: vec![format!("path: {}", dest.display())],
-        })?;
-
-        let stamp = parse_stamp(&item.content_with_stamp).ok_or_else(|| AppError {
-            category: ErrorCategory::Io,
-            message: "rendered output missing stamp".to_string(),
-            context: vec![format!("path: {}", p.path.as_str())],
+            context: vec![],
         })?;
 
-        outs.push(agents_core::matwiz::RenderedOutput {
-            path: p.path.clone(),
-            bytes: item.content_with_stamp.as_bytes().to_vec(),
-            stamp_meta: stamp.meta,
-            drift_status,
-        });
-    }
-
+    let outs = rendered.materialize_outputs();
     let report = backend.apply(&mut session, &outs).map_err(|e| AppError {
         category: ErrorCategory::Io,
         message: e.to_string(),
@@ -379,28 +451,197 @@ fn apply_materialize(
     Ok(())
 }
 
+/// How the agent process ended.
+enum RunOutcome {
+    Exited(ExitStatus),
+
+    /// Killed after `maxCommandRuntimeSec`.
+    TimedOut(Duration),
+}
+
+/// `with_shims` prepends the workspace's `.agents-bin/` to the agent's `PATH`.
 fn run_host_agent(
     repo_root: &Path,
     exec: &str,
     passthrough: &[String],
-) -> Result<std::process::ExitStatus, AppError> {
+    timeout: Option<Duration>,
+    with_shims: bool,
+) -> Result<RunOutcome, AppError> {
     let cmd = build_agent_cmd(exec, passthrough);
     let (exec, args) = cmd.split_first().expect("agent command present");
 
-    let status = std::process::Command::new(exec)
+    let mut command = std::process::Command::new(exec);
+    if with_shims {
+        command.env("PATH", shims::path_with_shims(repo_root));
+    }
+    let mut child = command
         .args(args)
         .current_dir(repo_root)
         .stdin(std::process::Stdio::inherit())
         .stdout(std::process::Stdio::inherit())
         .stderr(std::process::Stdio::inherit())
-        .status()
+        .spawn()
         .map_err(|e| AppError {
             category: ErrorCategory::ExternalToolMissing,
             message: e.to_string(),
             context: vec![format!("exec: {exec}")],
         })?;
 
-    Ok(status)
+    wait_with_timeout(&mut child, timeout, || {})
+}
+
+/// Wait for `child`, killing it once `timeout` elapses. `on_timeout` runs before the kill so
+/// callers can stop anything the child does not own (e.g. a docker container).
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+    on_timeout: impl FnOnce(),
+) -> Result<RunOutcome, AppError> {
+    let wait_error = |e: std::io::Error| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec!["while waiting for agent".to_string()],
+    };
+
+    let Some(timeout) = timeout else {
+        return child.wait().map(RunOutcome::Exited).map_err(wait_error);
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().map_err(wait_error)? {
+            return Ok(RunOutcome::Exited(status));
+        }
+        if Instant::now() >= deadline {
+            on_timeout();
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(RunOutcome::TimedOut(timeout));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Inputs for checking limits and collecting agent changes after a vfs run.
+struct WorkspaceCheck<'a> {
+    repo_root: &'a Path,
+    workspace: &'a Path,
+    rendered: &'a PipelineResult,
+
+    /// Paths placed in the workspace besides rendered outputs (skill shims).
+    extra_paths: &'a [String],
+
+    /// The repo as it was before the agent started.
+    baseline: &'a WorkspaceBaseline,
+    limits: &'a Limits,
+    policy: &'a Policy,
+    collect: &'a CollectOptions,
+}
+
+fn exit_with_outcome(
+    outcome: RunOutcome,
+    check: Option<WorkspaceCheck<'_>>,
+) -> Result<(), AppError> {
+    let status = match outcome {
+        RunOutcome::Exited(status) => status,
+        RunOutcome::TimedOut(timeout) => {
+            return Err(AppError {
+                category: ErrorCategory::LimitExceeded,
+                message: "agent killed: runtime limit exceeded".to_string(),
+                context: vec![format!(
+                    "limit: {} max={}",
+                    LimitKind::MaxCommandRuntimeSec.as_str(),
+                    timeout.as_secs()
+                )],
+            });
+        }
+    };
+
+    if let Some(check) = check {
+        let exclude = overlay_paths(check.rendered, check.extra_paths);
+        let diff = diff_workspace(
+            check.repo_root,
+            check.workspace,
+            &WorkspaceDiffOptions {
+                exclude,
+                baseline: Some(check.baseline.clone()),
+            },
+        )
+        .map_err(|e| AppError {
+            category: ErrorCategory::Io,
+            message: e.to_string(),
+            context: vec![format!("workspace: {}", check.workspace.display())],
+        })?;
+
+        if has_diff_limits(check.limits) {
+            println!(
+                "limits: files_changed={} patch_lines={}",
+                diff.files_changed(),
+                diff.patch_lines()
+            );
+        }
+
+        let violations = check_diff_limits(check.limits, &diff);
+        if !violations.is_empty() {
+            let mut context: Vec<String> = violations
+                .iter()
+                .map(|v| {
+                    format!(
+                        "limit: {} actual={} max={}",
+                        v.limit.as_str(),
+                        v.actual,
+                        v.max
+                    )
+                })
+                .collect();
+            context.push("hint: workspace changes were not applied".to_string());
+            return Err(AppError {
+                category: ErrorCategory::LimitExceeded,
+                message: "policy limits exceeded".to_string(),
+                context,
+            });
+        }
+
+        if check.collect.is_requested() {
+            collect_changes(
+                check.repo_root,
+                check.workspace,
+                &diff,
+                check.baseline,
+                check.policy,
+                check.collect,
+                status.success(),
+            )?;
+        }
+    }
+
+    exit_with_status(status)
+}
+
+/// Generated outputs and shims are overlaid into the workspace and are not agent changes.
+fn overlay_paths(rendered: &PipelineResult, extra_paths: &[String]) -> BTreeSet<String> {
+    rendered
+        .outputs
+        .iter()
+        .map(|o| o.planned.path.as_str().to_string())
+        .chain(extra_paths.iter().cloned())
+        .collect()
+}
+
+/// Snapshot the repo before the agent starts, so collecting changes can tell agent edits from
+/// edits made to the repo meanwhile.
+fn capture_baseline(
+    repo_root: &Path,
+    rendered: &PipelineResult,
+    extra_paths: &[String],
+) -> Result<WorkspaceBaseline, AppError> {
+    WorkspaceBaseline::capture(repo_root, &overlay_paths(rendered, extra_paths)).map_err(|e| {
+        AppError {
+            category: ErrorCategory::Io,
+            message: e.to_string(),
+            context: vec![format!("repo: {}", repo_root.display())],
+        }
+    })
 }
 
 fn build_agent_cmd(agent_cmd: &str, passthrough: &[String]) -> Vec<String> {
@@ -423,6 +664,14 @@ fn exit_with_status(status: std::process::ExitStatus) -> Result<(), AppError> {
     }
 }
 
+fn current_exe() -> Result<PathBuf, AppError> {
+    std::env::current_exe().map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: format!("cannot locate the agents binary: {e}"),
+        context: vec![],
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::build_agent_cmd;
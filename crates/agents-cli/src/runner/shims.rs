@@ -0,0 +1,289 @@
+//! `cli_shim` skills: wrappers in `.agents-bin/` for `agents run` on the vfs backends, and the
+//! hidden `agents shim-exec` command those wrappers call.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use agents_core::loadag::RepoConfig;
+use agents_core::resolv::EffectiveConfig;
+use agents_core::skillpl::{
+    check_contract, contract_is_open, plan_cli_shims, shim_overlay_files, standalone_shim_files,
+    CliShimPlan, CliShimSpec, SkillPlanner, SHIM_BIN_DIR,
+};
+use agents_core::vfsmnt::OverlayFile;
+
+use crate::{AppError, ErrorCategory};
+
+/// Log file name, under `.agents/state/shims/` on the host.
+const LOG_FILE: &str = "invocations.jsonl";
+
+/// Host dir shim invocations are logged to.
+pub fn shim_log_dir(repo_root: &Path) -> PathBuf {
+    agents_core::fsutil::agents_state_dir(repo_root).join("shims")
+}
+
+/// The adapter's `cli_shim` skills; skills that get no wrapper are reported as warnings.
+pub fn plan(
+    repo_root: &Path,
+    repo: &RepoConfig,
+    effective: &EffectiveConfig,
+    adapter_id: &str,
+) -> Result<CliShimPlan, AppError> {
+    let skills = SkillPlanner::new(Arc::new(repo.clone()))
+        .plan(effective, Some(adapter_id))
+        .map_err(|e| AppError {
+            category: ErrorCategory::Io,
+            message: e.to_string(),
+            context: vec![],
+        })?;
+
+    let plan = plan_cli_shims(repo_root, &skills);
+    for (id, reason) in &plan.excluded {
+        eprintln!("warning: skill {id}: no shim generated: {reason}");
+    }
+    Ok(plan)
+}
+
+/// Wrapper and spec files for `plan`. `log_dir` and `agents_exe` are paths as seen from inside
+/// the sandbox.
+pub fn shim_files(plan: &CliShimPlan, log_dir: &Path, agents_exe: &Path) -> Vec<OverlayFile> {
+    shim_overlay_files(
+        &plan.shims,
+        &agents_exe.to_string_lossy(),
+        &log_dir.join(LOG_FILE).to_string_lossy(),
+    )
+}
+
+/// Wrapper files for the container backend, where the host `agents` binary may not run (another
+/// libc or OS). The wrappers log to `log_dir` (mounted from the host) themselves; skills with a
+/// contract need `agents shim-exec` to check it, so they are refused.
+pub fn container_shim_files(
+    plan: &CliShimPlan,
+    log_dir: &Path,
+) -> Result<Vec<OverlayFile>, AppError> {
+    let checked: Vec<&str> = plan
+        .shims
+        .iter()
+        .filter(|s| !contract_is_open(&s.inputs) || !contract_is_open(&s.outputs))
+        .map(|s| s.skill_id.as_str())
+        .collect();
+    if !checked.is_empty() {
+        return Err(AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: "cli_shim contracts cannot be checked inside a container".to_string(),
+            context: vec![
+                format!("skills: {}", checked.join(", ")),
+                "hint: run with `--backend vfs-mount`, or drop `contract` from these skills"
+                    .to_string(),
+            ],
+        });
+    }
+
+    Ok(standalone_shim_files(
+        &plan.shims,
+        &log_dir.join(LOG_FILE).to_string_lossy(),
+    ))
+}
+
+/// `<workspace>/.agents-bin` prepended to the current `PATH`.
+pub fn path_with_shims(workspace: &Path) -> std::ffi::OsString {
+    let mut paths = vec![workspace.join(SHIM_BIN_DIR)];
+    if let Some(path) = std::env::var_os("PATH") {
+        paths.extend(std::env::split_paths(&path));
+    }
+    std::env::join_paths(paths).unwrap_or_default()
+}
+
+/// Run the skill described by `spec_path`: check stdin against `contract.inputs`, run the
+/// entrypoint with the skill's env and args, check stdout against `contract.outputs`, and
+/// append the invocation to the log.
+pub fn cmd_shim_exec(spec_path: &Path, args: &[String]) -> Result<(), AppError> {
+    let spec: CliShimSpec = std::fs::read_to_string(spec_path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+        .map_err(|e| AppError {
+            category: ErrorCategory::Io,
+            message: format!("failed to read shim spec: {e}"),
+            context: vec![format!("path: {}", spec_path.display())],
+        })?;
+
+    let started = Instant::now();
+    let mut record = InvocationRecord::new(&spec, args);
+
+    // Only a checked contract needs stdin (and stdout) buffered; otherwise they pass through, so
+    // a caller that keeps stdin open does not block the skill.
+    let check_inputs = !contract_is_open(&spec.inputs);
+    let check_outputs = !contract_is_open(&spec.outputs);
+
+    let mut input = vec![];
+    if check_inputs {
+        std::io::stdin()
+            .read_to_end(&mut input)
+            .map_err(|e| AppError {
+                category: ErrorCategory::Io,
+                message: format!("failed to read stdin: {e}"),
+                context: vec![],
+            })?;
+
+        if let Err(errors) = check_json(&spec.inputs, &input) {
+            record.input = "invalid";
+            record.finish(&spec, started);
+            return Err(contract_error(&spec.skill_id, "stdin", "inputs", errors));
+        }
+        record.input = "ok";
+    }
+
+    let entrypoint = resolve_entrypoint(spec_path, &spec.entrypoint);
+    let piped = |buffered: bool| {
+        if buffered {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        }
+    };
+    let mut child = std::process::Command::new(&entrypoint)
+        .args(&spec.args)
+        .args(args)
+        .envs(&spec.env)
+        .stdin(piped(check_inputs))
+        .stdout(piped(check_outputs))
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| {
+            record.finish(&spec, started);
+            AppError {
+                category: ErrorCategory::ExternalToolMissing,
+                message: format!("skill {}: {e}", spec.skill_id),
+                context: vec![format!("exec: {}", entrypoint.display())],
+            }
+        })?;
+
+    // Write stdin from a thread so a child that writes before reading cannot deadlock.
+    let writer = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        })
+    });
+    let output = child.wait_with_output().map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec![format!("skill: {}", spec.skill_id)],
+    })?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+
+    let code = output.status.code().unwrap_or(1);
+    record.exit_code = Some(code);
+
+    if output.status.success() && check_outputs {
+        if let Err(errors) = check_json(&spec.outputs, &output.stdout) {
+            record.output = "invalid";
+            record.finish(&spec, started);
+            return Err(contract_error(&spec.skill_id, "stdout", "outputs", errors));
+        }
+        record.output = "ok";
+    }
+
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(&output.stdout);
+    let _ = stdout.flush();
+    record.finish(&spec, started);
+
+    if code != 0 {
+        return Err(AppError {
+            category: ErrorCategory::AgentExit { code },
+            message: String::new(),
+            context: vec![],
+        });
+    }
+    Ok(())
+}
+
+/// `./` entrypoints are relative to the workspace root, i.e. three levels above
+/// `.agents-bin/.spec/<id>.json`.
+fn resolve_entrypoint(spec_path: &Path, entrypoint: &str) -> PathBuf {
+    if !entrypoint.starts_with("./") {
+        return PathBuf::from(entrypoint);
+    }
+    let workspace = spec_path
+        .ancestors()
+        .nth(3)
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    workspace.join(&entrypoint[2..])
+}
+
+fn check_json(schema: &serde_json::Value, bytes: &[u8]) -> Result<(), Vec<String>> {
+    let value: serde_json::Value =
+        serde_json::from_slice(bytes).map_err(|e| vec![format!("not valid JSON: {e}")])?;
+    check_contract(schema, &value)
+}
+
+fn contract_error(skill_id: &str, stream: &str, contract: &str, errors: Vec<String>) -> AppError {
+    AppError {
+        category: ErrorCategory::SchemaInvalid,
+        message: format!("skill {skill_id}: {stream} does not match contract.{contract}"),
+        context: errors,
+    }
+}
+
+/// One line of `.agents/state/shims/invocations.jsonl`.
+#[derive(Debug, serde::Serialize)]
+struct InvocationRecord {
+    ts: u64,
+    skill: String,
+    args: Vec<String>,
+    exit_code: Option<i32>,
+    duration_ms: u128,
+
+    /// `ok`, `invalid`, or `unchecked` (open contract).
+    input: &'static str,
+
+    /// `ok`, `invalid`, or `unchecked` (open contract or the skill failed).
+    output: &'static str,
+}
+
+impl InvocationRecord {
+    fn new(spec: &CliShimSpec, args: &[String]) -> Self {
+        Self {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            skill: spec.skill_id.clone(),
+            args: args.to_vec(),
+            exit_code: None,
+            duration_ms: 0,
+            input: "unchecked",
+            output: "unchecked",
+        }
+    }
+
+    /// Append to the log; a failed write only warns so logging never breaks the skill.
+    fn finish(&mut self, spec: &CliShimSpec, started: Instant) {
+        self.duration_ms = started.elapsed().as_millis();
+
+        let path = Path::new(&spec.log);
+        let res = (|| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut f = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            let line = serde_json::to_string(self).map_err(std::io::Error::other)?;
+            writeln!(f, "{line}")
+        })();
+        if let Err(e) = res {
+            eprintln!(
+                "warning: failed to log skill invocation: {}: {e}",
+                path.display()
+            );
+        }
+    }
+}
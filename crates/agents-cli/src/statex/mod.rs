@@ -0,0 +1,196 @@
+use std::path::Path;
+
+use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions, RepoConfig};
+use agents_core::model::{BackendKind, State};
+use agents_core::statex::{self, StateError};
+
+use crate::{AppError, ErrorCategory, OutputMode};
+
+#[derive(Debug, Clone)]
+pub struct SetModeOptions {
+    pub mode: String,
+    pub profile: Option<String>,
+    pub backend: Option<BackendKind>,
+    pub scopes: Vec<String>,
+}
+
+pub fn cmd_set_mode(repo_root: &Path, opts: SetModeOptions) -> Result<(), AppError> {
+    let repo = load_repo(repo_root)?;
+
+    // Modes from the user overlay are personal, so they need not be enabled in the manifest.
+    let overlay_modes: &[String] = repo
+        .user_overlay
+        .as_ref()
+        .map(|o| o.modes.as_slice())
+        .unwrap_or(&[]);
+    if !repo.manifest.enabled.modes.contains(&opts.mode) && !overlay_modes.contains(&opts.mode) {
+        let mut enabled = repo.manifest.enabled.modes.clone();
+        enabled.extend(overlay_modes.iter().cloned());
+        return Err(AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: format!("mode is not enabled: {}", opts.mode),
+            context: vec![format!("hint: enabled modes: {}", enabled.join(", "))],
+        });
+    }
+
+    if let Some(p) = &opts.profile {
+        if !repo.profiles.contains_key(p) {
+            return Err(AppError {
+                category: ErrorCategory::InvalidArgs,
+                message: format!("unknown profile: {p}"),
+                context: vec![format!(
+                    "hint: add .agents/profiles/{p}.yaml or pick an existing profile"
+                )],
+            });
+        }
+    }
+
+    for s in &opts.scopes {
+        if !repo.scopes.contains_key(s) {
+            return Err(AppError {
+                category: ErrorCategory::InvalidArgs,
+                message: format!("unknown scope: {s}"),
+                context: vec![format!("hint: add .agents/scopes/{s}.yaml")],
+            });
+        }
+    }
+
+    // Unspecified fields keep their current values.
+    let prev = repo.state.clone();
+    let mut scopes = if opts.scopes.is_empty() {
+        prev.as_ref().map(|s| s.scopes.clone()).unwrap_or_default()
+    } else {
+        opts.scopes.clone()
+    };
+    scopes.sort();
+    scopes.dedup();
+
+    let state = State {
+        mode: opts.mode.clone(),
+        profile: opts
+            .profile
+            .clone()
+            .or_else(|| prev.as_ref().and_then(|s| s.profile.clone())),
+        backend: opts.backend.or(prev.as_ref().and_then(|s| s.backend)),
+        scopes,
+    };
+
+    let path = statex::write_state(repo_root, &state).map_err(state_error)?;
+
+    println!("state: {}", display_path(repo_root, &path));
+    print_state_human(&state);
+
+    Ok(())
+}
+
+pub fn cmd_state_show(repo_root: &Path, output: OutputMode) -> Result<(), AppError> {
+    // Ensure the repo is initialized so "no state" is not confused with "no repo".
+    load_repo(repo_root)?;
+
+    let state = statex::read_state(repo_root).map_err(state_error)?;
+
+    match output {
+        OutputMode::Json => {
+            let s = serde_json::to_string_pretty(&state).map_err(|e| AppError {
+                category: ErrorCategory::Io,
+                message: e.to_string(),
+                context: vec!["while serializing state".to_string()],
+            })?;
+            println!("{s}");
+        }
+        OutputMode::Human => match &state {
+            Some(state) => {
+                println!(
+                    "state: {}",
+                    display_path(repo_root, &statex::state_path(repo_root))
+                );
+                print_state_human(state);
+            }
+            None => {
+                println!("state: none");
+                println!("hint: run `agents set-mode <mode>` to pin a mode");
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn cmd_state_clear(repo_root: &Path) -> Result<(), AppError> {
+    load_repo(repo_root)?;
+
+    if statex::clear_state(repo_root).map_err(state_error)? {
+        println!("state: cleared");
+    } else {
+        println!("state: none");
+    }
+
+    Ok(())
+}
+
+fn load_repo(repo_root: &Path) -> Result<RepoConfig, AppError> {
+    let (mut repo, _report) = load_repo_config(
+        repo_root,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .map_err(|e| match e {
+        LoadError::NotInitialized { .. } => AppError::not_initialized(repo_root),
+        other => AppError {
+            category: ErrorCategory::Io,
+            message: other.to_string(),
+            context: vec![],
+        },
+    })?;
+    crate::apply_user_overlay(&mut repo)?;
+
+    Ok(repo)
+}
+
+fn print_state_human(state: &State) {
+    println!("mode: {}", state.mode);
+    if let Some(p) = &state.profile {
+        println!("profile: {p}");
+    }
+    if let Some(b) = state.backend {
+        println!("backend: {}", backend_str(b));
+    }
+    if !state.scopes.is_empty() {
+        println!("scopes: {}", state.scopes.join(", "));
+    }
+}
+
+fn backend_str(b: BackendKind) -> &'static str {
+    match b {
+        BackendKind::VfsContainer => "vfs_container",
+        BackendKind::Materialize => "materialize",
+        BackendKind::VfsMount => "vfs_mount",
+    }
+}
+
+fn display_path(repo_root: &Path, path: &Path) -> String {
+    agents_core::fsutil::display_repo_path(repo_root, path)
+        .unwrap_or_else(|_| path.display().to_string())
+}
+
+fn state_error(err: StateError) -> AppError {
+    match err {
+        StateError::Schema(err) => AppError {
+            category: ErrorCategory::SchemaInvalid,
+            message: format!("schema invalid: {} ({})", err.path.display(), err.schema),
+            context: {
+                let mut c = vec![format!("pointer: {}", err.pointer), err.message];
+                if let Some(h) = err.hint {
+                    c.push(h);
+                }
+                c
+            },
+        },
+        other => AppError {
+            category: ErrorCategory::Io,
+            message: other.to_string(),
+            context: vec![],
+        },
+    }
+}
@@ -1,14 +1,19 @@
 use std::path::Path;
 
 use agents_core::loadag::{load_repo_config, LoaderOptions};
-use agents_core::resolv::{ResolutionRequest, Resolver};
-use agents_core::skillpl::SkillPlanner;
+use agents_core::resolv::{ResolutionRequest, ResolveError, Resolver};
+use agents_core::skillpl::{plan_mcp_servers, SkillPlanError, SkillPlanner};
 
 use crate::status::StatusReport;
 use crate::{AppError, ErrorCategory, OutputMode};
 
-pub fn cmd_status(repo_root: &Path, output: OutputMode) -> Result<(), AppError> {
-    let (repo, report) = load_repo_config(
+pub fn cmd_status(
+    repo_root: &Path,
+    profile: Option<String>,
+    trace: bool,
+    output: OutputMode,
+) -> Result<(), AppError> {
+    let (mut repo, report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -23,21 +28,18 @@ pub fn cmd_status(repo_root: &Path, output: OutputMode) -> Result<(), AppError>
     // Validate schemas (best-effort: only if schemas exist).
     let _ = agents_core::schemas::validate_repo(repo_root);
 
+    // Merge the user overlay up front so the planner sees overlay policies too.
+    let user_overlay_enabled = repo.user_overlay_enabled();
+    crate::apply_user_overlay(&mut repo)?;
+
     let resolver = Resolver::new(repo.clone());
 
     // If state.yaml exists, resolution will likely use it. Expose this as a hint.
     let state_influences = repo.state.is_some();
 
-    // If user overlay is enabled, mention it. (The actual overlay root resolution is handled in agents-core.)
-    let user_overlay_enabled = repo
-        .manifest
-        .resolution
-        .as_ref()
-        .map(|r| r.enable_user_overlay)
-        .unwrap_or(true);
-
     let req = ResolutionRequest {
         repo_root: repo_root.to_path_buf(),
+        override_profile: profile,
         enable_user_overlay: user_overlay_enabled,
         ..Default::default()
     };
@@ -46,26 +48,74 @@ pub fn cmd_status(repo_root: &Path, output: OutputMode) -> Result<(), AppError>
     let has_load_warnings = !report.warnings.is_empty();
 
     let effective = resolver.resolve(&req).map_err(|e| AppError {
-        category: ErrorCategory::Io,
+        category: match e {
+            ResolveError::PolicyConflict { .. } => ErrorCategory::PolicyDenied,
+            _ => ErrorCategory::Io,
+        },
         message: e.to_string(),
         context: vec![],
     })?;
 
+    let profile_overrides = effective
+        .profile
+        .as_ref()
+        .and_then(|p| repo.profiles.get(p))
+        .map(|p| p.overrides())
+        .unwrap_or_default();
+    let policy = effective.policy(&repo).cloned();
+    let policy_extends = repo.policy_chain(&effective.policy_id);
+    let (policy_merged_from, policy_conflicts) = effective
+        .merged_policy
+        .as_ref()
+        .map(|m| (m.sources.clone(), m.conflicts.clone()))
+        .unwrap_or_default();
     let planner = SkillPlanner::new(repo);
-    let skills = planner.plan(&effective, None).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
+    let skills = planner.plan(&effective, None).map_err(|e| match e {
+        SkillPlanError::PolicyConflict { .. } => AppError {
+            category: ErrorCategory::PolicyDenied,
+            message: e.to_string(),
+            context: vec![
+                "hint: set manifest.resolution.onConflict=warn to disable the skill instead"
+                    .to_string(),
+            ],
+        },
+        other => AppError {
+            category: ErrorCategory::Io,
+            message: other.to_string(),
+            context: vec![],
+        },
     })?;
+    let mcp = policy
+        .as_ref()
+        .map(|p| plan_mcp_servers(repo_root, &skills, p))
+        .unwrap_or_default();
 
     let mut report = StatusReport {
         repo_root: repo_root.display().to_string(),
         effective_mode: effective.mode_id,
         effective_policy: effective.policy_id,
+        policy_extends,
+        policy_merged_from,
+        policy_conflicts,
+        policy_flattened: policy,
         effective_profile: effective.profile,
+        profile_overrides,
         effective_backend: effective.backend,
         scopes_matched: effective.scopes_matched.into_iter().map(|s| s.id).collect(),
         skills_enabled: skills.enabled.into_iter().map(|s| s.id).collect(),
+        skills_disabled: skills
+            .disabled
+            .into_iter()
+            .map(|(id, reason)| format!("{id}: {reason}"))
+            .collect(),
+        mcp_servers: mcp.servers.into_keys().collect(),
+        mcp_excluded: mcp
+            .excluded
+            .into_iter()
+            .map(|(id, reason)| format!("{id}: {reason}"))
+            .collect(),
+        from_user_overlay: effective.from_user_overlay,
+        trace: effective.trace,
         agent_id: None,
         hints: vec![],
     };
@@ -98,6 +148,9 @@ pub fn cmd_status(repo_root: &Path, output: OutputMode) -> Result<(), AppError>
     match output {
         OutputMode::Human => {
             print!("{}", report.render_human());
+            if trace {
+                print!("{}", report.render_trace());
+            }
             Ok(())
         }
         OutputMode::Json => {
@@ -1,4 +1,5 @@
-use agents_core::model::BackendKind;
+use agents_core::model::{BackendKind, Policy};
+use agents_core::resolv::ResolutionTrace;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct StatusReport {
@@ -6,12 +7,39 @@ pub struct StatusReport {
 
     pub effective_mode: String,
     pub effective_policy: String,
+
+    /// Policies the effective policy inherits from, nearest first, and the result of applying
+    /// them.
+    pub policy_extends: Vec<String>,
+    pub policy_flattened: Option<Policy>,
+
+    /// Scope policies merged into the effective one, and entries they both allowed and denied.
+    pub policy_merged_from: Vec<String>,
+    pub policy_conflicts: Vec<String>,
+
     pub effective_profile: Option<String>,
+
+    /// `"field: value"` for each setting the profile contributes.
+    pub profile_overrides: Vec<String>,
+
     pub effective_backend: BackendKind,
 
     pub scopes_matched: Vec<String>,
     pub skills_enabled: Vec<String>,
 
+    /// `"id: reason"` for skills whose requirements the policy forbids.
+    pub skills_disabled: Vec<String>,
+
+    /// MCP servers generated from `mcp_tool` skills, and those the policy filtered out.
+    pub mcp_servers: Vec<String>,
+    pub mcp_excluded: Vec<String>,
+
+    /// Every effective value the user overlay supplied, e.g. `"snippet: my-tips"`.
+    pub from_user_overlay: Vec<String>,
+
+    /// Which resolution layer set each value (printed by `--trace`).
+    pub trace: ResolutionTrace,
+
     pub agent_id: Option<String>,
 
     pub hints: Vec<String>,
@@ -24,10 +52,33 @@ impl StatusReport {
         out.push_str(&format!("repo: {}\n", self.repo_root));
         out.push_str(&format!("mode: {}\n", self.effective_mode));
         out.push_str(&format!("policy: {}\n", self.effective_policy));
+        if !self.policy_merged_from.is_empty() {
+            out.push_str(&format!(
+                "  merged from: {}\n",
+                self.policy_merged_from.join(", ")
+            ));
+            for c in &self.policy_conflicts {
+                out.push_str(&format!("  conflict: {c}\n"));
+            }
+        }
+        if !self.policy_extends.is_empty() {
+            out.push_str(&format!("  extends: {}\n", self.policy_extends.join(", ")));
+        }
+        if !self.policy_extends.is_empty() || !self.policy_merged_from.is_empty() {
+            if let Some(policy) = &self.policy_flattened {
+                out.push_str("  flattened:\n");
+                for line in serde_yaml::to_string(policy).unwrap_or_default().lines() {
+                    out.push_str(&format!("    {line}\n"));
+                }
+            }
+        }
         out.push_str(&format!(
             "profile: {}\n",
             self.effective_profile.as_deref().unwrap_or("<none>")
         ));
+        for o in &self.profile_overrides {
+            out.push_str(&format!("  - {o}\n"));
+        }
         out.push_str(&format!("backend: {:?}\n", self.effective_backend));
 
         out.push_str("scopes:\n");
@@ -40,12 +91,32 @@ impl StatusReport {
         }
 
         out.push_str("skills:\n");
-        if self.skills_enabled.is_empty() {
+        if self.skills_enabled.is_empty() && self.skills_disabled.is_empty() {
             out.push_str("  - <none>\n");
         } else {
             for s in &self.skills_enabled {
                 out.push_str(&format!("  - {s}\n"));
             }
+            for s in &self.skills_disabled {
+                out.push_str(&format!("  - disabled {s}\n"));
+            }
+        }
+
+        if !self.mcp_servers.is_empty() || !self.mcp_excluded.is_empty() {
+            out.push_str("mcp:\n");
+            for s in &self.mcp_servers {
+                out.push_str(&format!("  - {s}\n"));
+            }
+            for s in &self.mcp_excluded {
+                out.push_str(&format!("  - excluded {s}\n"));
+            }
+        }
+
+        if !self.from_user_overlay.is_empty() {
+            out.push_str("user overlay:\n");
+            for v in &self.from_user_overlay {
+                out.push_str(&format!("  - {v}\n"));
+            }
         }
 
         if let Some(agent) = &self.agent_id {
@@ -61,4 +132,12 @@ impl StatusReport {
 
         out
     }
+
+    pub fn render_trace(&self) -> String {
+        let mut out = String::from("trace:\n");
+        for line in self.trace.lines() {
+            out.push_str(&format!("  - {line}\n"));
+        }
+        out
+    }
 }
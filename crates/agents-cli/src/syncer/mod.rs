@@ -1,25 +1,67 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use agents_core::loadag::{load_repo_config, LoadError, LoaderOptions, RepoConfig};
-use agents_core::matwiz::{Backend, MaterializeBackend};
-use agents_core::model::BackendKind;
-use agents_core::outputs::{plan_outputs, render_planned_output};
-use agents_core::resolv::{ResolutionRequest, Resolver};
-use agents_core::stamps::{classify, parse_stamp};
-use agents_core::vfsmnt::{OverlayFile, VfsMountOptions};
+use agents_core::matwiz::{apply_transaction, planned_overwrites, TransactionReport};
+use agents_core::model::{BackendKind, ConfirmationType, Policy, Profile};
+use agents_core::pipeline::{
+    check_cross_adapter_collisions, dedupe_skill_assets, yield_shared_surfaces_to_owner, Pipeline,
+    PipelineResult, RenderCacheMode,
+};
+use agents_core::resolv::ResolutionRequest;
+use agents_core::vfsmnt::VfsMountOptions;
 
+use crate::confrm::{self, ConfirmOptions, Gate};
 use crate::{AppError, ErrorCategory};
 
 #[derive(Debug, Clone)]
 pub struct SyncOptions {
-    pub agent: String,
+    /// Adapters to sync; empty means `core` unless `all` is set.
+    pub agents: Vec<String>,
+
+    /// Sync every adapter in `manifest.enabled.adapters` (or the active profile's `adapters`).
+    pub all: bool,
+
     pub backend: Option<BackendKind>,
+
+    /// `--profile`; overrides the profile pinned in state or set as the manifest default.
+    pub profile: Option<String>,
+
+    /// Policy confirmations (`overwrite`) for `writePolicy: always` outputs.
+    pub confirm: ConfirmOptions,
+
+    /// Keep running and re-sync after every change to `.agents/` or a renderer source.
+    pub watch: bool,
+
     pub verbose: bool,
 }
 
 pub fn cmd_sync(repo_root: &Path, opts: SyncOptions) -> Result<(), AppError> {
+    if !opts.watch {
+        return sync_once(repo_root, &opts).map(|_| ());
+    }
+
+    // Checks that no edit can fix end the watch before it starts.
+    if !repo_root.join(".agents/manifest.yaml").is_file() {
+        return Err(AppError::not_initialized(repo_root));
+    }
+    if let Some(backend) = opts.backend.filter(|b| *b != BackendKind::Materialize) {
+        return Err(AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: "`sync --watch` requires the materialize backend".to_string(),
+            context: vec![format!("backend: {backend:?}")],
+        });
+    }
+
+    crate::watchr::watch(repo_root, || sync_once(repo_root, &opts))
+}
+
+/// One load, resolve, plan, render and apply pass. Returns the renderer source files the synced
+/// adapters read, for `--watch`.
+fn sync_once(repo_root: &Path, opts: &SyncOptions) -> Result<Vec<PathBuf>, AppError> {
     // Load repo config.
-    let (repo, _report) = load_repo_config(
+    let (mut repo, _report) = load_repo_config(
         repo_root,
         &LoaderOptions {
             require_schemas_dir: false,
@@ -33,89 +75,91 @@ pub fn cmd_sync(repo_root: &Path, opts: SyncOptions) -> Result<(), AppError> {
             context: vec![],
         },
     })?;
+    crate::apply_user_overlay(&mut repo)?;
 
-    // Validate schemas best-effort.
-    let _ = agents_core::schemas::validate_repo(repo_root);
+    if opts.watch {
+        crate::watchr::validate_schemas(repo_root, &repo)?;
+    } else {
+        // Validate schemas best-effort.
+        let _ = agents_core::schemas::validate_repo(repo_root);
+    }
 
-    let selected_backend = select_backend(&repo, &opts.agent, opts.backend);
+    let agents = sync_targets(&repo, opts)?;
+    let sources = crate::watchr::renderer_sources(repo_root, &repo, &agents);
 
-    // Resolve effective config.
-    let resolver = Resolver::new(repo.clone());
-    let req = ResolutionRequest {
-        repo_root: repo_root.to_path_buf(),
-        override_backend: Some(selected_backend),
-        ..Default::default()
-    };
+    if opts.watch {
+        require_materialize(&repo, &agents, opts, select_backend, "`sync --watch`")?;
+    }
+    if let [agent] = agents.as_slice() {
+        if !opts.all {
+            sync_one(repo_root, repo, agent, opts)?;
+            return Ok(sources);
+        }
+    }
 
-    let effective = resolver.resolve(&req).map_err(|e| AppError {
-        category: ErrorCategory::Io,
-        message: e.to_string(),
-        context: vec![],
-    })?;
+    sync_many(repo_root, &repo, &agents, opts)?;
+    Ok(sources)
+}
 
-    // Plan outputs.
-    let plan_res =
-        plan_outputs(repo_root, repo.clone(), &effective, &opts.agent).map_err(|e| AppError {
-            category: ErrorCategory::Io,
-            message: e.to_string(),
-            context: vec![],
-        })?;
+fn sync_targets(repo: &RepoConfig, opts: &SyncOptions) -> Result<Vec<String>, AppError> {
+    let mut agents = if opts.all {
+        active_profile(repo, opts.profile.as_deref())
+            .and_then(|p| p.adapters.clone())
+            .unwrap_or_else(|| repo.manifest.enabled.adapters.clone())
+    } else if opts.agents.is_empty() {
+        vec!["core".to_string()]
+    } else {
+        opts.agents.clone()
+    };
+    agents.sort();
+    agents.dedup();
 
-    // Persist source maps for `agents explain`.
-    crate::explnx::persist_source_maps(repo_root, &plan_res)?;
+    if agents.is_empty() {
+        return Err(AppError {
+            category: ErrorCategory::InvalidArgs,
+            message: "no adapters enabled".to_string(),
+            context: vec!["hint: add adapters to manifest.enabled.adapters".to_string()],
+        });
+    }
 
-    match selected_backend {
-        BackendKind::Materialize => {
-            let backend = MaterializeBackend;
-            let mut session = backend
-                .prepare(repo_root, &plan_res.plan)
-                .map_err(|e| AppError {
-                    category: ErrorCategory::Io,
-                    message: e.to_string(),
-                    context: vec![],
-                })?;
+    Ok(agents)
+}
 
-            let mut rendered_outputs: Vec<agents_core::matwiz::RenderedOutput> = vec![];
-            for out in &plan_res.plan.outputs {
-                let rendered = render_planned_output(repo_root, out).map_err(|e| AppError {
-                    category: ErrorCategory::Io,
-                    message: e.to_string(),
-                    context: vec![format!("path: {}", out.path.as_str())],
-                })?;
+fn sync_one(
+    repo_root: &Path,
+    repo: RepoConfig,
+    agent: &str,
+    opts: &SyncOptions,
+) -> Result<(), AppError> {
+    let selected_backend = select_backend(&repo, agent, opts.profile.as_deref(), opts.backend);
 
-                let stamp = parse_stamp(&rendered.content_with_stamp).ok_or_else(|| AppError {
-                    category: ErrorCategory::Io,
-                    message: "rendered output missing stamp".to_string(),
-                    context: vec![format!("path: {}", out.path.as_str())],
-                })?;
+    // Resolve, plan and render.
+    let pipeline = Pipeline::for_agent(repo_root, repo.clone(), agent)
+        .with_request(ResolutionRequest {
+            repo_root: repo_root.to_path_buf(),
+            override_backend: Some(selected_backend),
+            override_profile: opts.profile.clone(),
+            ..Default::default()
+        })
+        .with_render_cache(RenderCacheMode::ReadWrite);
+    let rendered = pipeline.render().map_err(AppError::pipeline)?;
+    let effective = &rendered.effective;
 
-                let drift_status = classify(
-                    &repo_root.join(out.path.as_str()),
-                    &rendered.content_without_stamp,
-                    &out.drift_detection,
-                )
-                .map_err(|e| AppError {
-                    category: ErrorCategory::Io,
-                    message: e.to_string(),
-                    context: vec![format!("path: {}", out.path.as_str())],
-                })?;
+    // Persist source maps for `agents explain`.
+    crate::explnx::persist_source_maps(repo_root, &rendered.plan)?;
 
-                rendered_outputs.push(agents_core::matwiz::RenderedOutput {
-                    path: out.path.clone(),
-                    bytes: rendered.content_with_stamp.into_bytes(),
-                    stamp_meta: stamp.meta,
-                    drift_status,
-                });
-            }
+    match selected_backend {
+        BackendKind::Materialize => {
+            confirm_overwrites(repo_root, &repo, &[&rendered], opts.confirm)?;
 
-            let report = backend
-                .apply(&mut session, &rendered_outputs)
-                .map_err(|e| AppError {
+            let tx =
+                apply_transaction(repo_root, &[rendered.apply_batch()]).map_err(|e| AppError {
                     category: ErrorCategory::Io,
                     message: e.to_string(),
                     context: vec![],
                 })?;
 
+            let (_, report) = tx.reports.first().expect("one batch");
             if !report.conflicts.is_empty() {
                 let msg = report
                     .conflict_details
@@ -134,9 +178,19 @@ pub fn cmd_sync(repo_root: &Path, opts: SyncOptions) -> Result<(), AppError> {
             }
 
             if opts.verbose {
+                println!(
+                    "cache: hits={} outputs={}",
+                    rendered.cache_hits,
+                    rendered.outputs.len()
+                );
+            }
+            // Watch mode always names what changed; unchanged outputs are skipped silently.
+            if opts.verbose || opts.watch {
                 for p in &report.written {
                     println!("write: {}", p.as_str());
                 }
+            }
+            if opts.verbose {
                 for p in &report.skipped {
                     println!("skip: {}", p.as_str());
                 }
@@ -152,14 +206,11 @@ pub fn cmd_sync(repo_root: &Path, opts: SyncOptions) -> Result<(), AppError> {
             Ok(())
         }
         BackendKind::VfsMount => {
-            let policy = repo
-                .policies
-                .get(&effective.policy_id)
-                .ok_or_else(|| AppError {
-                    category: ErrorCategory::Io,
-                    message: "missing effective policy".to_string(),
-                    context: vec![format!("policy: {}", effective.policy_id)],
-                })?;
+            let policy = effective.policy(&repo).ok_or_else(|| AppError {
+                category: ErrorCategory::Io,
+                message: "missing effective policy".to_string(),
+                context: vec![format!("policy: {}", effective.policy_id)],
+            })?;
 
             let fs_write_enabled = policy
                 .capabilities
@@ -168,23 +219,9 @@ pub fn cmd_sync(repo_root: &Path, opts: SyncOptions) -> Result<(), AppError> {
                 .map(|f| f.write)
                 .unwrap_or(true);
 
-            let mut overlays: Vec<OverlayFile> = vec![];
-            for out in &plan_res.plan.outputs {
-                let rendered = render_planned_output(repo_root, out).map_err(|e| AppError {
-                    category: ErrorCategory::Io,
-                    message: e.to_string(),
-                    context: vec![format!("path: {}", out.path.as_str())],
-                })?;
-
-                overlays.push(OverlayFile {
-                    rel_path: out.path.as_str().to_string(),
-                    bytes: rendered.content_with_stamp.into_bytes(),
-                });
-            }
-
             let workspace = agents_core::vfsmnt::create_workspace(
                 repo_root,
-                &overlays,
+                &rendered.overlay_files(),
                 &VfsMountOptions {
                     deny_writes: !fs_write_enabled,
                     verbose: opts.verbose,
@@ -212,27 +249,266 @@ pub fn cmd_sync(repo_root: &Path, opts: SyncOptions) -> Result<(), AppError> {
     }
 }
 
-fn select_backend(repo: &RepoConfig, agent: &str, cli: Option<BackendKind>) -> BackendKind {
-    if let Some(b) = cli {
-        return b;
+fn sync_many(
+    repo_root: &Path,
+    repo: &RepoConfig,
+    agents: &[String],
+    opts: &SyncOptions,
+) -> Result<(), AppError> {
+    // Multi-adapter sync writes to the working tree in one transaction, so every adapter must
+    // materialize.
+    require_materialize(
+        repo,
+        agents,
+        opts,
+        multi_adapter_backend,
+        "multi-adapter sync",
+    )?;
+
+    let shared = Arc::new(repo.clone());
+    let mut results: Vec<PipelineResult> = vec![];
+    for agent in agents {
+        let rendered = Pipeline::for_agent(repo_root, Arc::clone(&shared), agent)
+            .with_request(ResolutionRequest {
+                repo_root: repo_root.to_path_buf(),
+                override_backend: Some(BackendKind::Materialize),
+                override_profile: opts.profile.clone(),
+                ..Default::default()
+            })
+            .with_render_cache(RenderCacheMode::ReadWrite)
+            .render()
+            .map_err(|e| {
+                let mut err = AppError::pipeline(e);
+                err.context.insert(0, format!("adapter: {agent}"));
+                err
+            })?;
+        results.push(rendered);
     }
 
-    if let Some(backends) = &repo.manifest.backends {
-        if let Some(b) = backends.by_agent.get(agent) {
-            return *b;
+    let shared_owner = repo
+        .manifest
+        .defaults
+        .shared_surfaces_owner
+        .as_deref()
+        .unwrap_or(agents_core::shared::CORE_ADAPTER_ID);
+    yield_shared_surfaces_to_owner(&mut results, shared_owner);
+    check_cross_adapter_collisions(&results).map_err(AppError::pipeline)?;
+    dedupe_skill_assets(&mut results);
+
+    let refs: Vec<&PipelineResult> = results.iter().collect();
+    confirm_overwrites(repo_root, repo, &refs, opts.confirm)?;
+
+    let batches: Vec<_> = results.iter().map(|r| r.apply_batch()).collect();
+    let tx = apply_transaction(repo_root, &batches).map_err(|e| AppError {
+        category: ErrorCategory::Io,
+        message: e.to_string(),
+        context: vec!["hint: no outputs were written".to_string()],
+    })?;
+
+    if !tx.committed {
+        return Err(conflict_error(&tx));
+    }
+
+    // Persist source maps for `agents explain`.
+    for r in &results {
+        crate::explnx::persist_source_maps(repo_root, &r.plan)?;
+    }
+
+    let (mut written, mut skipped) = (0usize, 0usize);
+    for (agent, report) in &tx.reports {
+        println!(
+            "adapter: {agent} written={} skipped={} conflict={}",
+            report.written.len(),
+            report.skipped.len(),
+            report.conflicts.len()
+        );
+        if opts.verbose || opts.watch {
+            for p in &report.written {
+                println!("  write: {}", p.as_str());
+            }
         }
-        if let Some(b) = backends.default {
-            return b;
+        if opts.verbose {
+            for p in &report.skipped {
+                println!("  skip: {}", p.as_str());
+            }
+        }
+        written += report.written.len();
+        skipped += report.skipped.len();
+    }
+
+    println!(
+        "sync: adapters={} written={} skipped={} conflict=0",
+        tx.reports.len(),
+        written,
+        skipped
+    );
+
+    Ok(())
+}
+
+fn require_materialize(
+    repo: &RepoConfig,
+    agents: &[String],
+    opts: &SyncOptions,
+    select: fn(&RepoConfig, &str, Option<&str>, Option<BackendKind>) -> BackendKind,
+    what: &str,
+) -> Result<(), AppError> {
+    for agent in agents {
+        let backend = select(repo, agent, opts.profile.as_deref(), opts.backend);
+        if backend != BackendKind::Materialize {
+            return Err(AppError {
+                category: ErrorCategory::InvalidArgs,
+                message: format!("{what} requires the materialize backend"),
+                context: vec![
+                    format!("adapter: {agent} (backend: {backend:?})"),
+                    "hint: pass `--backend materialize` or sync adapters one at a time".to_string(),
+                ],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Ask for the `overwrite` confirmation, once per effective policy, before replacing files agents
+/// did not generate (or generated files edited since).
+fn confirm_overwrites(
+    repo_root: &Path,
+    repo: &RepoConfig,
+    results: &[&PipelineResult],
+    confirm: ConfirmOptions,
+) -> Result<(), AppError> {
+    let mut by_policy: BTreeMap<&str, (Option<&Policy>, Vec<String>)> = BTreeMap::new();
+    for r in results {
+        let overwrites =
+            planned_overwrites(repo_root, &[r.apply_batch()]).map_err(|e| AppError {
+                category: ErrorCategory::Io,
+                message: e.to_string(),
+                context: vec![],
+            })?;
+        by_policy
+            .entry(r.effective.policy_id.as_str())
+            .or_insert_with(|| (r.effective.policy(repo), vec![]))
+            .1
+            .extend(overwrites.into_iter().map(|(_, p)| p.as_str().to_string()));
+    }
+
+    for (policy, paths) in by_policy.into_values() {
+        if paths.is_empty() {
+            continue;
+        }
+        let Some(policy) = policy else {
+            continue;
+        };
+        confrm::require(
+            repo_root,
+            policy,
+            &Gate {
+                command: "sync",
+                action: format!("overwrite {}", paths.join(", ")),
+                kinds: vec![ConfirmationType::Overwrite],
+            },
+            confirm,
+        )
+        .map_err(|mut e| {
+            e.context.push("hint: no outputs were written".to_string());
+            e
+        })?;
+    }
+
+    Ok(())
+}
+
+fn conflict_error(tx: &TransactionReport) -> AppError {
+    let mut context: Vec<String> = vec![];
+    let mut hints: Vec<String> = vec![];
+    for (agent, report) in &tx.reports {
+        for c in &report.conflict_details {
+            context.push(format!(
+                "conflict: {agent}: {}: {}",
+                c.path.as_str(),
+                c.message
+            ));
+            hints.extend(c.hints.iter().cloned());
         }
     }
+    hints.dedup();
+    context.extend(hints);
+    context.push("hint: no outputs were written".to_string());
 
-    if let Some(b) = repo.manifest.defaults.backend {
-        return b;
+    AppError {
+        category: ErrorCategory::Conflict,
+        message: "conflicts detected; sync aborted".to_string(),
+        context,
     }
+}
+
+/// The `--profile` profile, else the one pinned in state or set as the manifest default.
+fn active_profile<'a>(repo: &'a RepoConfig, cli: Option<&'a str>) -> Option<&'a Profile> {
+    repo.selected_profile(cli)
+        .and_then(|id| repo.profiles.get(id))
+}
+
+pub(crate) fn select_backend(
+    repo: &RepoConfig,
+    agent: &str,
+    profile: Option<&str>,
+    cli: Option<BackendKind>,
+) -> BackendKind {
+    configured_backend(repo, agent, profile, cli).unwrap_or_else(|| {
+        repo.adapters
+            .get(agent)
+            .map(|a| a.backend_defaults.preferred)
+            .unwrap_or(BackendKind::VfsContainer)
+    })
+}
 
-    if let Some(adapter) = repo.adapters.get(agent) {
-        return adapter.backend_defaults.preferred;
+/// Like `select_backend`, but an adapter nothing configures a backend for uses its fallback when
+/// that materializes, so `sync --all` works with the adapters' own defaults.
+fn multi_adapter_backend(
+    repo: &RepoConfig,
+    agent: &str,
+    profile: Option<&str>,
+    cli: Option<BackendKind>,
+) -> BackendKind {
+    configured_backend(repo, agent, profile, cli).unwrap_or_else(|| {
+        match repo.adapters.get(agent) {
+            Some(a) if a.backend_defaults.fallback == BackendKind::Materialize => {
+                BackendKind::Materialize
+            }
+            Some(a) => a.backend_defaults.preferred,
+            None => BackendKind::VfsContainer,
+        }
+    })
+}
+
+/// The backend chosen by the CLI, state, the active profile or the manifest, if any (the same
+/// order the resolver applies them in).
+fn configured_backend(
+    repo: &RepoConfig,
+    agent: &str,
+    profile: Option<&str>,
+    cli: Option<BackendKind>,
+) -> Option<BackendKind> {
+    if let Some(b) = cli {
+        return Some(b);
+    }
+
+    if let Some(b) = repo.state.as_ref().and_then(|s| s.backend) {
+        return Some(b);
+    }
+
+    if let Some(b) = active_profile(repo, profile).and_then(|p| p.backend) {
+        return Some(b);
+    }
+
+    if let Some(backends) = &repo.manifest.backends {
+        if let Some(b) = backends.by_agent.get(agent) {
+            return Some(*b);
+        }
+        if let Some(b) = backends.default {
+            return Some(b);
+        }
     }
 
-    BackendKind::VfsContainer
+    repo.manifest.defaults.backend
 }
@@ -0,0 +1,110 @@
+//! Polling watcher behind `agents sync --watch` and `agents preview --watch`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use agents_core::loadag::RepoConfig;
+use agents_core::outputs::repo_file_source;
+
+use crate::AppError;
+
+/// How often the watched files are polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A change is only acted on once the files have been quiet this long, so an editor saving
+/// several files (or writing a file in chunks) triggers a single re-run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Run `cycle` once, then again after every debounced change under `.agents/` (except
+/// `.agents/state/`) or to one of the extra paths the last successful cycle returned.
+///
+/// Errors from `cycle` are printed and the watch continues; this only returns when the process
+/// is interrupted.
+pub fn watch<F>(repo_root: &Path, mut cycle: F) -> Result<(), AppError>
+where
+    F: FnMut() -> Result<Vec<PathBuf>, AppError>,
+{
+    let mut sources: Vec<PathBuf> = vec![];
+    loop {
+        match cycle() {
+            Ok(s) => sources = s,
+            Err(err) => eprint!("{err}"),
+        }
+        let mut snap = snapshot(repo_root, &sources);
+        println!("watch: waiting for changes (ctrl-c to stop)");
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let next = snapshot(repo_root, &sources);
+            if next != snap {
+                snap = next;
+                break;
+            }
+        }
+        loop {
+            std::thread::sleep(DEBOUNCE);
+            let next = snapshot(repo_root, &sources);
+            if next == snap {
+                break;
+            }
+            snap = next;
+        }
+
+        println!("watch: change detected");
+    }
+}
+
+/// Repo files read by `repo:`/`file:` renderer sources of `agents`' adapters.
+pub fn renderer_sources(repo_root: &Path, repo: &RepoConfig, agents: &[String]) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = agents
+        .iter()
+        .filter_map(|a| repo.adapters.get(a))
+        .flat_map(|a| a.outputs.iter())
+        .flat_map(|o| o.renderer.sources.iter())
+        .filter_map(|s| repo_file_source(s))
+        .map(|p| repo_root.join(p))
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Schema errors fail a watch cycle (and are printed) instead of being ignored as in a one-shot
+/// sync. Repos without `.agents/schemas/` are not validated.
+pub fn validate_schemas(repo_root: &Path, repo: &RepoConfig) -> Result<(), AppError> {
+    if !repo_root.join(".agents/schemas").is_dir() {
+        return Ok(());
+    }
+    agents_core::schemas::validate_repo_config(repo_root, repo).map_err(AppError::schema_invalid)
+}
+
+type Snapshot = BTreeMap<PathBuf, Option<(SystemTime, u64)>>;
+
+fn snapshot(repo_root: &Path, sources: &[PathBuf]) -> Snapshot {
+    let agents_dir = repo_root.join(".agents");
+    let state_dir = agents_dir.join("state");
+
+    let mut snap = Snapshot::new();
+    for entry in walkdir::WalkDir::new(&agents_dir)
+        .into_iter()
+        .filter_entry(|e| e.path() != state_dir)
+        .flatten()
+    {
+        if entry.file_type().is_file() {
+            snap.insert(entry.path().to_path_buf(), stat(entry.path()));
+        }
+    }
+
+    // Missing sources are recorded too, so creating one counts as a change.
+    for path in sources {
+        snap.insert(path.clone(), stat(path));
+    }
+
+    snap
+}
+
+fn stat(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
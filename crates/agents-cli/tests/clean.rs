@@ -124,3 +124,51 @@ fn prunes_empty_dirs() {
     assert!(!repo.join("gen/sub").exists());
     assert!(!repo.join("gen").exists());
 }
+
+#[test]
+fn delete_confirmation_fails_in_ci_and_is_recorded_with_yes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+    adapter(repo, "out.md");
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: { requiredFor: [delete] }\n",
+    );
+
+    let mut sync_cmd = support::agents_cmd();
+    sync_cmd
+        .current_dir(repo)
+        .args(["sync", "--agent", "a", "--backend", "materialize"]);
+    sync_cmd.assert().success();
+
+    let mut clean_cmd = support::agents_cmd();
+    clean_cmd
+        .current_dir(repo)
+        .args(["clean", "--agent", "a", "--ci"]);
+    clean_cmd
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains(
+            "error: delete requires confirmation by policy",
+        ))
+        .stderr(predicate::str::contains("hint: rerun with `--yes`"));
+    assert!(repo.join("out.md").is_file());
+
+    let mut clean_cmd = support::agents_cmd();
+    clean_cmd
+        .current_dir(repo)
+        .args(["clean", "--agent", "a", "--ci", "--yes"]);
+    clean_cmd
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("confirm: delete (flag)"));
+    assert!(!repo.join("out.md").exists());
+
+    let log = fs::read_to_string(repo.join(".agents/state/confirmations.jsonl")).unwrap();
+    let entry: serde_json::Value = serde_json::from_str(log.lines().next().unwrap()).unwrap();
+    assert_eq!(entry["command"], "clean");
+    assert_eq!(entry["policy"], "safe");
+    assert_eq!(entry["granted"], serde_json::json!(["delete"]));
+    assert_eq!(entry["via"], "flag");
+}
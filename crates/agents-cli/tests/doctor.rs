@@ -102,4 +102,5 @@ fn doctor_fix_creates_missing_state_gitignore() {
 
     let content = fs::read_to_string(repo.join(".agents/state/.gitignore")).unwrap();
     assert!(content.contains("state.yaml"));
+    assert!(content.lines().any(|l| l == "shims/"), "{content}");
 }
@@ -81,3 +81,127 @@ fn explain_reports_unmanaged_file_helpfully() {
         .failure()
         .stderr(predicate::str::contains("unmanaged file"));
 }
+
+#[test]
+fn explain_lists_every_mode_file_of_an_extended_mode() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: review, policy: safe }\n\
+         enabled: { modes: [default, review], policies: [safe], skills: [], adapters: [a] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\nBe careful.\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/review.md"),
+        "---\nid: review\nextends: default\n---\n\nReview only.\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/adapter.yaml"),
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: AGENTS.md
+    format: md
+    renderer: { type: template, template: t.hbs }
+"#,
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "{{{effective.mode.body}}}",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--agent", "a", "--backend", "materialize"])
+        .assert()
+        .success();
+    let out = fs::read_to_string(repo.join("AGENTS.md")).unwrap();
+    assert!(out.contains("Be careful.\n\nReview only.\n"), "{out}");
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["explain", "AGENTS.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "mode_sources:\n- .agents/modes/default.md\n- .agents/modes/review.md\n",
+        ));
+}
+
+#[test]
+fn explain_records_agent_specific_resolution() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe, strict], skills: [], adapters: [a, b] }\n\
+         byAgent: { a: { policy: strict } }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    for id in ["safe", "strict"] {
+        write_file(
+            &repo.join(format!(".agents/policies/{id}.yaml")),
+            &format!(
+                "id: {id}\ndescription: {id}\ncapabilities: {{}}\npaths: {{}}\nconfirmations: {{}}\n"
+            ),
+        );
+    }
+    for (agent, out) in [("a", "A.md"), ("b", "B.md")] {
+        write_file(
+            &repo.join(format!(".agents/adapters/{agent}/adapter.yaml")),
+            &format!(
+                "agentId: {agent}\nversion: '0.1'\n\
+                 backendDefaults: {{ preferred: materialize, fallback: materialize }}\n\
+                 outputs:\n  - path: {out}\n    format: md\n    renderer: {{ type: template, template: t.hbs }}\n"
+            ),
+        );
+        write_file(
+            &repo.join(format!(".agents/adapters/{agent}/templates/t.hbs")),
+            "policy={{effective.policy.id}}\n",
+        );
+    }
+
+    for agent in ["a", "b"] {
+        support::agents_cmd()
+            .current_dir(repo)
+            .args(["sync", "--agent", agent])
+            .assert()
+            .success();
+    }
+    assert!(fs::read_to_string(repo.join("A.md"))
+        .unwrap()
+        .contains("policy=strict"));
+    assert!(fs::read_to_string(repo.join("B.md"))
+        .unwrap()
+        .contains("policy=safe"));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["explain", "A.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "resolved_for: a\nmode: default\npolicy: strict\n",
+        ));
+}
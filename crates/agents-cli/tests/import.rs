@@ -65,3 +65,340 @@ fn import_dry_run_does_not_write_anything() {
 
     assert!(!root.join(".agents").exists());
 }
+
+#[test]
+fn import_claude_maps_permissions_into_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+
+    write_file(&root.join("CLAUDE.md"), "# Claude\n\nBe careful.\n");
+    write_file(
+        &root.join(".claude/settings.json"),
+        r#"{
+  "permissions": {
+    "deny": ["Read(./.env.production)", "Edit(infra/**)", "Bash(rm -rf:*)"],
+    "allow": ["Bash(cargo test:*)"]
+  },
+  "model": "sonnet"
+}
+"#,
+    );
+
+    let mut import = support::agents_cmd();
+    import
+        .current_dir(root)
+        .args(["import", "--from", "claude"]);
+    import
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ok: schemas valid"))
+        .stdout(predicate::str::contains(
+            "note: claude settings: ignored key: model",
+        ));
+
+    let snippet = fs::read_to_string(root.join(".agents/prompts/snippets/claude.md")).unwrap();
+    assert!(snippet.contains("Be careful."));
+
+    let policy = fs::read_to_string(root.join(".agents/policies/claude-import.yaml")).unwrap();
+    assert!(policy.contains(".env.production"));
+    assert!(policy.contains("infra/**"));
+    assert!(policy.contains("rm -rf"));
+    assert!(policy.contains("cargo test"));
+
+    let mode = fs::read_to_string(root.join(".agents/modes/claude-import.md")).unwrap();
+    assert!(mode.contains("policy: claude-import"));
+
+    let mut validate = support::agents_cmd();
+    validate.current_dir(root).arg("validate");
+    validate.assert().success();
+}
+
+#[test]
+fn import_cursor_maps_rule_globs_into_scopes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+
+    write_file(
+        &root.join(".cursor/rules/frontend.mdc"),
+        "---\ndescription: Frontend\nglobs: web/**/*.tsx, web/**/*.ts\nalwaysApply: false\n---\n\nUse hooks.\n",
+    );
+    write_file(
+        &root.join(".cursor/rules/general.md"),
+        "---\nalwaysApply: true\n---\n\nBe concise.\n",
+    );
+
+    let mut import = support::agents_cmd();
+    import
+        .current_dir(root)
+        .args(["import", "--from", "cursor"]);
+    import.assert().success();
+
+    let scope = fs::read_to_string(root.join(".agents/scopes/cursor-frontend.yaml")).unwrap();
+    assert!(scope.contains(r#"applyTo: ["web/**/*.tsx","web/**/*.ts"]"#));
+    assert!(scope.contains(r#"includeSnippets: ["cursor-frontend"]"#));
+
+    let snippet =
+        fs::read_to_string(root.join(".agents/prompts/snippets/cursor-frontend.md")).unwrap();
+    assert_eq!(snippet, "Use hooks.\n");
+
+    let mode = fs::read_to_string(root.join(".agents/modes/cursor-import.md")).unwrap();
+    assert!(mode.contains("includeSnippets: [cursor-general]"));
+
+    let mut validate = support::agents_cmd();
+    validate.current_dir(root).arg("validate");
+    validate.assert().success();
+}
+
+#[test]
+fn import_cursor_keeps_rules_that_share_a_stem() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+
+    write_file(&root.join(".cursor/rules/style.md"), "From md.\n");
+    write_file(
+        &root.join(".cursor/rules/style.mdc"),
+        "---\nglobs: web/**\n---\n\nFrom mdc.\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(root)
+        .args(["import", "--from", "cursor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "note: cursor: style.mdc imported as cursor-style-mdc (cursor-style is already taken)",
+        ));
+
+    let snippets = root.join(".agents/prompts/snippets");
+    assert_eq!(
+        fs::read_to_string(snippets.join("cursor-style.md")).unwrap(),
+        "From md.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(snippets.join("cursor-style-mdc.md")).unwrap(),
+        "From mdc.\n"
+    );
+    let scope = fs::read_to_string(root.join(".agents/scopes/cursor-style-mdc.yaml")).unwrap();
+    assert!(scope.contains(r#"includeSnippets: ["cursor-style-mdc"]"#));
+
+    support::agents_cmd()
+        .current_dir(root)
+        .arg("validate")
+        .assert()
+        .success();
+}
+
+fn assert_import_writes_snippet(from: &str, sources: &[(&str, &str)], snippet: &str) {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    for (p, c) in sources {
+        write_file(&root.join(p), c);
+    }
+
+    let mut import = support::agents_cmd();
+    import.current_dir(root).args(["import", "--from", from]);
+    import
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ok: schemas valid"));
+
+    assert!(
+        root.join(".agents/prompts/snippets")
+            .join(snippet)
+            .is_file(),
+        "{from}: missing snippet {snippet}"
+    );
+}
+
+#[test]
+fn import_agents_md_opencode_and_gemini_sources() {
+    assert_import_writes_snippet("codex", &[("AGENTS.md", "# Agents\n")], "codex.md");
+
+    assert_import_writes_snippet(
+        "opencode",
+        &[
+            (
+                "opencode.jsonc",
+                "{\n  // comment\n  \"instructions\": [\"docs/rules.md\"],\n  \"permission\": { \"bash\": { \"git push*\": \"deny\" } },\n}\n",
+            ),
+            ("docs/rules.md", "Rules.\n"),
+        ],
+        "opencode-rules.md",
+    );
+
+    assert_import_writes_snippet(
+        "gemini",
+        &[
+            (
+                ".gemini/settings.json",
+                "{ \"excludeTools\": [\"run_shell_command(rm -rf)\"] }\n",
+            ),
+            ("GEMINI.md", "Gemini context.\n"),
+        ],
+        "gemini.md",
+    );
+}
+
+#[test]
+fn import_merge_adds_new_content_without_clobbering() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+
+    let mut init = support::agents_cmd();
+    init.current_dir(root).arg("init");
+    init.assert().success();
+
+    write_file(
+        &root.join(".agents/prompts/snippets/cursor-style.md"),
+        "mine\n",
+    );
+    write_file(&root.join(".cursor/rules/style.md"), "theirs\n");
+    write_file(&root.join(".cursor/rules/general.md"), "Be concise.\n");
+
+    let mut dry = support::agents_cmd();
+    dry.current_dir(root)
+        .args(["import", "--from", "cursor", "--merge", "--dry-run"]);
+    dry.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "add: .agents/prompts/snippets/cursor-general.md",
+        ))
+        .stdout(predicate::str::contains(
+            "add: .agents/prompts/snippets/cursor-style-2.md (snippet `cursor-style` exists with different content)",
+        ))
+        .stdout(predicate::str::contains(
+            "hint: add mode `cursor-import` to enabled.modes",
+        ));
+    assert!(!root
+        .join(".agents/prompts/snippets/cursor-general.md")
+        .exists());
+
+    let mut merge = support::agents_cmd();
+    merge
+        .current_dir(root)
+        .args(["import", "--from", "cursor", "--merge"]);
+    merge
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ok: merged into .agents/"));
+
+    assert!(root
+        .join(".agents/prompts/snippets/cursor-general.md")
+        .is_file());
+    assert_eq!(
+        fs::read_to_string(root.join(".agents/prompts/snippets/cursor-style.md")).unwrap(),
+        "mine\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join(".agents/prompts/snippets/cursor-style-2.md")).unwrap(),
+        "theirs\n"
+    );
+    assert_eq!(
+        mode_snippets(&root.join(".agents/modes/cursor-import.md")),
+        ["cursor-general", "cursor-style-2"]
+    );
+
+    // A rule added later extends the existing import mode.
+    write_file(&root.join(".cursor/rules/extra.md"), "Extra.\n");
+
+    // Rewriting the mode is an overwrite, which the preset `safe` policy gates.
+    let mut denied = support::agents_cmd();
+    denied
+        .current_dir(root)
+        .args(["import", "--from", "cursor", "--merge", "--ci"]);
+    denied.assert().code(5).stderr(predicate::str::contains(
+        "error: overwrite requires confirmation by policy",
+    ));
+
+    let mut again = support::agents_cmd();
+    again
+        .current_dir(root)
+        .args(["import", "--from", "cursor", "--merge", "--yes"]);
+    again
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "extend: .agents/modes/cursor-import.md (includeSnippets += cursor-extra)",
+        ))
+        .stderr(predicate::str::contains("confirm: overwrite (flag)"));
+
+    // The renamed snippet is reused rather than imported a third time.
+    assert!(!root
+        .join(".agents/prompts/snippets/cursor-style-3.md")
+        .exists());
+    assert_eq!(
+        mode_snippets(&root.join(".agents/modes/cursor-import.md")),
+        ["cursor-general", "cursor-style-2", "cursor-extra"]
+    );
+}
+
+fn mode_snippets(path: &std::path::Path) -> Vec<String> {
+    let text = fs::read_to_string(path).unwrap();
+    let fm = text
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split("\n---\n").next())
+        .unwrap();
+    let fm: serde_yaml::Value = serde_yaml::from_str(fm).unwrap();
+    serde_yaml::from_value(fm["includeSnippets"].clone()).unwrap()
+}
+
+#[test]
+fn import_merge_extends_block_and_multiline_snippet_lists() {
+    for list in [
+        "includeSnippets:\n  - cursor-general\n",
+        "includeSnippets: [\n  cursor-general,\n]\n",
+    ] {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        let mut init = support::agents_cmd();
+        init.current_dir(root).arg("init");
+        init.assert().success();
+
+        let mode = root.join(".agents/modes/cursor-import.md");
+        write_file(
+            &mode,
+            &format!("---\nid: cursor-import\n{list}---\n\nKept as written.\n"),
+        );
+        write_file(
+            &root.join(".agents/prompts/snippets/cursor-general.md"),
+            "Be concise.\n",
+        );
+        write_file(&root.join(".cursor/rules/general.md"), "Be concise.\n");
+        write_file(&root.join(".cursor/rules/extra.md"), "Extra.\n");
+
+        let mut merge = support::agents_cmd();
+        merge
+            .current_dir(root)
+            .args(["import", "--from", "cursor", "--merge", "--yes"]);
+        merge.assert().success().stdout(predicate::str::contains(
+            "extend: .agents/modes/cursor-import.md (includeSnippets += cursor-extra)",
+        ));
+
+        assert_eq!(mode_snippets(&mode), ["cursor-general", "cursor-extra"]);
+        assert!(fs::read_to_string(&mode)
+            .unwrap()
+            .ends_with("---\n\nKept as written.\n"));
+
+        let mut validate = support::agents_cmd();
+        validate.current_dir(root).arg("validate");
+        validate.assert().success();
+    }
+}
+
+#[test]
+fn import_merge_requires_initialized_repo() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+
+    write_file(&root.join(".github/copilot-instructions.md"), "x\n");
+
+    let mut import = support::agents_cmd();
+    import
+        .current_dir(root)
+        .args(["import", "--from", "copilot", "--merge"]);
+    import
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("cannot merge"));
+}
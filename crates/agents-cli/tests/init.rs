@@ -18,7 +18,19 @@ fn init_standard_creates_agents_dir_and_validate_passes() {
 
     assert!(root.join(".agents/manifest.yaml").is_file());
     assert!(root.join(".agents/schemas/manifest.schema.json").is_file());
-    assert!(root.join(".agents/state/.gitignore").is_file());
+    let gitignore = std::fs::read_to_string(root.join(".agents/state/.gitignore")).unwrap();
+    for entry in [
+        "state.yaml",
+        "explain/",
+        "confirmations.jsonl",
+        "cache/",
+        "shims/",
+    ] {
+        assert!(
+            gitignore.lines().any(|l| l == entry),
+            "{entry}:\n{gitignore}"
+        );
+    }
 
     let mut validate = support::agents_cmd();
     validate.current_dir(root).arg("validate");
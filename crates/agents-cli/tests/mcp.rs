@@ -0,0 +1,184 @@
+use std::fs;
+
+use predicates::prelude::*;
+
+mod support;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn mcp_skill(id: &str) -> String {
+    format!(
+        "id: {id}\nversion: '0.1.0'\ntitle: {id}\ndescription: test\nactivation: mcp_tool\n\
+         interface: {{ type: mcp, entrypoint: ./server.js, args: ['--stdio'], env: {{ LOG: debug }} }}\n\
+         contract: {{ inputs: {{}}, outputs: {{}} }}\n\
+         requirements: {{ capabilities: {{ filesystem: read, exec: none, network: none }} }}\n"
+    )
+}
+
+fn read_json(path: &std::path::Path) -> serde_json::Value {
+    let text = fs::read_to_string(path).unwrap();
+    // opencode.jsonc starts with a line comment.
+    let text: String = text
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    serde_json::from_str(&text).unwrap_or_else(|e| panic!("{}: {e}\n{text}", path.display()))
+}
+
+#[test]
+fn sync_generates_policy_filtered_mcp_config_for_each_agent() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["init", "--preset", "agent-pack"])
+        .assert()
+        .success();
+
+    let manifest = repo.join(".agents/manifest.yaml");
+    let text = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        text.replace("skills: []", "skills: [fs-tools, blocked]"),
+    )
+    .unwrap();
+    let policy = repo.join(".agents/policies/safe.yaml");
+    let text = fs::read_to_string(&policy).unwrap();
+    fs::write(
+        &policy,
+        text.replace("denyServers: []", "denyServers: [blocked]"),
+    )
+    .unwrap();
+    write_file(
+        &repo.join(".agents/skills/fs-tools/skill.yaml"),
+        &mcp_skill("fs-tools"),
+    );
+    write_file(
+        &repo.join(".agents/skills/blocked/skill.yaml"),
+        &mcp_skill("blocked"),
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args([
+            "sync",
+            "--agent",
+            "claude",
+            "--agent",
+            "cursor",
+            "--agent",
+            "opencode",
+            "--agent",
+            "gemini-cli",
+            "--backend",
+            "materialize",
+        ])
+        .assert()
+        .success();
+
+    let expected = serde_json::json!({
+        "command": "./.agents/skills/fs-tools/server.js",
+        "args": ["--stdio"],
+        "env": { "LOG": "debug" },
+    });
+
+    for path in [".mcp.json", ".cursor/mcp.json", ".gemini/settings.json"] {
+        let v = read_json(&repo.join(path));
+        assert_eq!(v["mcpServers"]["fs-tools"], expected, "{path}");
+        assert!(v["mcpServers"].get("blocked").is_none(), "{path}");
+    }
+
+    let claude = read_json(&repo.join(".claude/settings.json"));
+    assert_eq!(
+        claude["enabledMcpjsonServers"],
+        serde_json::json!(["fs-tools"])
+    );
+
+    let opencode = read_json(&repo.join("opencode.jsonc"));
+    assert_eq!(
+        opencode["mcp"]["fs-tools"]["command"],
+        serde_json::json!(["./.agents/skills/fs-tools/server.js", "--stdio"])
+    );
+    assert_eq!(
+        opencode["mcp"]["fs-tools"]["environment"],
+        serde_json::json!({ "LOG": "debug" })
+    );
+    assert!(opencode["mcp"].get("blocked").is_none());
+}
+
+#[test]
+fn sync_without_mcp_skills_writes_no_mcp_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["init", "--preset", "agent-pack"])
+        .assert()
+        .success();
+    support::agents_cmd()
+        .current_dir(repo)
+        .args([
+            "sync",
+            "--agent",
+            "claude",
+            "--agent",
+            "cursor",
+            "--agent",
+            "opencode",
+            "--agent",
+            "gemini-cli",
+            "--backend",
+            "materialize",
+        ])
+        .assert()
+        .success();
+
+    assert!(!repo.join(".mcp.json").exists());
+    assert!(!repo.join(".cursor/mcp.json").exists());
+    assert!(read_json(&repo.join(".gemini/settings.json"))
+        .get("mcpServers")
+        .is_none());
+    assert!(read_json(&repo.join("opencode.jsonc")).get("mcp").is_none());
+}
+
+#[test]
+fn status_lists_mcp_servers_and_policy_exclusions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [a, b], adapters: [] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: { mcp: { allowServers: [a] } }\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(&repo.join(".agents/skills/a/skill.yaml"), &mcp_skill("a"));
+    write_file(&repo.join(".agents/skills/b/skill.yaml"), &mcp_skill("b"));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "mcp:\n  - a\n  - excluded b: not in policy allowServers\n",
+        ));
+}
@@ -1,3 +1,4 @@
+use predicates::prelude::*;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -133,3 +134,312 @@ fn run_executes_in_vfs_mount_workspace() {
     assert!(result.contains("output"));
     assert_eq!(fs::read_to_string(repo.join("out.md")).unwrap(), "repo\n");
 }
+
+fn limited_vfs_mount_repo(repo: &Path, limits: &str, agent_script: &str) {
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+defaults: { mode: default, policy: safe }\n\
+enabled: { modes: [default], policies: [safe], skills: [], adapters: [dummy] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        &format!(
+            "id: safe\ndescription: safe\ncapabilities: {{}}\npaths: {{}}\nconfirmations: {{}}\nlimits: {limits}\n"
+        ),
+    );
+    write_file(
+        &repo.join(".agents/adapters/dummy/adapter.yaml"),
+        "agentId: dummy\nversion: '0.1'\nbackendDefaults: { preferred: vfs_mount, fallback: materialize }\noutputs:\n  - path: out.md\n    format: md\n    renderer: { type: template, template: out.md.hbs }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/dummy/templates/out.md.hbs"),
+        "output\n",
+    );
+
+    let agent_path = repo.join("dummy-agent.sh");
+    write_file(&agent_path, agent_script);
+    make_executable(&agent_path);
+}
+
+#[cfg(unix)]
+#[test]
+fn run_vfs_mount_reports_changes_within_limits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(
+        repo,
+        "{ maxFilesChanged: 5, maxPatchLines: 10 }",
+        "#!/bin/sh\nset -eu\nprintf 'a\\nb\\n' > new.txt\n",
+    );
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["run", "./dummy-agent.sh", "--adapter", "dummy"])
+        .args(["--backend", "vfs-mount"]);
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "limits: files_changed=1 patch_lines=2",
+    ));
+    assert!(!repo.join("new.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_vfs_mount_fails_when_files_changed_exceeds_limit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(
+        repo,
+        "{ maxFilesChanged: 1 }",
+        "#!/bin/sh\nset -eu\necho a > a.txt\necho b > b.txt\n",
+    );
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["run", "./dummy-agent.sh", "--adapter", "dummy"])
+        .args(["--backend", "vfs-mount"]);
+
+    cmd.assert()
+        .code(6)
+        .stderr(predicate::str::contains("error: policy limits exceeded"))
+        .stderr(predicate::str::contains(
+            "limit: maxFilesChanged actual=2 max=1",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn run_vfs_mount_kills_agent_after_runtime_limit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(
+        repo,
+        "{ maxCommandRuntimeSec: 1 }",
+        "#!/bin/sh\nexec sleep 30\n",
+    );
+
+    let started = std::time::Instant::now();
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["run", "./dummy-agent.sh", "--adapter", "dummy"])
+        .args(["--backend", "vfs-mount"]);
+
+    cmd.assert().code(6).stderr(predicate::str::contains(
+        "limit: maxCommandRuntimeSec max=1",
+    ));
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+}
+
+#[cfg(unix)]
+const EDITING_AGENT: &str =
+    "#!/bin/sh\nset -eu\necho new > notes.txt\necho added > added.txt\nrm gone.txt\n";
+
+#[cfg(unix)]
+#[test]
+fn run_vfs_mount_writes_patch_of_agent_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(repo, "{}", EDITING_AGENT);
+    write_file(&repo.join("notes.txt"), "old\n");
+    write_file(&repo.join("gone.txt"), "bye\n");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["run", "./dummy-agent.sh", "--adapter", "dummy"])
+        .args(["--backend", "vfs-mount", "--patch", "agent.patch"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "changes: files_changed=3 patch_lines=4",
+        ))
+        .stdout(predicate::str::contains("modified: notes.txt"));
+
+    let patch = fs::read_to_string(repo.join("agent.patch")).unwrap();
+    assert!(patch.contains("--- a/notes.txt\n+++ b/notes.txt\n"));
+    assert!(patch.contains("-old\n+new\n"));
+    assert!(patch.contains("--- /dev/null\n+++ b/added.txt\n"));
+    assert!(patch.contains("--- a/gone.txt\n+++ /dev/null\n"));
+    assert!(!patch.contains("out.md"));
+
+    // Nothing is written back without `--apply`.
+    assert_eq!(fs::read_to_string(repo.join("notes.txt")).unwrap(), "old\n");
+    assert!(repo.join("gone.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_vfs_mount_apply_writes_changes_back() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(repo, "{}", EDITING_AGENT);
+    write_file(&repo.join("notes.txt"), "old\n");
+    write_file(&repo.join("gone.txt"), "bye\n");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["run", "./dummy-agent.sh", "--adapter", "dummy"])
+        .args(["--backend", "vfs-mount", "--apply", "--yes"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("apply: 3 files"));
+
+    assert_eq!(fs::read_to_string(repo.join("notes.txt")).unwrap(), "new\n");
+    assert_eq!(
+        fs::read_to_string(repo.join("added.txt")).unwrap(),
+        "added\n"
+    );
+    assert!(!repo.join("gone.txt").exists());
+    // Overlay outputs stay in the workspace.
+    assert!(!repo.join("out.md").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_apply_without_yes_requires_a_terminal() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(repo, "{}", EDITING_AGENT);
+    write_file(&repo.join("notes.txt"), "old\n");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["run", "./dummy-agent.sh", "--adapter", "dummy"])
+        .args(["--backend", "vfs-mount", "--apply"]);
+
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains("`--apply` requires confirmation"));
+    assert_eq!(fs::read_to_string(repo.join("notes.txt")).unwrap(), "old\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn run_vfs_mount_exposes_cli_shim_skills_on_path() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    limited_vfs_mount_repo(
+        repo,
+        "{}",
+        "#!/bin/sh\nout=\"$1\"\n\
+         echo '{\"name\":\"x\"}' | greet --loud > \"$out\"\n\
+         echo '{}' | greet 2> \"$out.err\"\necho \"exit=$?\" >> \"$out\"\n",
+    );
+
+    let manifest = repo.join(".agents/manifest.yaml");
+    let text = fs::read_to_string(&manifest).unwrap();
+    fs::write(&manifest, text.replace("skills: []", "skills: [greet]")).unwrap();
+    write_file(
+        &repo.join(".agents/skills/greet/skill.yaml"),
+        "id: greet\nversion: '0.1.0'\ntitle: greet\ndescription: test\nactivation: cli_shim\n\
+         interface: { type: script, entrypoint: ./greet.sh, args: [--json], env: { GREETING: hi } }\n\
+         contract:\n  inputs: { type: object, required: [name] }\n  outputs: { type: object, required: [greeting] }\n\
+         requirements: { capabilities: { filesystem: none, exec: none, network: none } }\n",
+    );
+    let tool = repo.join(".agents/skills/greet/greet.sh");
+    write_file(
+        &tool,
+        "#!/bin/sh\ncat > /dev/null\nprintf '{\"greeting\":\"%s\",\"args\":\"%s\"}\\n' \"$GREETING\" \"$*\"\n",
+    );
+    make_executable(&tool);
+
+    let result_path = repo.join("run-result.txt");
+    support::agents_cmd()
+        .current_dir(repo)
+        .args([
+            "run",
+            "./dummy-agent.sh",
+            "--adapter",
+            "dummy",
+            "--backend",
+            "vfs-mount",
+            "--",
+        ])
+        .arg(result_path.to_string_lossy().to_string())
+        .assert()
+        .success();
+
+    let result = fs::read_to_string(&result_path).unwrap();
+    assert!(
+        result.starts_with("{\"greeting\":\"hi\",\"args\":\"--json --loud\"}\n"),
+        "{result}"
+    );
+    assert!(result.contains("exit=4"), "{result}");
+    let err = fs::read_to_string(repo.join("run-result.txt.err")).unwrap();
+    assert!(
+        err.contains("stdin does not match contract.inputs"),
+        "{err}"
+    );
+
+    let log = fs::read_to_string(repo.join(".agents/state/shims/invocations.jsonl")).unwrap();
+    let lines: Vec<serde_json::Value> = log
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2, "{log}");
+    assert_eq!(lines[0]["skill"], "greet");
+    assert_eq!(lines[0]["input"], "ok");
+    assert_eq!(lines[0]["output"], "ok");
+    assert_eq!(lines[0]["exit_code"], 0);
+    assert_eq!(lines[1]["input"], "invalid");
+}
+
+#[cfg(unix)]
+#[test]
+fn shim_exec_passes_stdin_through_for_open_contracts() {
+    let tmp = tempfile::tempdir().unwrap();
+    let ws = tmp.path();
+    let tool = ws.join("tool.sh");
+    write_file(&tool, "#!/bin/sh\necho done\n");
+    make_executable(&tool);
+
+    let spec = ws.join(".agents-bin/.spec/t.json");
+    write_file(
+        &spec,
+        &serde_json::json!({
+            "skill_id": "t",
+            "entrypoint": "./tool.sh",
+            "args": [],
+            "env": {},
+            "inputs": {},
+            "outputs": {},
+            "log": ws.join("log.jsonl").to_string_lossy(),
+        })
+        .to_string(),
+    );
+
+    // The caller never closes stdin; the skill must still run to completion.
+    let mut child = support::agents_std_cmd()
+        .arg("shim-exec")
+        .arg(&spec)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let _stdin = child.stdin.take();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if std::time::Instant::now() > deadline {
+            let _ = child.kill();
+            panic!("shim-exec blocked on stdin");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    };
+    assert!(status.success());
+
+    let mut out = String::new();
+    std::io::Read::read_to_string(&mut child.stdout.take().unwrap(), &mut out).unwrap();
+    assert_eq!(out, "done\n");
+}
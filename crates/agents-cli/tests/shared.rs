@@ -74,3 +74,39 @@ fn sync_core_is_deterministic() {
     let b = fs::read(repo.join("AGENTS.md")).unwrap();
     assert_eq!(a, b);
 }
+
+#[test]
+fn diff_core_after_sync_is_noop() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let mut diff1 = support::agents_cmd();
+    diff1
+        .current_dir(repo)
+        .arg("diff")
+        .arg("--agent")
+        .arg("core");
+    diff1
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CREATE: AGENTS.md"));
+
+    let mut sync = support::agents_cmd();
+    sync.current_dir(repo)
+        .arg("sync")
+        .arg("--agent")
+        .arg("core");
+    sync.assert().success();
+
+    let mut diff2 = support::agents_cmd();
+    diff2
+        .current_dir(repo)
+        .arg("diff")
+        .arg("--agent")
+        .arg("core");
+    diff2
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("NOOP: AGENTS.md"));
+}
@@ -0,0 +1,196 @@
+use std::fs;
+
+use predicates::prelude::*;
+
+mod support;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn init_with_asset_skill(repo: &std::path::Path) {
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["init", "--preset", "agent-pack"])
+        .assert()
+        .success();
+
+    let manifest = repo.join(".agents/manifest.yaml");
+    let text = fs::read_to_string(&manifest).unwrap();
+    fs::write(&manifest, text.replace("skills: []", "skills: [lint]")).unwrap();
+
+    write_file(
+        &repo.join(".agents/skills/lint/skill.yaml"),
+        "id: lint\nversion: '0.1.0'\ntitle: lint\ndescription: test\nactivation: instruction_only\n\
+         interface: { type: cli }\n\
+         contract: { inputs: {}, outputs: {} }\n\
+         requirements: { capabilities: { filesystem: read, exec: none, network: none } }\n\
+         assets: { materialize: [RULES.md], mount: [data] }\n",
+    );
+    write_file(&repo.join(".agents/skills/lint/RULES.md"), "# Rules\n");
+    write_file(
+        &repo.join(".agents/skills/lint/data/config.json"),
+        "{\n  \"strict\": true\n}\n",
+    );
+}
+
+#[test]
+fn sync_materializes_stamped_skill_assets_once_across_adapters() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    init_with_asset_skill(repo);
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args([
+            "sync",
+            "--agent",
+            "claude",
+            "--agent",
+            "cursor",
+            "--backend",
+            "materialize",
+        ])
+        .assert()
+        .success();
+
+    let rules = fs::read_to_string(repo.join(".agents-skills/lint/RULES.md")).unwrap();
+    assert!(rules.starts_with("<!-- @generated by agents:"), "{rules}");
+    assert!(rules.ends_with("# Rules\n"), "{rules}");
+
+    let config = fs::read_to_string(repo.join(".agents-skills/lint/data/config.json")).unwrap();
+    let v: serde_json::Value = serde_json::from_str(&config).unwrap();
+    assert_eq!(v["strict"], serde_json::json!(true));
+    assert!(v.get("x_generated").is_some(), "{config}");
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["explain", ".agents-skills/lint/RULES.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("adapter: claude"))
+        .stdout(predicate::str::contains(".agents/skills/lint/RULES.md"));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["clean", "--agent", "claude", "--yes"])
+        .assert()
+        .success();
+    assert!(!repo.join(".agents-skills/lint/RULES.md").exists());
+}
+
+#[test]
+fn preview_lists_skill_assets() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    init_with_asset_skill(repo);
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["preview", "--agent", "claude"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "preview: .agents-skills/lint/RULES.md",
+        ))
+        .stdout(predicate::str::contains(
+            "preview: .agents-skills/lint/data/config.json",
+        ));
+}
+
+#[test]
+fn preview_rejects_missing_skill_asset() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    init_with_asset_skill(repo);
+    fs::remove_file(repo.join(".agents/skills/lint/RULES.md")).unwrap();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["preview", "--agent", "claude"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("asset not found: RULES.md"));
+}
+
+#[test]
+fn sync_copies_unstampable_assets_byte_for_byte_and_tracks_them_in_state() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    init_with_asset_skill(repo);
+
+    let script = "#!/bin/sh\necho lint";
+    let binary: &[u8] = &[0x89, b'P', b'N', b'G', 0x00, 0xff, 0xfe, b'\r', b'\n'];
+    write_file(&repo.join(".agents/skills/lint/data/run.sh"), script);
+    fs::write(repo.join(".agents/skills/lint/data/logo.png"), binary).unwrap();
+
+    let sync = || {
+        support::agents_cmd()
+            .current_dir(repo)
+            .args(["sync", "--agent", "claude", "--backend", "materialize"])
+            .assert()
+    };
+    sync().success();
+
+    let out = repo.join(".agents-skills/lint/data");
+    assert_eq!(fs::read_to_string(out.join("run.sh")).unwrap(), script);
+    assert_eq!(fs::read(out.join("logo.png")).unwrap(), binary);
+
+    let ledger = fs::read_to_string(repo.join(".agents/state/unstamped.json")).unwrap();
+    let ledger: serde_json::Value = serde_json::from_str(&ledger).unwrap();
+    let files = ledger["files"].as_object().unwrap();
+    assert!(
+        files.contains_key(".agents-skills/lint/data/run.sh"),
+        "{ledger}"
+    );
+    assert!(
+        files.contains_key(".agents-skills/lint/data/logo.png"),
+        "{ledger}"
+    );
+    assert!(
+        !files.contains_key(".agents-skills/lint/RULES.md"),
+        "{ledger}"
+    );
+
+    // Unchanged tracked files are re-synced; edited ones are left alone.
+    sync().success();
+    fs::write(out.join("run.sh"), "#!/bin/sh\necho edited\n").unwrap();
+    sync().failure().stderr(predicate::str::contains("drifted"));
+
+    fs::write(out.join("run.sh"), script).unwrap();
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["clean", "--agent", "claude", "--yes"])
+        .assert()
+        .success();
+    assert!(!out.join("run.sh").exists());
+    assert!(!out.join("logo.png").exists());
+    let ledger = fs::read_to_string(repo.join(".agents/state/unstamped.json")).unwrap();
+    assert!(!ledger.contains("run.sh"), "{ledger}");
+}
+
+#[test]
+fn sync_refuses_to_overwrite_untracked_file_at_unstamped_asset_path() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    init_with_asset_skill(repo);
+    write_file(&repo.join(".agents/skills/lint/data/run.sh"), "#!/bin/sh\n");
+    write_file(
+        &repo.join(".agents-skills/lint/data/run.sh"),
+        "#!/bin/sh\nmine\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--agent", "claude", "--backend", "materialize"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unmanaged"));
+    assert_eq!(
+        fs::read_to_string(repo.join(".agents-skills/lint/data/run.sh")).unwrap(),
+        "#!/bin/sh\nmine\n"
+    );
+}
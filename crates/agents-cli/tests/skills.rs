@@ -0,0 +1,58 @@
+use std::fs;
+
+mod support;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn generated_guidance_documents_enabled_skills() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["init", "--preset", "agent-pack"])
+        .assert()
+        .success();
+
+    let manifest = repo.join(".agents/manifest.yaml");
+    let text = fs::read_to_string(&manifest).unwrap();
+    fs::write(&manifest, text.replace("skills: []", "skills: [greet]")).unwrap();
+    write_file(
+        &repo.join(".agents/skills/greet/skill.yaml"),
+        "id: greet\nversion: '0.1.0'\ntitle: Greeter\ndescription: Greets people by name.\n\
+         activation: cli_shim\ninterface: { type: script, entrypoint: ./greet.sh }\n\
+         contract: { inputs: { type: object }, outputs: {} }\n\
+         requirements: { capabilities: { filesystem: read, exec: none, network: none } }\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args([
+            "sync", "--agent", "core", "--agent", "claude", "--agent", "cursor",
+        ])
+        .assert()
+        .success();
+
+    for path in ["AGENTS.md", "CLAUDE.md", ".cursor/rules/10-guidance.md"] {
+        let text = fs::read_to_string(repo.join(path)).unwrap();
+        assert!(text.contains("\n## Skills\n"), "{path}:\n{text}");
+        assert!(
+            text.contains("- **Greeter** (`greet`): Greets people by name.\n"),
+            "{path}:\n{text}"
+        );
+        assert!(
+            text.contains(r#"  - invoke: run `greet` (on PATH via `.agents-bin/` in sandboxed runs); stdin JSON must match `{"type":"object"}`"#),
+            "{path}:\n{text}"
+        );
+        assert!(
+            text.contains("  - requires: filesystem=read, exec=none, network=none\n"),
+            "{path}:\n{text}"
+        );
+    }
+}
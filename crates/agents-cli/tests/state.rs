@@ -0,0 +1,127 @@
+use predicates::prelude::*;
+
+mod support;
+use std::fs;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn base_repo(repo: &std::path::Path) {
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default, review], policies: [safe], skills: [], adapters: [] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/review.md"),
+        "---\nid: review\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/api.yaml"),
+        "id: api\napplyTo: [\"packages/api/**\"]\npriority: 0\noverrides: {}\n",
+    );
+}
+
+#[test]
+fn set_mode_persists_state_and_status_uses_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["set-mode", "review", "--backend", "materialize"])
+        .args(["--scope", "api"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("state: .agents/state/state.yaml"))
+        .stdout(predicate::str::contains("mode: review"));
+
+    let text = fs::read_to_string(repo.join(".agents/state/state.yaml")).unwrap();
+    assert!(text.contains("mode: review"));
+    assert!(text.contains("backend: materialize"));
+    assert!(text.contains("- api"));
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).arg("status");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("mode: review"))
+        .stdout(predicate::str::contains("api"));
+
+    // Unspecified fields are kept.
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["set-mode", "default"]);
+    cmd.assert().success();
+
+    let text = fs::read_to_string(repo.join(".agents/state/state.yaml")).unwrap();
+    assert!(text.contains("mode: default"));
+    assert!(text.contains("backend: materialize"));
+}
+
+#[test]
+fn set_mode_rejects_unknown_mode_and_scope() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["set-mode", "nope"]);
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains("mode is not enabled: nope"));
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["set-mode", "default", "--scope", "missing"]);
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown scope: missing"));
+
+    assert!(!repo.join(".agents/state/state.yaml").exists());
+}
+
+#[test]
+fn state_show_and_clear_round_trip() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["state", "show"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("state: none"));
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["set-mode", "review"]);
+    cmd.assert().success();
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["state", "show", "--json"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"mode\": \"review\""));
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["state", "clear"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("state: cleared"));
+    assert!(!repo.join(".agents/state/state.yaml").exists());
+}
@@ -75,3 +75,290 @@ fn status_json_output_contains_effective_fields() {
         .stdout(predicate::str::contains("\"scopes_matched\": []"))
         .stdout(predicate::str::contains("\"skills_enabled\": []"));
 }
+
+#[test]
+fn status_lists_skills_disabled_by_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [fetch], adapters: [] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/skills/fetch/skill.yaml"),
+        "id: fetch\nversion: '0.0.1'\ntitle: Fetch\ndescription: test\nactivation: instruction_only\ninterface: { type: cli }\n\
+         contract: { inputs: {}, outputs: {} }\n\
+         requirements: { capabilities: { filesystem: none, exec: none, network: read } }\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "skills:\n  - disabled fetch: needs network; policy disables it\n",
+        ));
+
+    let manifest = repo.join(".agents/manifest.yaml");
+    let text = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        format!("{text}resolution: {{ onConflict: error }}\n"),
+    )
+    .unwrap();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .arg("status")
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains(
+            "error: skill fetch not allowed by policy safe: needs network; policy disables it",
+        ));
+}
+
+#[test]
+fn status_profile_flag_applies_and_lists_profile_overrides() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default, audit], policies: [safe, locked], skills: [], adapters: [] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/audit.md"),
+        "---\nid: audit\n---\n\n",
+    );
+    for id in ["safe", "locked"] {
+        write_file(
+            &repo.join(format!(".agents/policies/{id}.yaml")),
+            &format!("id: {id}\ndescription: {id}\ncapabilities: {{}}\npaths: {{}}\nconfirmations: {{}}\n"),
+        );
+    }
+    write_file(
+        &repo.join(".agents/profiles/ci.yaml"),
+        "mode: audit\npolicy: locked\nbackend: materialize\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["status", "--profile", "ci"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "mode: audit\npolicy: locked\nprofile: ci\n  - mode: audit\n  - policy: locked\n  - backend: Materialize\nbackend: Materialize\n",
+        ));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["status", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"effective_mode\": \"default\""))
+        .stdout(predicate::str::contains("\"profile_overrides\": []"));
+}
+
+#[test]
+fn status_shows_flattened_policy_for_extends() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["init", "--preset", "ci-safe"])
+        .assert()
+        .success();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "policy: ci-safe\n  extends: safe\n  flattened:\n    id: ci-safe\n    extends:\n    - safe\n    description: CI-safe (no exec/network)\n",
+        ))
+        .stdout(predicate::str::contains("      exec:\n        enabled: false\n"));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["status", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"policy_extends\": [\n    \"safe\"\n  ]",
+        ))
+        .stdout(predicate::str::contains("\"maxFilesChanged\": 200"));
+}
+
+#[test]
+fn status_lists_values_from_the_user_overlay() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    let home = tempfile::tempdir().unwrap();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [], adapters: [] }\n\
+         resolution: { enableUserOverlay: true, userOverlayDefaults: [mode] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+
+    let overlay = home.path().join(".agents");
+    write_file(
+        &overlay.join("manifest.yaml"),
+        "defaults: { mode: personal }\n",
+    );
+    write_file(&overlay.join("prompts/snippets/tips.md"), "My tips\n");
+    write_file(
+        &overlay.join("modes/personal.md"),
+        "---\nid: personal\nincludeSnippets: [tips]\n---\nPersonal.\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .env("HOME", home.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mode: personal\n"))
+        .stdout(predicate::str::contains(
+            "user overlay:\n  - defaults.mode: personal\n  - mode: personal\n  - snippet: tips\n",
+        ));
+}
+
+#[test]
+fn status_trace_shows_the_layer_behind_each_value() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default, readonly-audit], policies: [safe], skills: [], adapters: [] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/readonly-audit.md"),
+        "---\nid: readonly-audit\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/state/state.yaml"),
+        "mode: readonly-audit\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["status", "--trace"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "trace:\n  - mode: readonly-audit (set by state; overrode defaults=default)\n  - policy: safe (set by defaults)\n",
+        ));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("trace:").not());
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["status", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"set_by\": \"state\""));
+}
+
+#[test]
+fn status_explains_merged_scope_policies() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: open }\n\
+         enabled: { modes: [default], policies: [open, locked], skills: [], adapters: [] }\n\
+         resolution: { mergeScopePolicies: true }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/open.yaml"),
+        "id: open\ndescription: open\ncapabilities: { exec: { allow: [curl] } }\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/locked.yaml"),
+        "id: locked\ndescription: locked\ncapabilities: { exec: { deny: [curl] } }\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/all.yaml"),
+        "id: all\napplyTo: ['**']\noverrides: { policy: open }\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/root.yaml"),
+        "id: root\napplyTo: ['.']\noverrides: { policy: locked }\n",
+    );
+    write_file(
+        &repo.join(".agents/state/state.yaml"),
+        "mode: default\nscopes: [all, root]\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "policy: open+locked\n  merged from: open, locked\n  conflict: exec: curl\n  flattened:\n",
+        ));
+}
@@ -1,5 +1,16 @@
 use assert_cmd::Command;
 
 pub fn agents_cmd() -> Command {
-    Command::new(assert_cmd::cargo::cargo_bin!("agents"))
+    Command::from_std(agents_std_cmd())
+}
+
+/// `agents_cmd` as a plain `std::process::Command`, for tests that spawn long-running commands.
+pub fn agents_std_cmd() -> std::process::Command {
+    let mut cmd = std::process::Command::new(assert_cmd::cargo::cargo_bin!("agents"));
+    // Keep the developer's `~/.agents` user overlay out of tests; tests that need one set HOME.
+    cmd.env(
+        "HOME",
+        std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-home"),
+    );
+    cmd
 }
@@ -133,3 +133,420 @@ fn sync_vfs_mount_reports_workspace_without_writing_repo() {
 
     assert_eq!(fs::read_to_string(repo.join("out.md")).unwrap(), "repo\n");
 }
+
+#[test]
+fn sync_renders_modes_and_snippets_from_the_user_overlay() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    let home = tempfile::tempdir().unwrap();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/adapters/a/adapter.yaml"),
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: materialize, fallback: materialize }\noutputs:\n  - path: out.md\n    format: md\n    renderer: { type: template, template: t.hbs }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "mode={{generation.stamp.mode}}\n{{#each effective.prompts.snippets}}{{this.id}}\n{{/each}}",
+    );
+
+    let overlay = home.path().join(".agents");
+    write_file(&overlay.join("prompts/snippets/tips.md"), "My tips\n");
+    write_file(
+        &overlay.join("modes/personal.md"),
+        "---\nid: personal\nincludeSnippets: [tips]\n---\nPersonal.\n",
+    );
+
+    // Overlay modes are personal: set-mode accepts them without `enabled.modes`.
+    support::agents_cmd()
+        .current_dir(repo)
+        .env("HOME", home.path())
+        .args(["set-mode", "personal"])
+        .assert()
+        .success();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .env("HOME", home.path())
+        .args(["sync", "--agent", "a"])
+        .assert()
+        .success();
+
+    let out = fs::read_to_string(repo.join("out.md")).unwrap();
+    assert!(out.contains("mode=personal\ntips\n"), "{out}");
+}
+
+fn two_adapter_repo(repo: &std::path::Path, b_path: &str) {
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe, backend: materialize }\n\
+         enabled: { modes: [default], policies: [safe], skills: [], adapters: [a, b] }\n",
+    );
+
+    for (id, path) in [("a", "a.md"), ("b", b_path)] {
+        write_file(
+            &repo.join(format!(".agents/adapters/{id}/adapter.yaml")),
+            &format!("agentId: {id}\nversion: '0.1'\nbackendDefaults: {{ preferred: materialize, fallback: materialize }}\noutputs:\n  - path: {path}\n    format: md\n    renderer: {{ type: template, template: t.hbs }}\n"),
+        );
+        write_file(
+            &repo.join(format!(".agents/adapters/{id}/templates/t.hbs")),
+            &format!("{id}\n"),
+        );
+    }
+}
+
+#[test]
+fn sync_all_writes_every_enabled_adapter_grouped_by_adapter() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    two_adapter_repo(repo, "b.md");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).arg("sync").arg("--all");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "adapter: a written=1 skipped=0 conflict=0",
+        ))
+        .stdout(predicate::str::contains(
+            "adapter: b written=1 skipped=0 conflict=0",
+        ))
+        .stdout(predicate::str::contains("sync: adapters=2 written=2"));
+
+    assert!(repo.join("a.md").is_file());
+    assert!(repo.join("b.md").is_file());
+}
+
+#[test]
+fn sync_repeated_agent_writes_nothing_when_any_adapter_conflicts() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    two_adapter_repo(repo, "b.md");
+
+    // Unmanaged file blocks adapter b; adapter a must not be written either.
+    write_file(&repo.join("b.md"), "manual\n");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .arg("sync")
+        .arg("--agent")
+        .arg("a")
+        .arg("--agent")
+        .arg("b");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("conflict: b: b.md"))
+        .stderr(predicate::str::contains("no outputs were written"));
+
+    assert!(!repo.join("a.md").exists());
+    assert_eq!(fs::read_to_string(repo.join("b.md")).unwrap(), "manual\n");
+}
+
+#[test]
+fn sync_all_detects_cross_adapter_path_collisions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    two_adapter_repo(repo, "a.md");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).arg("sync").arg("--all");
+
+    cmd.assert()
+        .failure()
+        .code(5)
+        .stderr(predicate::str::contains(
+            "output collision across adapters at path: a.md: a, b",
+        ));
+
+    assert!(!repo.join("a.md").exists());
+}
+
+#[test]
+fn sync_all_works_on_the_agent_pack_preset() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["init", "--preset", "agent-pack"])
+        .assert()
+        .success();
+
+    // codex also targets AGENTS.md; core owns that shared surface and writes it once.
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "adapter: codex written=0 skipped=0 conflict=0",
+        ))
+        .stdout(predicate::str::contains(
+            "adapter: core written=1 skipped=0 conflict=0",
+        ))
+        .stdout(predicate::str::contains("sync: adapters=8"));
+
+    let agents_md = fs::read_to_string(repo.join("AGENTS.md")).unwrap();
+    assert!(agents_md.contains("\"adapterAgentId\":\"core\""));
+}
+
+#[test]
+fn sync_all_falls_back_to_materialize_when_no_backend_is_configured() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    two_adapter_repo(repo, "b.md");
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [], adapters: [a, b] }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/b/adapter.yaml"),
+        "agentId: b\nversion: '0.1'\nbackendDefaults: { preferred: vfs_container, fallback: materialize }\noutputs:\n  - path: b.md\n    format: md\n    renderer: { type: template, template: t.hbs }\n",
+    );
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sync: adapters=2 written=2"));
+
+    // An explicit non-materialize backend is still rejected.
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--all", "--backend", "vfs-container"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "multi-adapter sync requires the materialize backend",
+        ));
+}
+
+#[test]
+fn sync_backend_follows_cli_then_state_then_profile() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [], adapters: [a] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/adapter.yaml"),
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: vfs_container, fallback: materialize }\noutputs:\n  - path: out.md\n    format: md\n    renderer: { type: template, template: t.hbs }\n",
+    );
+    write_file(&repo.join(".agents/adapters/a/templates/t.hbs"), "hello\n");
+    write_file(
+        &repo.join(".agents/profiles/local.yaml"),
+        "backend: materialize\n",
+    );
+
+    // Without a profile the adapter's preferred backend applies.
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--agent", "a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("VfsContainer"));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["diff", "--agent", "a", "--profile", "local"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("create=1"));
+
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--agent", "a", "--profile", "local"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sync: written=1"));
+    assert!(repo.join("out.md").is_file());
+
+    // A backend pinned in state wins over the profile's; `--backend` wins over both.
+    write_file(
+        &repo.join(".agents/state/state.yaml"),
+        "mode: default\nbackend: vfs_container\n",
+    );
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--agent", "a", "--profile", "local"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("VfsContainer"));
+    support::agents_cmd()
+        .current_dir(repo)
+        .args(["sync", "--agent", "a", "--backend", "materialize"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn sync_requires_overwrite_confirmation_for_unmanaged_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: { requiredFor: [overwrite] }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/adapter.yaml"),
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: materialize, fallback: materialize }\noutputs:\n  - path: out.md\n    format: md\n    renderer: { type: template, template: t.hbs }\n    driftDetection: { method: sha256, stamp: comment }\n    writePolicy: { mode: always }\n",
+    );
+    write_file(&repo.join(".agents/adapters/a/templates/t.hbs"), "hello\n");
+    write_file(&repo.join("out.md"), "manual\n");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["sync", "--agent", "a", "--ci"]);
+    cmd.assert()
+        .code(5)
+        .stderr(predicate::str::contains(
+            "error: overwrite requires confirmation by policy",
+        ))
+        .stderr(predicate::str::contains("action: overwrite out.md"));
+    assert_eq!(fs::read_to_string(repo.join("out.md")).unwrap(), "manual\n");
+
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo)
+        .args(["sync", "--agent", "a", "--ci", "--yes"]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("confirm: overwrite (flag)"));
+    assert!(fs::read_to_string(repo.join("out.md"))
+        .unwrap()
+        .contains("hello"));
+
+    // Once the file is generated, re-syncing is not an overwrite.
+    let mut cmd = support::agents_cmd();
+    cmd.current_dir(repo).args(["sync", "--agent", "a", "--ci"]);
+    cmd.assert().success();
+}
+
+/// Lines from the child's stdout and stderr, in arrival order.
+/// Kills and reaps the child when dropped, so a failing assertion does not leak the process.
+struct ChildGuard(std::process::Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn spawn_lines(cmd: &mut std::process::Command) -> (ChildGuard, std::sync::mpsc::Receiver<String>) {
+    use std::io::BufRead;
+
+    let mut child = cmd
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+    let tx2 = tx.clone();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout)
+            .lines()
+            .map_while(Result::ok)
+        {
+            let _ = tx.send(line);
+        }
+    });
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stderr)
+            .lines()
+            .map_while(Result::ok)
+        {
+            let _ = tx2.send(line);
+        }
+    });
+    (ChildGuard(child), rx)
+}
+
+fn wait_for(rx: &std::sync::mpsc::Receiver<String>, needle: &str) -> Vec<String> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+    let mut seen = vec![];
+    while let Some(left) = deadline.checked_duration_since(std::time::Instant::now()) {
+        let Ok(line) = rx.recv_timeout(left) else {
+            break;
+        };
+        let done = line.contains(needle);
+        seen.push(line);
+        if done {
+            return seen;
+        }
+    }
+    panic!("timed out waiting for {needle:?}; got {seen:?}");
+}
+
+#[test]
+fn sync_watch_resyncs_changes_and_reports_errors_inline() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    let adapter = "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: materialize, fallback: materialize }\noutputs:\n  - path: out.md\n    format: md\n    renderer: { type: template, template: t.hbs }\n    driftDetection: { method: sha256, stamp: comment }\n    writePolicy: { mode: always }\n";
+    write_file(&repo.join(".agents/adapters/a/adapter.yaml"), adapter);
+    write_file(&repo.join(".agents/adapters/a/templates/t.hbs"), "hello\n");
+    write_file(&repo.join("out.md"), "");
+
+    let (mut child, rx) = spawn_lines(
+        support::agents_std_cmd()
+            .current_dir(repo)
+            .args(["sync", "--agent", "a", "--watch"]),
+    );
+
+    let first = wait_for(&rx, "watch: waiting");
+    assert!(first.iter().any(|l| l == "write: out.md"), "{first:?}");
+    assert!(fs::read_to_string(repo.join("out.md"))
+        .unwrap()
+        .contains("hello\n"));
+
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "hello again\n",
+    );
+    let second = wait_for(&rx, "watch: waiting");
+    assert!(second.iter().any(|l| l == "write: out.md"), "{second:?}");
+    assert!(fs::read_to_string(repo.join("out.md"))
+        .unwrap()
+        .contains("hello again\n"));
+
+    // A broken adapter is reported and the watch keeps going.
+    write_file(
+        &repo.join(".agents/adapters/a/adapter.yaml"),
+        "agentId: [\n",
+    );
+    let broken = wait_for(&rx, "watch: waiting");
+    assert!(broken.iter().any(|l| l.starts_with("error:")), "{broken:?}");
+
+    write_file(&repo.join(".agents/adapters/a/adapter.yaml"), adapter);
+    let fixed = wait_for(&rx, "watch: waiting");
+    assert!(
+        fixed.iter().any(|l| l.starts_with("sync: written=")),
+        "{fixed:?}"
+    );
+    assert!(child.0.try_wait().unwrap().is_none());
+}
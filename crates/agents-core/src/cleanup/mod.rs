@@ -1,11 +1,15 @@
 use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::fsutil::{self, RepoPath};
 use crate::loadag::RepoConfig;
 use crate::outputs::plan_outputs;
 use crate::resolv::EffectiveConfig;
-use crate::stamps::{compute_sha256_hex, parse_stamp, strip_existing_stamp};
+use crate::stamps::{
+    compute_bytes_sha256_hex, compute_sha256_hex, parse_stamp, strip_existing_stamp,
+    UnstampedLedger,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SkipReason {
@@ -48,6 +52,8 @@ pub fn delete_paths(
     dry_run: bool,
 ) -> Result<DeleteReport, CleanupError> {
     let mut report = DeleteReport::default();
+    let mut ledger = UnstampedLedger::load(repo_root);
+    let mut ledger_changed = false;
 
     for rp in paths {
         let abs = repo_root.join(rp.as_str());
@@ -68,10 +74,15 @@ pub fn delete_paths(
         report.deleted.push(rp.clone());
 
         if !dry_run {
+            ledger_changed |= ledger.forget(rp.as_str());
             prune_empty_parents(repo_root, &abs, &mut report.pruned_dirs)?;
         }
     }
 
+    if ledger_changed {
+        ledger.save(repo_root)?;
+    }
+
     Ok(report)
 }
 
@@ -128,6 +139,8 @@ fn prune_empty_parents(
 /// - stamp generator is `agents`
 /// - stamp adapter matches the requested agent
 /// - the current content (without stamp) matches the stamped sha256
+///
+/// Unstamped outputs use their `UnstampedLedger` entry in place of the stamp.
 pub fn identify_deletable(
     repo_root: &Path,
     repo: &RepoConfig,
@@ -137,14 +150,44 @@ pub fn identify_deletable(
     let mut eligible_by_path: BTreeMap<String, RepoPath> = BTreeMap::new();
     let mut skipped: Vec<SkippedPath> = vec![];
 
+    let ledger = UnstampedLedger::load(repo_root);
+    let repo = Arc::new(repo.clone());
     for agent_id in agent_ids {
-        let plan_res = plan_outputs(repo_root, repo.clone(), effective, agent_id)?;
+        let plan_res = plan_outputs(repo_root, Arc::clone(&repo), effective, agent_id)?;
         for out in &plan_res.plan.outputs {
             let abs = repo_root.join(out.path.as_str());
             if !abs.is_file() {
                 continue;
             }
 
+            if out.stamp_method().is_none() {
+                let reason = match ledger.get(out.path.as_str()) {
+                    None => Some(SkipReason::NoStamp),
+                    Some(meta) if meta.adapter_agent_id != *agent_id => {
+                        Some(SkipReason::DifferentAdapter)
+                    }
+                    Some(meta)
+                        if compute_bytes_sha256_hex(&fsutil::read_bytes(&abs)?)
+                            != meta.content_sha256 =>
+                    {
+                        Some(SkipReason::Drifted)
+                    }
+                    Some(_) => None,
+                };
+                match reason {
+                    Some(reason) => skipped.push(SkippedPath {
+                        path: out.path.clone(),
+                        reason,
+                    }),
+                    None => {
+                        eligible_by_path
+                            .entry(out.path.as_str().to_string())
+                            .or_insert_with(|| out.path.clone());
+                    }
+                }
+                continue;
+            }
+
             let existing = fsutil::read_to_string(&abs)?;
             let Some(stamp) = parse_stamp(&existing) else {
                 skipped.push(SkippedPath {
@@ -2,16 +2,19 @@ use std::collections::BTreeSet;
 use std::path::Path;
 
 use crate::fsutil;
-use crate::outputs::{OutputPlan, PlannedOutput};
-use crate::stamps::{classify, strip_existing_stamp, DriftStatus};
-use crate::templ::TemplateEngine;
+use crate::outputs::{
+    render_planned_bytes, render_planned_content, OutputPlan, PlannedOutput, RenderError,
+};
+use crate::stamps::{
+    classify, classify_unstamped, strip_existing_stamp, DriftStatus, UnstampedLedger,
+};
 
 use super::{detect_stale_generated, unified_diff_for, DiffEntry, DiffKind, DiffReport};
 
 #[derive(Debug, thiserror::Error)]
 pub enum DriftxError {
-    #[error("template error: {0}")]
-    Template(#[from] crate::templ::TemplateError),
+    #[error("render error: {0}")]
+    Render(#[from] RenderError),
 
     #[error("io error: {0}")]
     Fs(#[from] fsutil::FsError),
@@ -21,44 +24,69 @@ pub enum DriftxError {
 
     #[error("stamp error: {0}")]
     Stamp(#[from] crate::stamps::StampError),
-
-    #[error("missing template_dir for template renderer")]
-    MissingTemplateDir,
-
-    #[error("renderer type not implemented in driftx: {0:?}")]
-    UnsupportedRenderer(crate::model::RendererType),
 }
 
 pub fn diff_plan(repo_root: &Path, plan: &OutputPlan) -> Result<DiffReport, DriftxError> {
     let mut entries = vec![];
+    let ledger = UnstampedLedger::load(repo_root);
 
     for out in &plan.outputs {
-        entries.push(diff_one(repo_root, out)?);
+        if out.stamp_method().is_none() {
+            let planned = render_planned_bytes(repo_root, out)?;
+            let drift = classify_unstamped(
+                &repo_root.join(out.path.as_str()),
+                &planned,
+                ledger.get(out.path.as_str()),
+                &out.drift_detection,
+            )?;
+            entries.push(diff_unstamped_output(repo_root, out, &planned, drift)?);
+            continue;
+        }
+
+        // Render planned bytes without stamp.
+        let planned_without_stamp = render_planned_content(repo_root, out)?;
+
+        // Use drift classification.
+        let drift = classify(
+            &repo_root.join(out.path.as_str()),
+            &planned_without_stamp,
+            &out.drift_detection,
+        )?;
+
+        entries.push(diff_planned_output(
+            repo_root,
+            out,
+            &planned_without_stamp,
+            drift,
+        )?);
     }
 
-    // Optional: detect generated files that are no longer planned.
-    //
-    // v1 scope: only files stamped by agents AND matching this plan's adapter id.
+    entries.extend(stale_entries(repo_root, plan)?);
+
+    Ok(DiffReport { entries })
+}
+
+/// Generated files that are no longer planned.
+///
+/// v1 scope: only files stamped by agents AND matching this plan's adapter id.
+pub fn stale_entries(repo_root: &Path, plan: &OutputPlan) -> Result<Vec<DiffEntry>, DriftxError> {
     let planned_paths: BTreeSet<String> = plan
         .outputs
         .iter()
         .map(|o| o.path.as_str().to_string())
         .collect();
-    let stale = detect_stale_generated(repo_root, &plan.agent_id, &planned_paths)?;
-    entries.extend(stale);
-
-    Ok(DiffReport { entries })
+    detect_stale_generated(repo_root, &plan.agent_id, &planned_paths)
 }
 
-fn diff_one(repo_root: &Path, out: &PlannedOutput) -> Result<DiffEntry, DriftxError> {
+/// Diff one planned output whose content has already been rendered and classified.
+pub fn diff_planned_output(
+    repo_root: &Path,
+    out: &PlannedOutput,
+    planned_without_stamp: &str,
+    drift: DriftStatus,
+) -> Result<DiffEntry, DriftxError> {
     let target_path = repo_root.join(out.path.as_str());
 
-    // Render planned bytes without stamp.
-    let planned_without_stamp = render_planned(out)?;
-
-    // Use drift classification.
-    let drift = classify(&target_path, &planned_without_stamp, &out.drift_detection)?;
-
     // Read existing if present.
     let existing = if target_path.exists() {
         Some(fsutil::read_to_string(&target_path)?)
@@ -95,20 +123,20 @@ fn diff_one(repo_root: &Path, out: &PlannedOutput) -> Result<DiffEntry, DriftxEr
             // Diff against empty.
             Some(unified_diff_for(
                 "",
-                &planned_without_stamp,
+                planned_without_stamp,
                 "(missing)",
                 out.path.as_str(),
             ))
         }
         DiffKind::Update | DiffKind::Drifted => Some(unified_diff_for(
             &existing_without_stamp,
-            &planned_without_stamp,
+            planned_without_stamp,
             "(existing)",
             out.path.as_str(),
         )),
         DiffKind::UnmanagedExists => Some(unified_diff_for(
             &existing_without_stamp,
-            &planned_without_stamp,
+            planned_without_stamp,
             "(unmanaged)",
             out.path.as_str(),
         )),
@@ -124,20 +152,58 @@ fn diff_one(repo_root: &Path, out: &PlannedOutput) -> Result<DiffEntry, DriftxEr
     })
 }
 
-fn render_planned(out: &PlannedOutput) -> Result<String, DriftxError> {
-    match out.renderer.type_ {
-        crate::model::RendererType::Template => {
-            let dir = out
-                .template_dir
-                .as_ref()
-                .ok_or(DriftxError::MissingTemplateDir)?;
-            let mut engine = TemplateEngine::new();
-            engine.register_partials_from_dir(dir)?;
+/// `diff_planned_output` for an unstamped output (see `UnstampedLedger`). Text content gets a
+/// unified diff; binary content is only reported as changed.
+pub fn diff_unstamped_output(
+    repo_root: &Path,
+    out: &PlannedOutput,
+    planned: &[u8],
+    drift: DriftStatus,
+) -> Result<DiffEntry, DriftxError> {
+    let target_path = repo_root.join(out.path.as_str());
+    let existing = if target_path.exists() {
+        fsutil::read_bytes(&target_path)?
+    } else {
+        vec![]
+    };
 
-            let template_name = out.renderer.template.as_deref().unwrap_or("<missing>");
+    let kind = match drift {
+        DriftStatus::Missing => DiffKind::Create,
+        DriftStatus::Unmanaged => DiffKind::UnmanagedExists,
+        DriftStatus::Clean if existing == planned => DiffKind::Noop,
+        DriftStatus::Clean => DiffKind::Update,
+        DriftStatus::Drifted => DiffKind::Drifted,
+    };
 
-            Ok(engine.render(template_name, &out.render_context)?)
-        }
-        other => Err(DriftxError::UnsupportedRenderer(other)),
-    }
+    let label = match drift {
+        DriftStatus::Missing => "(missing)",
+        DriftStatus::Unmanaged => "(unmanaged)",
+        _ => "(existing)",
+    };
+
+    let (details, unified_diff) = match (
+        kind,
+        std::str::from_utf8(&existing),
+        std::str::from_utf8(planned),
+    ) {
+        (DiffKind::Noop, _, _) => (None, None),
+        (_, Ok(existing), Ok(planned)) => (
+            None,
+            Some(unified_diff_for(
+                existing,
+                planned,
+                label,
+                out.path.as_str(),
+            )),
+        ),
+        _ => (Some("binary content differs".to_string()), None),
+    };
+
+    Ok(DiffEntry {
+        path: out.path.as_str().to_string(),
+        kind,
+        drift: Some(drift),
+        details,
+        unified_diff,
+    })
 }
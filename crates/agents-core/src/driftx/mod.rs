@@ -3,7 +3,9 @@ mod diff;
 mod stale;
 mod types;
 
-pub use compare::{diff_plan, DriftxError};
+pub use compare::{
+    diff_plan, diff_planned_output, diff_unstamped_output, stale_entries, DriftxError,
+};
 pub use diff::unified_diff_for;
 pub use stale::detect_stale_generated;
 pub use types::{DiffEntry, DiffKind, DiffReport};
@@ -3,7 +3,9 @@ use std::path::{Component, Path};
 
 use walkdir::WalkDir;
 
-use crate::stamps::parse_stamp;
+use crate::stamps::{
+    parse_stamp, UnstampedLedger, COMMENT_STAMP_PREFIX, FRONTMATTER_STAMP_KEY, JSON_STAMP_FIELD,
+};
 
 use super::{DiffEntry, DiffKind, DriftxError};
 
@@ -43,6 +45,11 @@ pub fn detect_stale_generated(
             source: e,
         })?;
 
+        // Cheap byte scan first; most repo files carry no stamp at all.
+        if !has_stamp_marker(&bytes) {
+            continue;
+        }
+
         let content = match String::from_utf8(bytes) {
             Ok(s) => s.replace("\r\n", "\n"),
             Err(_) => continue,
@@ -69,11 +76,40 @@ pub fn detect_stale_generated(
         });
     }
 
+    // Unstamped outputs are recognized by their ledger entry instead.
+    let ledger = UnstampedLedger::load(repo_root);
+    for (path, meta) in &ledger.files {
+        if meta.adapter_agent_id != adapter_agent_id
+            || planned_paths.contains(path)
+            || !repo_root.join(path).is_file()
+        {
+            continue;
+        }
+
+        out.push(DiffEntry {
+            path: path.clone(),
+            kind: DiffKind::Delete,
+            drift: None,
+            details: Some("generated output exists but is no longer planned".to_string()),
+            unified_diff: None,
+        });
+    }
+
     // Ensure deterministic ordering.
     out.sort_by(|a, b| a.path.cmp(&b.path));
     Ok(out)
 }
 
+fn has_stamp_marker(bytes: &[u8]) -> bool {
+    [
+        COMMENT_STAMP_PREFIX,
+        FRONTMATTER_STAMP_KEY,
+        JSON_STAMP_FIELD,
+    ]
+    .iter()
+    .any(|m| bytes.windows(m.len()).any(|w| w == m.as_bytes()))
+}
+
 fn is_skipped_dir(path: &Path) -> bool {
     // Skip .git and .agents by default to avoid scanning internal config.
     if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
@@ -29,8 +29,37 @@ pub struct ExplainRenderer {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ExplainEffectiveConfig {
+    /// The agent whose `byAgent` overrides were applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+
+    /// For a per-scope output, the scope whose config it was rendered with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope_id: Option<String>,
+
     pub mode_id: String,
+
+    /// Mode files that make up `mode_id`, parents first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mode_source_paths: Vec<String>,
+
     pub policy_id: String,
+
+    /// Policies `policy_id` inherits from, nearest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_extends: Vec<String>,
+
+    /// Scope policies merged into `policy_id` (`resolution.mergeScopePolicies`), and entries they
+    /// both allowed and denied.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_merged_from: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_conflicts: Vec<String>,
+
+    /// The policy after `extends` (or merging) is applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<serde_json::Value>,
+
     pub profile: Option<String>,
     pub backend: BackendKind,
 
@@ -71,8 +100,19 @@ pub fn build_explain_source_maps(
                 sources: p.renderer.sources.clone(),
             },
             effective: ExplainEffectiveConfig {
+                agent_id: skel.and_then(|s| s.agent_id.clone()),
+                scope_id: skel.and_then(|s| s.scope_id.clone()),
                 mode_id: skel.map(|s| s.mode_id.clone()).unwrap_or_default(),
+                mode_source_paths: skel
+                    .map(|s| s.mode_source_paths.clone())
+                    .unwrap_or_default(),
                 policy_id: skel.map(|s| s.policy_id.clone()).unwrap_or_default(),
+                policy_extends: skel.map(|s| s.policy_extends.clone()).unwrap_or_default(),
+                policy_merged_from: skel
+                    .map(|s| s.policy_merged_from.clone())
+                    .unwrap_or_default(),
+                policy_conflicts: skel.map(|s| s.policy_conflicts.clone()).unwrap_or_default(),
+                policy: serde_json::to_value(&p.render_context.effective.policy).ok(),
                 profile: p.render_context.profile.clone(),
                 backend: plan.backend,
                 scopes_matched: p.render_context.scopes_matched.clone(),
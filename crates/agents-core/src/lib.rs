@@ -8,15 +8,18 @@ pub mod loadag;
 pub mod matwiz;
 pub mod model;
 pub mod outputs;
+pub mod pipeline;
 pub mod prompts;
 pub mod resolv;
 pub mod schemas;
 pub mod shared;
 pub mod skillpl;
 pub mod stamps;
+pub mod statex;
 pub mod templ;
 pub mod vfsctr;
 pub mod vfsmnt;
+pub mod wsdiff;
 
 pub fn hello_core() -> &'static str {
     "agents-core"
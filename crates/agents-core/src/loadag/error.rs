@@ -19,6 +19,12 @@ pub enum LoadError {
 
     #[error("missing required id {id} in {kind}")]
     MissingId { kind: &'static str, id: String },
+
+    #[error("extends cycle in {kind}: {chain}")]
+    ExtendsCycle { kind: &'static str, chain: String },
+
+    #[error("scope {scope} has invalid root {root}: must be a repo-relative path without `..`")]
+    InvalidScopeRoot { scope: String, root: String },
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::loadag::{check_extends, LoadError};
+use crate::model::{ModeFile, ModeFrontmatter, Policy, ToolIntent};
+
+/// A line in a mode body that is replaced by the bodies of the modes it extends.
+pub const PARENT_MARKER: &str = "{{> parent}}";
+
+/// `(path to the caps block, allow key, deny key)` for every allow/deny pair in a policy.
+const ALLOW_DENY_PAIRS: &[(&[&str], &str, &str)] = &[
+    (&["capabilities", "exec"], "allow", "deny"),
+    (&["capabilities", "network"], "allowHosts", "denyHosts"),
+    (&["capabilities", "mcp"], "allowServers", "denyServers"),
+    (&["paths"], "allow", "deny"),
+];
+
+/// Resolve `extends` for raw policy documents (keyed by id) and parse the results.
+///
+/// Parents are merged in order, then the policy itself on top: mappings merge key by key,
+/// lists union (parent entries first), and scalars override. For each allow/deny pair an entry
+/// the child allows is dropped from the inherited deny list, unless `deny_overrides_allow` is
+/// set, in which case denied entries are dropped from the allow list instead.
+pub fn flatten_policies(
+    dir: &Path,
+    raw: BTreeMap<String, Value>,
+    deny_overrides_allow: bool,
+) -> Result<BTreeMap<String, Policy>, LoadError> {
+    let mut graph = BTreeMap::new();
+    for (id, doc) in &raw {
+        let extends = match doc.get("extends") {
+            None | Some(Value::Null) => vec![],
+            Some(v) => serde_yaml::from_value::<Extends>(v.clone())
+                .map(|e| e.0)
+                .map_err(|e| LoadError::Parse {
+                    path: dir.join(format!("{id}.yaml")),
+                    message: format!("extends: {e}"),
+                })?,
+        };
+        graph.insert(id.clone(), extends);
+    }
+    check_extends("policies", &graph)?;
+
+    let mut flat: BTreeMap<String, Value> = BTreeMap::new();
+    for id in raw.keys() {
+        flatten_one(id, &raw, &graph, deny_overrides_allow, &mut flat);
+    }
+
+    flat.into_iter()
+        .map(|(id, doc)| {
+            let policy: Policy = serde_yaml::from_value(doc).map_err(|e| LoadError::Parse {
+                path: dir.join(format!("{id}.yaml")),
+                message: e.to_string(),
+            })?;
+            Ok((id, policy))
+        })
+        .collect()
+}
+
+#[derive(serde::Deserialize)]
+struct Extends(#[serde(deserialize_with = "crate::model::one_or_many")] Vec<String>);
+
+/// `check_extends` has ruled out cycles and missing parents, so the recursion terminates.
+fn flatten_one(
+    id: &str,
+    raw: &BTreeMap<String, Value>,
+    graph: &BTreeMap<String, Vec<String>>,
+    deny_overrides_allow: bool,
+    flat: &mut BTreeMap<String, Value>,
+) -> Value {
+    if let Some(done) = flat.get(id) {
+        return done.clone();
+    }
+
+    let own = raw[id].clone();
+    let parents = &graph[id];
+
+    let doc = if parents.is_empty() {
+        own
+    } else {
+        let mut base: Option<Value> = None;
+        for parent in parents {
+            let p = flatten_one(parent, raw, graph, deny_overrides_allow, flat);
+            base = Some(match base {
+                None => p,
+                Some(b) => merge_policy(b, &p, deny_overrides_allow),
+            });
+        }
+        let mut doc = merge_policy(
+            base.expect("parents is not empty"),
+            &own,
+            deny_overrides_allow,
+        );
+
+        // Identity is never inherited.
+        if let Value::Mapping(m) = &mut doc {
+            for key in ["id", "extends"] {
+                match own.get(key) {
+                    Some(v) => m.insert(key.into(), v.clone()),
+                    None => m.remove(key),
+                };
+            }
+        }
+        doc
+    };
+
+    flat.insert(id.to_string(), doc.clone());
+    doc
+}
+
+fn merge_policy(parent: Value, child: &Value, deny_overrides_allow: bool) -> Value {
+    let mut merged = merge_value(parent, child);
+
+    for (path, allow_key, deny_key) in ALLOW_DENY_PAIRS {
+        let child_block = path.iter().try_fold(child, |v, k| v.get(*k));
+        let child_allow = child_block
+            .map(|b| string_list(b.get(*allow_key)))
+            .unwrap_or_default();
+        let child_deny = child_block
+            .map(|b| string_list(b.get(*deny_key)))
+            .unwrap_or_default();
+
+        let Some(Value::Mapping(block)) = path.iter().try_fold(&mut merged, |v, k| v.get_mut(*k))
+        else {
+            continue;
+        };
+
+        if deny_overrides_allow {
+            let deny = string_list(block.get(*deny_key));
+            retain_strings(block, allow_key, |s| !deny.contains(&s.to_string()));
+        } else {
+            retain_strings(block, deny_key, |s| {
+                let s = s.to_string();
+                !child_allow.contains(&s) || child_deny.contains(&s)
+            });
+        }
+    }
+
+    merged
+}
+
+fn merge_value(parent: Value, child: &Value) -> Value {
+    match (parent, child) {
+        (Value::Mapping(mut p), Value::Mapping(c)) => {
+            for (k, cv) in c {
+                let merged = match p.remove(k) {
+                    Some(pv) => merge_value(pv, cv),
+                    None => cv.clone(),
+                };
+                p.insert(k.clone(), merged);
+            }
+            Value::Mapping(p)
+        }
+        (Value::Sequence(mut p), Value::Sequence(c)) => {
+            for item in c {
+                if !p.contains(item) {
+                    p.push(item.clone());
+                }
+            }
+            Value::Sequence(p)
+        }
+        (_, c) => c.clone(),
+    }
+}
+
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(Value::as_sequence)
+        .map(|s| {
+            s.iter()
+                .filter_map(|i| i.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn retain_strings(block: &mut Mapping, key: &str, keep: impl Fn(&str) -> bool) {
+    if let Some(Value::Sequence(items)) = block.get_mut(key) {
+        items.retain(|i| i.as_str().is_none_or(&keep));
+    }
+}
+
+/// Resolve `extends` in mode frontmatter.
+///
+/// Parents are applied in order, then the mode itself: `title` and `policy` override, and the
+/// skill, snippet and `toolIntent` lists union. The parents' bodies come before the mode's own
+/// body, or replace its `{{> parent}}` line when it has one.
+pub fn flatten_modes(
+    raw: BTreeMap<String, ModeFile>,
+) -> Result<BTreeMap<String, ModeFile>, LoadError> {
+    let graph: BTreeMap<String, Vec<String>> = raw
+        .iter()
+        .map(|(id, m)| {
+            let extends = m
+                .frontmatter
+                .as_ref()
+                .map(|fm| fm.extends.clone())
+                .unwrap_or_default();
+            (id.clone(), extends)
+        })
+        .collect();
+    check_extends("modes", &graph)?;
+
+    let mut flat: BTreeMap<String, ModeFile> = BTreeMap::new();
+    for id in raw.keys() {
+        flatten_mode(id, &raw, &graph, &mut flat);
+    }
+    Ok(flat)
+}
+
+fn flatten_mode(
+    id: &str,
+    raw: &BTreeMap<String, ModeFile>,
+    graph: &BTreeMap<String, Vec<String>>,
+    flat: &mut BTreeMap<String, ModeFile>,
+) -> ModeFile {
+    if let Some(done) = flat.get(id) {
+        return done.clone();
+    }
+
+    let own = &raw[id];
+    let parents = &graph[id];
+
+    let mode = if parents.is_empty() {
+        own.clone()
+    } else {
+        let mut fm = ModeFrontmatter::default();
+        let mut parent_body = String::new();
+        let mut sources = vec![];
+        for parent in parents {
+            let p = flatten_mode(parent, raw, graph, flat);
+            if let Some(pfm) = &p.frontmatter {
+                fm = merge_frontmatter(fm, pfm);
+            }
+            parent_body = join_sections(&parent_body, &p.body);
+            for src in p.sources {
+                if !sources.contains(&src) {
+                    sources.push(src);
+                }
+            }
+        }
+
+        let own_fm = own.frontmatter.clone().unwrap_or_default();
+        let mut fm = merge_frontmatter(fm, &own_fm);
+        fm.id = own_fm.id;
+        fm.extends = own_fm.extends;
+
+        sources.extend(own.sources.iter().cloned());
+        ModeFile {
+            frontmatter: Some(fm),
+            body: compose_body(&parent_body, &own.body),
+            sources,
+        }
+    };
+
+    flat.insert(id.to_string(), mode.clone());
+    mode
+}
+
+fn merge_frontmatter(base: ModeFrontmatter, child: &ModeFrontmatter) -> ModeFrontmatter {
+    let tool_intent = match (base.tool_intent, &child.tool_intent) {
+        (Some(b), Some(c)) => Some(ToolIntent {
+            allow: union(b.allow, &c.allow),
+            deny: union(b.deny, &c.deny),
+        }),
+        (b, c) => c.clone().or(b),
+    };
+
+    ModeFrontmatter {
+        id: child.id.clone(),
+        extends: child.extends.clone(),
+        title: child.title.clone().or(base.title),
+        policy: child.policy.clone().or(base.policy),
+        enable_skills: union(base.enable_skills, &child.enable_skills),
+        disable_skills: union(base.disable_skills, &child.disable_skills),
+        include_snippets: union(base.include_snippets, &child.include_snippets),
+        tool_intent,
+    }
+}
+
+fn union(mut base: Vec<String>, extra: &[String]) -> Vec<String> {
+    for s in extra {
+        if !base.contains(s) {
+            base.push(s.clone());
+        }
+    }
+    base
+}
+
+fn compose_body(parent: &str, own: &str) -> String {
+    let lines: Vec<&str> = own.lines().collect();
+    match lines.iter().position(|l| l.trim() == PARENT_MARKER) {
+        Some(i) => {
+            let before = lines[..i].join("\n");
+            let after = lines[i + 1..].join("\n");
+            join_sections(&join_sections(&before, parent), &after)
+        }
+        None => join_sections(parent, own),
+    }
+}
+
+/// `a` and `b` separated by one blank line; empty sections are dropped.
+fn join_sections(a: &str, b: &str) -> String {
+    let (a, b) = (a.trim_matches('\n'), b.trim_matches('\n'));
+    match (a.trim().is_empty(), b.trim().is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => format!("{b}\n"),
+        (false, true) => format!("{a}\n"),
+        (false, false) => format!("{a}\n\n{b}\n"),
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
 use crate::loadag::LoadError;
 use crate::loadag::RepoConfig;
 
@@ -63,5 +66,149 @@ pub fn check_referential_integrity(cfg: &RepoConfig) -> Result<(), LoadError> {
         }
     }
 
+    // byAgent
+    for (agent, o) in &cfg.manifest.by_agent {
+        if !cfg.adapters.contains_key(agent) {
+            return Err(LoadError::MissingId {
+                kind: "byAgent",
+                id: agent.clone(),
+            });
+        }
+        if let Some(id) = o.mode.iter().find(|id| !cfg.modes.contains_key(*id)) {
+            return Err(LoadError::MissingId {
+                kind: "byAgent.mode",
+                id: id.clone(),
+            });
+        }
+        if let Some(id) = o.policy.iter().find(|id| !cfg.policies.contains_key(*id)) {
+            return Err(LoadError::MissingId {
+                kind: "byAgent.policy",
+                id: id.clone(),
+            });
+        }
+    }
+
+    // scopes
+    for scope in cfg.scopes.values() {
+        if let Some(root) = &scope.root {
+            // Absolute paths and `..` would put `{{scopeDir}}` outputs outside the repo.
+            let escapes = Path::new(root)
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+            if escapes {
+                return Err(LoadError::InvalidScopeRoot {
+                    scope: scope.id.clone(),
+                    root: root.clone(),
+                });
+            }
+        }
+        for (agent, o) in &scope.by_agent {
+            if !cfg.adapters.contains_key(agent) {
+                return Err(LoadError::MissingId {
+                    kind: "scope.byAgent",
+                    id: agent.clone(),
+                });
+            }
+            if let Some(id) = o.mode.iter().find(|id| !cfg.modes.contains_key(*id)) {
+                return Err(LoadError::MissingId {
+                    kind: "scope.byAgent.mode",
+                    id: id.clone(),
+                });
+            }
+            if let Some(id) = o.policy.iter().find(|id| !cfg.policies.contains_key(*id)) {
+                return Err(LoadError::MissingId {
+                    kind: "scope.byAgent.policy",
+                    id: id.clone(),
+                });
+            }
+        }
+    }
+
+    // profiles
+    for profile in cfg.profiles.values() {
+        let missing = |kind: &'static str, id: &String| LoadError::MissingId {
+            kind,
+            id: id.clone(),
+        };
+        if let Some(id) = profile.mode.iter().find(|id| !cfg.modes.contains_key(*id)) {
+            return Err(missing("profile.mode", id));
+        }
+        if let Some(id) = profile
+            .policy
+            .iter()
+            .find(|id| !cfg.policies.contains_key(*id))
+        {
+            return Err(missing("profile.policy", id));
+        }
+        if let Some(id) = profile
+            .adapters
+            .iter()
+            .flatten()
+            .find(|id| !cfg.adapters.contains_key(*id))
+        {
+            return Err(missing("profile.adapters", id));
+        }
+        if let Some(id) = profile
+            .enable_skills
+            .iter()
+            .chain(&profile.disable_skills)
+            .find(|id| !cfg.skills.contains_key(*id))
+        {
+            return Err(missing("profile.skills", id));
+        }
+        if let Some(id) = profile
+            .include_snippets
+            .iter()
+            .find(|id| !cfg.prompts.snippets.contains_key(*id))
+        {
+            return Err(missing("profile.includeSnippets", id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check an `extends` graph (`id -> parent ids`): every parent exists and no id inherits from
+/// itself. `kind` names the collection in errors.
+pub fn check_extends(
+    kind: &'static str,
+    graph: &BTreeMap<String, Vec<String>>,
+) -> Result<(), LoadError> {
+    fn visit(
+        kind: &'static str,
+        graph: &BTreeMap<String, Vec<String>>,
+        id: &str,
+        path: &mut Vec<String>,
+        done: &mut Vec<String>,
+    ) -> Result<(), LoadError> {
+        if done.iter().any(|d| d == id) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|p| p == id) {
+            let mut chain = path[start..].to_vec();
+            chain.push(id.to_string());
+            return Err(LoadError::ExtendsCycle {
+                kind,
+                chain: chain.join(" -> "),
+            });
+        }
+
+        let parents = graph.get(id).ok_or_else(|| LoadError::MissingId {
+            kind,
+            id: id.to_string(),
+        })?;
+        path.push(id.to_string());
+        for parent in parents {
+            visit(kind, graph, parent, path, done)?;
+        }
+        path.pop();
+        done.push(id.to_string());
+        Ok(())
+    }
+
+    let mut done = vec![];
+    for id in graph.keys() {
+        visit(kind, graph, id, &mut vec![], &mut done)?;
+    }
     Ok(())
 }
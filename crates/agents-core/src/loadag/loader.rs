@@ -2,9 +2,9 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::fsutil;
-use crate::loadag::{LoadError, LoadReport, RepoConfig};
+use crate::loadag::{LoadError, LoadReport, RepoConfig, UserOverlay, UserOverlayDefaults};
 use crate::model::{
-    parse_frontmatter_markdown, Adapter, Manifest, ModeFile, Policy, Scope, Skill, State,
+    parse_frontmatter_markdown, Adapter, Manifest, ModeFile, Profile, Scope, Skill, State,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -74,45 +74,29 @@ pub fn load_repo_config(
         }
     }
 
-    // Load snippets
-    let mut snippets: BTreeMap<String, String> = BTreeMap::new();
-    let snippets_dir = prompts_dir.join("snippets");
-    if snippets_dir.is_dir() {
-        for entry in std::fs::read_dir(&snippets_dir).map_err(|e| LoadError::Io {
-            path: snippets_dir.clone(),
-            source: e,
-        })? {
-            let entry = entry.map_err(|e| LoadError::Io {
-                path: snippets_dir.clone(),
-                source: e,
-            })?;
-
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) != Some("md") {
-                continue;
-            }
-            let stem = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or_default()
-                .to_string();
-            let text = fsutil::read_to_string(&path).map_err(|e| LoadError::Io {
-                path: path.clone(),
-                source: io_from_fs_error(e),
-            })?;
-            snippets.insert(stem, text);
-        }
-    }
+    let snippets = load_snippets_dir(&prompts_dir.join("snippets"))?;
 
     // Collections
+    let policies_dir = agents_dir.join("policies");
+    let raw_policies = load_yaml_dir::<serde_yaml::Value>(&policies_dir, "policies", |p| {
+        p.get("id")
+            .and_then(|id| id.as_str())
+            .unwrap_or_default()
+            .to_string()
+    })?;
+    let deny_overrides_allow = manifest
+        .resolution
+        .as_ref()
+        .is_some_and(|r| r.deny_overrides_allow);
     let policies =
-        load_yaml_dir::<Policy>(&agents_dir.join("policies"), "policies", |p| p.id.clone())?;
+        crate::loadag::flatten_policies(&policies_dir, raw_policies, deny_overrides_allow)?;
 
     let (skills, skill_dirs) = load_skills_dir(&agents_dir.join("skills"))?;
 
     let scopes = load_yaml_dir::<Scope>(&agents_dir.join("scopes"), "scopes", |s| s.id.clone())?;
 
     let (modes, _mode_sources) = load_modes_dir(&agents_dir.join("modes"))?;
+    let modes = crate::loadag::flatten_modes(modes)?;
 
     let (mut adapters, adapter_template_dirs) = load_adapters_dir(&agents_dir.join("adapters"))?;
 
@@ -139,6 +123,7 @@ pub fn load_repo_config(
             snippets,
         },
         state,
+        user_overlay: None,
     };
 
     crate::loadag::check_referential_integrity(&cfg)?;
@@ -146,6 +131,162 @@ pub fn load_repo_config(
     Ok((cfg, report))
 }
 
+/// The user overlay dir: `<root>/.agents`, where `root` defaults to `$HOME`.
+pub fn user_overlay_dir(root: Option<&Path>) -> Option<PathBuf> {
+    root.map(Path::to_path_buf)
+        .or_else(|| std::env::var_os("HOME").map(PathBuf::from))
+        .map(|r| fsutil::agents_dir(&r))
+}
+
+/// Merge the user overlay at `dir` into `cfg`.
+///
+/// Every part of the overlay is optional: snippets, modes and policies are added under ids the
+/// repo does not define (overlay modes and policies may extend repo ones), and the manifest is
+/// only read for its `defaults`. A missing `dir` leaves `cfg` untouched.
+pub fn apply_user_overlay(cfg: &mut RepoConfig, dir: &Path) -> Result<(), LoadError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+
+    #[derive(serde::Deserialize)]
+    struct OverlayManifest {
+        #[serde(default)]
+        defaults: UserOverlayDefaults,
+    }
+
+    let manifest_path = dir.join("manifest.yaml");
+    let defaults = if manifest_path.is_file() {
+        let text = fsutil::read_to_string(&manifest_path).map_err(|e| LoadError::Io {
+            path: manifest_path.clone(),
+            source: io_from_fs_error(e),
+        })?;
+        serde_yaml::from_str::<OverlayManifest>(&text)
+            .map_err(|e| LoadError::Parse {
+                path: manifest_path.clone(),
+                message: e.to_string(),
+            })?
+            .defaults
+    } else {
+        UserOverlayDefaults::default()
+    };
+
+    let mut overlay = UserOverlay {
+        dir: dir.to_path_buf(),
+        defaults,
+        ..Default::default()
+    };
+
+    for (id, text) in load_snippets_dir(&dir.join("prompts/snippets"))? {
+        if !cfg.prompts.snippets.contains_key(&id) {
+            cfg.prompts.snippets.insert(id.clone(), text);
+            overlay.snippets.push(id);
+        }
+    }
+
+    // Flatten overlay modes against the (already flattened) repo modes so they can extend them.
+    let (own_modes, _) = load_modes_dir(&dir.join("modes"))?;
+    let mut modes: BTreeMap<String, ModeFile> = cfg
+        .modes
+        .iter()
+        .map(|(id, m)| {
+            let mut m = m.clone();
+            if let Some(fm) = &mut m.frontmatter {
+                fm.extends.clear();
+            }
+            (id.clone(), m)
+        })
+        .collect();
+    let own_mode_ids: Vec<String> = own_modes
+        .keys()
+        .filter(|id| !cfg.modes.contains_key(*id))
+        .cloned()
+        .collect();
+    for id in &own_mode_ids {
+        modes.insert(id.clone(), own_modes[id].clone());
+    }
+    let mut modes = crate::loadag::flatten_modes(modes)?;
+    for id in own_mode_ids {
+        cfg.modes
+            .insert(id.clone(), modes.remove(&id).expect("flattened"));
+        overlay.modes.push(id);
+    }
+
+    let policies_dir = dir.join("policies");
+    let own_policies = load_yaml_dir::<serde_yaml::Value>(&policies_dir, "policies", |p| {
+        p.get("id")
+            .and_then(|id| id.as_str())
+            .unwrap_or_default()
+            .to_string()
+    })?;
+    let mut policies: BTreeMap<String, serde_yaml::Value> = BTreeMap::new();
+    for (id, p) in &cfg.policies {
+        let mut p = p.clone();
+        p.extends.clear();
+        let value = serde_yaml::to_value(&p).map_err(|e| LoadError::Parse {
+            path: policies_dir.join(format!("{id}.yaml")),
+            message: e.to_string(),
+        })?;
+        policies.insert(id.clone(), value);
+    }
+    let own_policy_ids: Vec<String> = own_policies
+        .keys()
+        .filter(|id| !cfg.policies.contains_key(*id))
+        .cloned()
+        .collect();
+    for id in &own_policy_ids {
+        policies.insert(id.clone(), own_policies[id].clone());
+    }
+    let deny_overrides_allow = cfg
+        .manifest
+        .resolution
+        .as_ref()
+        .is_some_and(|r| r.deny_overrides_allow);
+    let mut policies =
+        crate::loadag::flatten_policies(&policies_dir, policies, deny_overrides_allow)?;
+    for id in own_policy_ids {
+        cfg.policies
+            .insert(id.clone(), policies.remove(&id).expect("flattened"));
+        overlay.policies.push(id);
+    }
+
+    cfg.user_overlay = Some(overlay);
+    Ok(())
+}
+
+fn load_snippets_dir(dir: &Path) -> Result<BTreeMap<String, String>, LoadError> {
+    let mut snippets: BTreeMap<String, String> = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(snippets);
+    }
+
+    for entry in std::fs::read_dir(dir).map_err(|e| LoadError::Io {
+        path: dir.to_path_buf(),
+        source: e,
+    })? {
+        let entry = entry.map_err(|e| LoadError::Io {
+            path: dir.to_path_buf(),
+            source: e,
+        })?;
+
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let text = fsutil::read_to_string(&path).map_err(|e| LoadError::Io {
+            path: path.clone(),
+            source: io_from_fs_error(e),
+        })?;
+        snippets.insert(stem, text);
+    }
+
+    Ok(snippets)
+}
+
 fn load_yaml_dir<T: serde::de::DeserializeOwned>(
     dir: &Path,
     kind: &'static str,
@@ -295,7 +436,14 @@ fn load_modes_dir(dir: &Path) -> Result<LoadedMap<ModeFile>, LoadError> {
             return Err(LoadError::DuplicateId { kind: "modes", id });
         }
 
-        modes.insert(id.clone(), ModeFile { frontmatter, body });
+        modes.insert(
+            id.clone(),
+            ModeFile {
+                frontmatter,
+                body,
+                sources: vec![path.clone()],
+            },
+        );
         sources.insert(id, path);
     }
 
@@ -356,7 +504,7 @@ fn load_adapters_dir(dir: &Path) -> Result<LoadedMap<Adapter>, LoadError> {
     Ok((adapters, template_dirs))
 }
 
-fn load_profiles_dir(dir: &Path) -> Result<BTreeMap<String, serde_yaml::Value>, LoadError> {
+fn load_profiles_dir(dir: &Path) -> Result<BTreeMap<String, Profile>, LoadError> {
     let mut out = BTreeMap::new();
     if !dir.is_dir() {
         return Ok(out);
@@ -383,7 +531,7 @@ fn load_profiles_dir(dir: &Path) -> Result<BTreeMap<String, serde_yaml::Value>,
             path: path.clone(),
             source: io_from_fs_error(e),
         })?;
-        let v: serde_yaml::Value = serde_yaml::from_str(&text).map_err(|e| LoadError::Parse {
+        let v: Profile = serde_yaml::from_str(&text).map_err(|e| LoadError::Parse {
             path: path.clone(),
             message: e.to_string(),
         })?;
@@ -1,9 +1,11 @@
 pub mod error;
+pub mod extends;
 pub mod integrity;
 pub mod loader;
 pub mod repo_config;
 
 pub use error::*;
+pub use extends::*;
 pub use integrity::*;
 pub use loader::*;
 pub use repo_config::*;
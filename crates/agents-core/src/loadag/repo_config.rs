@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use crate::model::{Adapter, Manifest, ModeFile, Policy, Scope, Skill, State};
+use crate::model::{
+    Adapter, BackendKind, Manifest, ModeFile, Policy, Profile, Scope, Skill, State,
+};
 
 #[derive(Debug, Clone)]
 pub struct PromptLibrary {
@@ -27,9 +29,74 @@ pub struct RepoConfig {
     pub adapters: BTreeMap<String, Adapter>,
     pub adapter_template_dirs: BTreeMap<String, PathBuf>,
 
-    pub profiles: BTreeMap<String, serde_yaml::Value>,
+    pub profiles: BTreeMap<String, Profile>,
 
     pub prompts: PromptLibrary,
 
     pub state: Option<State>,
+
+    /// Set once `apply_user_overlay` has merged a user overlay into this config.
+    pub user_overlay: Option<UserOverlay>,
+}
+
+/// What a user overlay (usually `~/.agents`) contributed to a repo config.
+#[derive(Debug, Clone, Default)]
+pub struct UserOverlay {
+    pub dir: PathBuf,
+
+    /// Ids defined only by the overlay; when both define an id the repo's entry is kept.
+    pub modes: Vec<String>,
+    pub policies: Vec<String>,
+    pub snippets: Vec<String>,
+
+    /// The overlay manifest's defaults. They only apply to the fields listed in the repo's
+    /// `resolution.userOverlayDefaults`.
+    pub defaults: UserOverlayDefaults,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct UserOverlayDefaults {
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub policy: Option<String>,
+    #[serde(default)]
+    pub backend: Option<BackendKind>,
+}
+
+impl RepoConfig {
+    /// The active profile id: `cli` if given, else the one pinned in state, else
+    /// `manifest.defaults.profile`.
+    pub fn selected_profile<'a>(&'a self, cli: Option<&'a str>) -> Option<&'a str> {
+        cli.or_else(|| self.state.as_ref().and_then(|s| s.profile.as_deref()))
+            .or(self.manifest.defaults.profile.as_deref())
+    }
+
+    /// `manifest.resolution.enableUserOverlay`; on when the manifest has no `resolution` block.
+    pub fn user_overlay_enabled(&self) -> bool {
+        self.manifest
+            .resolution
+            .as_ref()
+            .is_none_or(|r| r.enable_user_overlay)
+    }
+
+    /// Every policy `id` inherits from, nearest first (empty if it extends nothing).
+    pub fn policy_chain(&self, id: &str) -> Vec<String> {
+        let mut out: Vec<String> = vec![];
+        let mut stack: Vec<&str> = vec![id];
+        while let Some(cur) = stack.pop() {
+            let Some(policy) = self.policies.get(cur) else {
+                continue;
+            };
+            for parent in policy.extends.iter().rev() {
+                if !out.contains(parent) {
+                    stack.push(parent);
+                }
+            }
+            if cur != id && !out.iter().any(|o| o == cur) {
+                out.push(cur.to_string());
+            }
+        }
+        out
+    }
 }
@@ -1,10 +1,10 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-use crate::fsutil;
+use crate::fsutil::{self, RepoPath};
 use crate::model::OutputFormat;
 use crate::outputs::OutputPlan;
-use crate::stamps::parse_stamp;
+use crate::stamps::{parse_stamp, DriftStatus, UnstampedLedger};
 
 use super::{
     ApplyReport, Backend, BackendError, BackendSession, ConflictDetail, ConflictReason,
@@ -28,6 +28,8 @@ impl Backend for MaterializeBackend {
         outputs: &[RenderedOutput],
     ) -> Result<ApplyReport, BackendError> {
         let mut report = ApplyReport::default();
+        let mut ledger = UnstampedLedger::load(&session.repo_root);
+        let mut ledger_changed = false;
 
         // Index planned outputs by path for policy lookup.
         let mut planned_by_path: BTreeMap<&str, &crate::outputs::PlannedOutput> = BTreeMap::new();
@@ -54,54 +56,32 @@ impl Backend for MaterializeBackend {
                 continue;
             }
 
-            if mode == crate::model::WriteMode::IfGenerated {
-                // If a file exists, it must be generated by agents and not drifted.
-                if dest.exists() {
-                    let existing = fsutil::read_to_string(&dest)?;
-                    let stamp = parse_stamp(&existing);
-                    let managed_by_agents =
-                        stamp.as_ref().is_some_and(|s| s.meta.generator == "agents");
-                    if !managed_by_agents {
-                        report.conflicts.push(out.path.clone());
-                        report.conflict_details.push(ConflictDetail {
-                            path: out.path.clone(),
-                            reason: ConflictReason::Unmanaged,
-                            message: "refusing to overwrite unmanaged file (writePolicy=if_generated)"
-                                .to_string(),
-                            hints: vec![
-                                format!("hint: run `agents diff --agent {}`", session.plan.agent_id),
-                                "hint: change output.writePolicy.mode to `always` to force overwrite"
-                                    .to_string(),
-                            ],
-                        });
-                        continue;
-                    }
-
-                    if out.drift_status == crate::stamps::DriftStatus::Drifted {
-                        report.conflicts.push(out.path.clone());
-                        report.conflict_details.push(ConflictDetail {
-                            path: out.path.clone(),
-                            reason: ConflictReason::Drifted,
-                            message: "refusing to overwrite drifted generated file (writePolicy=if_generated)"
-                                .to_string(),
-                            hints: vec![
-                                format!("hint: run `agents diff --agent {}`", session.plan.agent_id),
-                                "hint: reconcile manual edits or change output.writePolicy.mode"
-                                    .to_string(),
-                            ],
-                        });
-                        continue;
-                    }
-                }
+            if let Some(conflict) = conflict_for(&session.plan.agent_id, planned, out, &dest)? {
+                report.conflicts.push(out.path.clone());
+                report.conflict_details.push(conflict);
+                continue;
             }
 
             // `always` overwrites unconditionally; `if_generated` reaches here only when safe.
-            let bytes = normalize_bytes_for_write(&dest, &out.bytes, Some(planned.format));
-            fsutil::atomic_write(&dest, &bytes)?;
+            if planned.is_verbatim() {
+                fsutil::atomic_write(&dest, &out.bytes)?;
+            } else {
+                let bytes = normalize_bytes_for_write(&dest, &out.bytes, Some(planned.format));
+                fsutil::atomic_write(&dest, &bytes)?;
+            }
+            if planned.stamp_method().is_none() {
+                // Unstamped outputs carry no ownership marker; the ledger records it instead.
+                ledger.record(out.path.as_str(), out.stamp_meta.clone());
+                ledger_changed = true;
+            }
             // Intentionally do not set executable bits or other platform-specific permissions.
             report.written.push(out.path.clone());
         }
 
+        if ledger_changed {
+            ledger.save(&session.repo_root)?;
+        }
+
         // Optional: update .gitignore entries for outputs that request it.
         update_gitignore_for_written(session, &planned_by_path, &mut report)?;
 
@@ -109,6 +89,126 @@ impl Backend for MaterializeBackend {
     }
 }
 
+impl MaterializeBackend {
+    /// Report the conflicts `apply` would hit without writing anything.
+    pub fn preflight(
+        &self,
+        session: &BackendSession,
+        outputs: &[RenderedOutput],
+    ) -> Result<Vec<ConflictDetail>, BackendError> {
+        let mut conflicts = vec![];
+
+        for out in outputs {
+            let Some(planned) = session
+                .plan
+                .outputs
+                .iter()
+                .find(|p| p.path.as_str() == out.path.as_str())
+            else {
+                return Err(BackendError::Unsupported {
+                    message: format!("rendered output not present in plan: {}", out.path.as_str()),
+                });
+            };
+
+            let dest = session.repo_root.join(out.path.as_str());
+            if let Some(c) = conflict_for(&session.plan.agent_id, planned, out, &dest)? {
+                conflicts.push(c);
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    /// Paths `apply` would write over content agents did not generate, or generated content that
+    /// was edited since. Only `writePolicy: always` outputs can do that; `if_generated` reports
+    /// such files as conflicts instead.
+    pub fn overwrites(
+        &self,
+        session: &BackendSession,
+        outputs: &[RenderedOutput],
+    ) -> Result<Vec<RepoPath>, BackendError> {
+        let mut out = vec![];
+
+        for o in outputs {
+            let Some(planned) = session.plan.outputs.iter().find(|p| {
+                p.path.as_str() == o.path.as_str()
+                    && p.write_policy.mode == Some(crate::model::WriteMode::Always)
+            }) else {
+                continue;
+            };
+            let dest = session.repo_root.join(o.path.as_str());
+            if !dest.is_file() {
+                continue;
+            }
+
+            if !managed_by_agents(planned, o, &dest)? || o.drift_status == DriftStatus::Drifted {
+                out.push(o.path.clone());
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+fn conflict_for(
+    agent_id: &str,
+    planned: &crate::outputs::PlannedOutput,
+    out: &RenderedOutput,
+    dest: &Path,
+) -> Result<Option<ConflictDetail>, BackendError> {
+    let mode = planned
+        .write_policy
+        .mode
+        .unwrap_or(crate::model::WriteMode::IfGenerated);
+
+    // If a file exists, it must be generated by agents and not drifted.
+    if mode != crate::model::WriteMode::IfGenerated || !dest.exists() {
+        return Ok(None);
+    }
+
+    if !managed_by_agents(planned, out, dest)? {
+        return Ok(Some(ConflictDetail {
+            path: out.path.clone(),
+            reason: ConflictReason::Unmanaged,
+            message: "refusing to overwrite unmanaged file (writePolicy=if_generated)".to_string(),
+            hints: vec![
+                format!("hint: run `agents diff --agent {agent_id}`"),
+                "hint: change output.writePolicy.mode to `always` to force overwrite".to_string(),
+            ],
+        }));
+    }
+
+    if out.drift_status == DriftStatus::Drifted {
+        return Ok(Some(ConflictDetail {
+            path: out.path.clone(),
+            reason: ConflictReason::Drifted,
+            message: "refusing to overwrite drifted generated file (writePolicy=if_generated)"
+                .to_string(),
+            hints: vec![
+                format!("hint: run `agents diff --agent {agent_id}`"),
+                "hint: reconcile manual edits or change output.writePolicy.mode".to_string(),
+            ],
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Whether the existing file at `dest` was generated by agents: an agents stamp in the file, or
+/// for unstamped outputs, an entry in the ledger (already reflected in the drift status).
+fn managed_by_agents(
+    planned: &crate::outputs::PlannedOutput,
+    out: &RenderedOutput,
+    dest: &Path,
+) -> Result<bool, BackendError> {
+    if planned.stamp_method().is_none() {
+        return Ok(out.drift_status != DriftStatus::Unmanaged);
+    }
+
+    let existing = fsutil::read_to_string(dest)?;
+    Ok(parse_stamp(&existing).is_some_and(|s| s.meta.generator == "agents"))
+}
+
 fn update_gitignore_for_written(
     session: &BackendSession,
     planned_by_path: &BTreeMap<&str, &crate::outputs::PlannedOutput>,
@@ -1,7 +1,9 @@
 mod materialize;
+mod transaction;
 mod types;
 
 pub use materialize::MaterializeBackend;
+pub use transaction::{apply_transaction, planned_overwrites, ApplyBatch, TransactionReport};
 pub use types::{
     ApplyReport, Backend, BackendError, BackendSession, ConflictDetail, ConflictReason,
     RenderedOutput,
@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use crate::fsutil::{self, RepoPath};
+use crate::outputs::OutputPlan;
+
+use super::{ApplyReport, Backend, BackendError, MaterializeBackend, RenderedOutput};
+
+/// One adapter's plan and rendered outputs within a multi-adapter apply.
+#[derive(Debug, Clone)]
+pub struct ApplyBatch {
+    pub plan: OutputPlan,
+    pub outputs: Vec<RenderedOutput>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransactionReport {
+    /// Per-adapter reports, in batch order.
+    pub reports: Vec<(String, ApplyReport)>,
+
+    /// True when outputs were written; false when conflicts aborted the apply up front.
+    pub committed: bool,
+}
+
+impl TransactionReport {
+    pub fn has_conflicts(&self) -> bool {
+        self.reports.iter().any(|(_, r)| !r.conflicts.is_empty())
+    }
+}
+
+/// Paths across `batches` that `apply_transaction` would overwrite (see
+/// `MaterializeBackend::overwrites`), as `(agent_id, path)`.
+pub fn planned_overwrites(
+    repo_root: &Path,
+    batches: &[ApplyBatch],
+) -> Result<Vec<(String, RepoPath)>, BackendError> {
+    let backend = MaterializeBackend;
+
+    let mut out = vec![];
+    for b in batches {
+        let session = backend.prepare(repo_root, &b.plan)?;
+        for p in backend.overwrites(&session, &b.outputs)? {
+            out.push((b.plan.agent_id.clone(), p));
+        }
+    }
+    Ok(out)
+}
+
+/// Materialize several adapters as one unit.
+///
+/// Conflicts are checked for every batch before anything is written. If a write fails part way
+/// through, files touched so far are restored to their previous contents (or removed).
+pub fn apply_transaction(
+    repo_root: &Path,
+    batches: &[ApplyBatch],
+) -> Result<TransactionReport, BackendError> {
+    let backend = MaterializeBackend;
+
+    let mut sessions = vec![];
+    let mut report = TransactionReport::default();
+    for b in batches {
+        let session = backend.prepare(repo_root, &b.plan)?;
+        let conflicts = backend.preflight(&session, &b.outputs)?;
+
+        report.reports.push((
+            b.plan.agent_id.clone(),
+            ApplyReport {
+                conflicts: conflicts.iter().map(|c| c.path.clone()).collect(),
+                conflict_details: conflicts,
+                ..Default::default()
+            },
+        ));
+        sessions.push(session);
+    }
+
+    if report.has_conflicts() {
+        return Ok(report);
+    }
+
+    let snapshot = Snapshot::capture(repo_root, batches)?;
+
+    let mut applied = vec![];
+    for (b, session) in batches.iter().zip(sessions.iter_mut()) {
+        match backend.apply(session, &b.outputs) {
+            Ok(r) if r.conflicts.is_empty() => applied.push((b.plan.agent_id.clone(), r)),
+            Ok(r) => {
+                // The tree changed between preflight and apply.
+                snapshot.restore()?;
+                applied.push((b.plan.agent_id.clone(), r));
+                return Ok(TransactionReport {
+                    reports: applied,
+                    committed: false,
+                });
+            }
+            Err(e) => {
+                snapshot.restore()?;
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(TransactionReport {
+        reports: applied,
+        committed: true,
+    })
+}
+
+struct Snapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Snapshot {
+    fn capture(repo_root: &Path, batches: &[ApplyBatch]) -> Result<Self, BackendError> {
+        let mut paths: Vec<PathBuf> = batches
+            .iter()
+            .flat_map(|b| b.outputs.iter())
+            .map(|o| repo_root.join(o.path.as_str()))
+            .collect();
+        paths.push(repo_root.join(".gitignore"));
+        paths.push(crate::stamps::ledger_path(repo_root));
+        paths.sort();
+        paths.dedup();
+
+        let mut files = vec![];
+        for p in paths {
+            let prev = if p.is_file() {
+                Some(fsutil::read_bytes(&p)?)
+            } else {
+                None
+            };
+            files.push((p, prev));
+        }
+
+        Ok(Self { files })
+    }
+
+    fn restore(&self) -> Result<(), BackendError> {
+        for (p, prev) in &self.files {
+            match prev {
+                Some(bytes) => fsutil::atomic_write(p, bytes)?,
+                None => {
+                    if p.is_file() {
+                        std::fs::remove_file(p).map_err(|e| fsutil::FsError::Io {
+                            path: p.clone(),
+                            source: e,
+                        })?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
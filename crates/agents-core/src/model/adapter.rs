@@ -85,6 +85,11 @@ pub struct OutputCondition {
 
     #[serde(default, rename = "profileIn")]
     pub profile_in: Vec<String>,
+
+    /// Plan the output only when the effective MCP server set is non-empty (`true`) or empty
+    /// (`false`).
+    #[serde(default, rename = "hasMcpServers")]
+    pub has_mcp_servers: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,3 +41,21 @@ id_type!(PolicyId);
 id_type!(SkillId);
 id_type!(AdapterId);
 id_type!(ScopeId);
+
+/// Deserialize an id list that may also be written as a single id (e.g. `extends: safe`).
+pub fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(id) => vec![id],
+        OneOrMany::Many(ids) => ids,
+    })
+}
@@ -12,6 +12,10 @@ pub struct Manifest {
     pub defaults: Defaults,
     pub enabled: Enabled,
 
+    /// Mode, policy, skill and snippet overrides for one agent, applied above `defaults`.
+    #[serde(default, rename = "byAgent")]
+    pub by_agent: std::collections::BTreeMap<String, crate::model::ScopeOverrides>,
+
     #[serde(default)]
     pub resolution: Option<Resolution>,
 
@@ -75,8 +79,24 @@ pub struct Resolution {
     #[serde(default, rename = "denyOverridesAllow")]
     pub deny_overrides_allow: bool,
 
+    /// Merge the policies of every matched scope instead of letting the most specific one win.
+    #[serde(default, rename = "mergeScopePolicies")]
+    pub merge_scope_policies: bool,
+
     #[serde(default, rename = "onConflict")]
     pub on_conflict: Option<OnConflict>,
+
+    /// Which of the user overlay's manifest defaults may replace the repo's.
+    #[serde(default, rename = "userOverlayDefaults")]
+    pub user_overlay_defaults: Vec<UserOverlayDefault>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserOverlayDefault {
+    Mode,
+    Policy,
+    Backend,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -3,6 +3,7 @@ pub mod ids;
 pub mod manifest;
 pub mod mode;
 pub mod policy;
+pub mod profile;
 pub mod scope;
 pub mod skill;
 pub mod state;
@@ -12,6 +13,7 @@ pub use ids::*;
 pub use manifest::*;
 pub use mode::*;
 pub use policy::*;
+pub use profile::*;
 pub use scope::*;
 pub use skill::*;
 pub use state::*;
@@ -1,11 +1,22 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ModeFrontmatter {
     #[serde(default)]
     pub id: Option<String>,
 
+    /// Parent modes: their frontmatter lists are merged in and their bodies come first, or
+    /// replace a `{{> parent}}` line in this body (see `loadag::flatten_modes`).
+    #[serde(
+        default,
+        deserialize_with = "crate::model::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extends: Vec<String>,
+
     #[serde(default)]
     pub title: Option<String>,
 
@@ -25,7 +36,7 @@ pub struct ModeFrontmatter {
     pub tool_intent: Option<ToolIntent>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ToolIntent {
     #[serde(default)]
@@ -39,6 +50,9 @@ pub struct ToolIntent {
 pub struct ModeFile {
     pub frontmatter: Option<ModeFrontmatter>,
     pub body: String,
+
+    /// Mode files that make up this mode, parents first and its own file last.
+    pub sources: Vec<PathBuf>,
 }
 
 pub fn parse_frontmatter_markdown(
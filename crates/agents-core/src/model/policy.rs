@@ -4,6 +4,16 @@ use serde::{Deserialize, Serialize};
 #[serde(deny_unknown_fields)]
 pub struct Policy {
     pub id: String,
+
+    /// Parent policies, merged in order underneath this one when the repo is loaded (see
+    /// `loadag::flatten_policies`).
+    #[serde(
+        default,
+        deserialize_with = "crate::model::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extends: Vec<String>,
+
     pub description: String,
 
     pub capabilities: Capabilities,
@@ -121,7 +131,7 @@ pub enum ConfirmationType {
     Rebase,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Limits {
     #[serde(default, rename = "maxFilesChanged")]
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::manifest::BackendKind;
+
+/// `.agents/profiles/<id>.yaml`; the id is the file stem.
+///
+/// Every field is optional; set fields override the manifest defaults and matched scopes (see
+/// `Resolver::resolve` for the full precedence).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub mode: Option<String>,
+
+    #[serde(default)]
+    pub policy: Option<String>,
+
+    #[serde(default)]
+    pub backend: Option<BackendKind>,
+
+    /// Replaces `manifest.enabled.adapters` (e.g. for `sync --all`).
+    #[serde(default)]
+    pub adapters: Option<Vec<String>>,
+
+    #[serde(default, rename = "enableSkills")]
+    pub enable_skills: Vec<String>,
+
+    #[serde(default, rename = "disableSkills")]
+    pub disable_skills: Vec<String>,
+
+    #[serde(default, rename = "includeSnippets")]
+    pub include_snippets: Vec<String>,
+
+    /// Template variables, available to renderers as `vars.<name>`.
+    #[serde(default)]
+    pub vars: BTreeMap<String, serde_json::Value>,
+}
+
+impl Profile {
+    /// `"field: value"` for each field the profile sets, in a stable order.
+    pub fn overrides(&self) -> Vec<String> {
+        let mut out = vec![];
+        if let Some(m) = &self.mode {
+            out.push(format!("mode: {m}"));
+        }
+        if let Some(p) = &self.policy {
+            out.push(format!("policy: {p}"));
+        }
+        if let Some(b) = &self.backend {
+            out.push(format!("backend: {b:?}"));
+        }
+        if let Some(a) = &self.adapters {
+            out.push(format!("adapters: [{}]", a.join(", ")));
+        }
+        for (field, ids) in [
+            ("enableSkills", &self.enable_skills),
+            ("disableSkills", &self.disable_skills),
+            ("includeSnippets", &self.include_snippets),
+        ] {
+            if !ids.is_empty() {
+                out.push(format!("{field}: [{}]", ids.join(", ")));
+            }
+        }
+        if !self.vars.is_empty() {
+            let names: Vec<&str> = self.vars.keys().map(String::as_str).collect();
+            out.push(format!("vars: [{}]", names.join(", ")));
+        }
+        out
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,7 +13,64 @@ pub struct Scope {
     #[serde(default)]
     pub priority: i64,
 
+    /// Repo-relative directory for `{{scopeDir}}` outputs; defaults to the literal prefix of
+    /// `applyTo` (see `Scope::dir`).
+    #[serde(default)]
+    pub root: Option<String>,
+
     pub overrides: ScopeOverrides,
+
+    /// Extra overrides for one agent, applied after `overrides`.
+    #[serde(default, rename = "byAgent")]
+    pub by_agent: BTreeMap<String, ScopeOverrides>,
+}
+
+impl Scope {
+    /// The directory this scope covers: `root`, else the leading literal directories shared by
+    /// every `applyTo` pattern (`packages/foo` for `packages/foo/**` and for
+    /// `packages/foo/README.md`). `None` for the repo root.
+    pub fn dir(&self) -> Option<String> {
+        let components: Vec<&str> = match &self.root {
+            Some(root) => literal_components(root),
+            None => {
+                let mut patterns = self.apply_to.iter().map(|p| literal_dir_components(p));
+                let first = patterns.next().unwrap_or_default();
+                patterns.fold(first, |common, next| {
+                    common
+                        .into_iter()
+                        .zip(next)
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a)
+                        .collect()
+                })
+            }
+        };
+        (!components.is_empty()).then(|| components.join("/"))
+    }
+}
+
+/// Path components up to the first one containing a glob metacharacter.
+fn literal_components(pattern: &str) -> Vec<&str> {
+    pattern
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .take_while(|c| !c.contains(['*', '?', '[', '{']))
+        .collect()
+}
+
+/// `literal_components` without a trailing file name: a fully literal pattern whose last
+/// component has an extension names a file, so the scope covers its parent.
+fn literal_dir_components(pattern: &str) -> Vec<&str> {
+    let mut components = literal_components(pattern);
+    let fully_literal = !pattern.contains(['*', '?', '[', '{']) && !pattern.ends_with('/');
+    let names_file = components
+        .last()
+        .and_then(|c| c.rsplit_once('.'))
+        .is_some_and(|(stem, ext)| !stem.is_empty() && !ext.is_empty());
+    if fully_literal && names_file {
+        components.pop();
+    }
+    components
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
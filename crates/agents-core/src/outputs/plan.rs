@@ -1,22 +1,26 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::fsutil;
 use crate::loadag::RepoConfig;
 use crate::model::{
     Adapter, AdapterOutput, CollisionPolicy, DriftDetection, DriftMethod, OutputFormat,
-    RendererType, StampMethod, WriteMode, WritePolicy,
+    OutputRenderer, RendererType, StampMethod, WriteMode, WritePolicy,
 };
 use crate::outputs::{OutputPlan, PlannedOutput, SourceMapSkeleton};
 use crate::prompts::PromptComposer;
-use crate::resolv::EffectiveConfig;
-use crate::skillpl::SkillPlanner;
+use crate::resolv::{EffectiveConfig, Resolver};
+use crate::skillpl::{
+    plan_mcp_servers, plan_skill_assets, skill_summaries, EffectiveSkills, SkillAsset, SkillPlanner,
+};
 use crate::templ::{
     AdapterCtx, EffectiveCtx, EffectiveModeCtx, EffectiveSkillsCtx, GenerationCtx,
-    GenerationStampCtx, RenderContext, ScopeCtx,
+    GenerationStampCtx, McpCtx, RenderContext, ScopeCtx,
 };
 
 const SCOPE_ID_PLACEHOLDER: &str = "{{scopeId}}";
+const SCOPE_DIR_PLACEHOLDER: &str = "{{scopeDir}}";
 
 #[derive(Debug, thiserror::Error)]
 pub enum PlanError {
@@ -38,6 +42,9 @@ pub enum PlanError {
 
     #[error("invalid renderer config for {path}: {message}")]
     InvalidRenderer { path: String, message: String },
+
+    #[error("cannot resolve scope {scope}: {message}")]
+    ScopeResolution { scope: String, message: String },
 }
 
 #[derive(Debug)]
@@ -48,10 +55,11 @@ pub struct PlanResult {
 
 pub fn plan_outputs(
     repo_root: &Path,
-    repo: RepoConfig,
+    repo: impl Into<Arc<RepoConfig>>,
     effective: &EffectiveConfig,
     agent_id: &str,
 ) -> Result<PlanResult, PlanError> {
+    let repo: Arc<RepoConfig> = repo.into();
     let adapter: Adapter =
         repo.adapters
             .get(agent_id)
@@ -62,13 +70,54 @@ pub fn plan_outputs(
 
     let template_dir = repo.adapter_template_dirs.get(agent_id).cloned();
 
-    let policy = repo
-        .policies
-        .get(&effective.policy_id)
+    let (render_ctx, prompt_source_paths, skills) =
+        build_render_context(repo_root, &repo, effective, agent_id)?;
+
+    let assets = plan_assets(repo_root, &skills)?;
+
+    let (outputs, scoped) = evaluate_outputs(
+        repo_root,
+        &repo,
+        effective,
+        &adapter,
+        template_dir.clone(),
+        &render_ctx,
+        assets,
+    )?;
+
+    let sources = build_source_map_skeletons(
+        repo_root,
+        effective,
+        agent_id,
+        &prompt_source_paths,
+        &repo,
+        &outputs,
+        &scoped,
+    );
+
+    Ok(PlanResult {
+        plan: OutputPlan {
+            agent_id: agent_id.to_string(),
+            backend: effective.backend,
+            outputs,
+        },
+        sources,
+    })
+}
+
+/// The template context for `effective`, the repo paths of its prompt sources, and its skills.
+fn build_render_context(
+    repo_root: &Path,
+    repo: &Arc<RepoConfig>,
+    effective: &EffectiveConfig,
+    agent_id: &str,
+) -> Result<(RenderContext, Vec<String>, EffectiveSkills), PlanError> {
+    let policy = effective
+        .policy(repo)
         .cloned()
         .expect("effective policy exists (validated earlier)");
 
-    let composer = PromptComposer::new(repo_root, repo.clone());
+    let composer = PromptComposer::new(repo_root, Arc::clone(repo));
     let (prompts, prompt_sources) =
         composer
             .compose(effective, &policy)
@@ -77,7 +126,7 @@ pub fn plan_outputs(
                 message: e.to_string(),
             })?;
 
-    let planner = SkillPlanner::new(repo.clone());
+    let planner = SkillPlanner::new(Arc::clone(repo));
     let skills =
         planner
             .plan(effective, Some(agent_id))
@@ -86,6 +135,8 @@ pub fn plan_outputs(
                 message: e.to_string(),
             })?;
 
+    let mcp = plan_mcp_servers(repo_root, &skills, &policy);
+
     let render_ctx = RenderContext {
         effective: EffectiveCtx {
             mode: {
@@ -101,7 +152,10 @@ pub fn plan_outputs(
             policy,
             skills: EffectiveSkillsCtx {
                 ids: skills.enabled.iter().map(|s| s.id.clone()).collect(),
-                summaries: vec![],
+                summaries: skill_summaries(&skills),
+            },
+            mcp: McpCtx {
+                servers: mcp.servers,
             },
             prompts,
         },
@@ -124,85 +178,138 @@ pub fn plan_outputs(
         adapter: AdapterCtx {
             agent_id: agent_id.to_string(),
         },
+        vars: effective.vars.clone(),
         x: None,
     };
 
-    let outputs = evaluate_outputs(
-        repo_root,
-        &repo,
-        effective,
-        agent_id,
-        &adapter,
-        template_dir.clone(),
-        &render_ctx,
-    )?;
-
     let prompt_source_paths = prompt_sources_to_repo_paths(repo_root, &prompt_sources);
-    let sources = build_source_map_skeletons(effective, agent_id, &prompt_source_paths, &outputs);
+    Ok((render_ctx, prompt_source_paths, skills))
+}
 
-    Ok(PlanResult {
-        plan: OutputPlan {
-            agent_id: agent_id.to_string(),
-            backend: effective.backend,
-            outputs,
-        },
-        sources,
+fn plan_assets(repo_root: &Path, skills: &EffectiveSkills) -> Result<Vec<SkillAsset>, PlanError> {
+    plan_skill_assets(repo_root, skills).map_err(|e| PlanError::InvalidRenderer {
+        path: "<skills>".to_string(),
+        message: e.to_string(),
     })
 }
 
+/// The resolution behind a per-scope output, keyed by output path.
+struct ScopedResolution {
+    effective: EffectiveConfig,
+    prompt_source_paths: Vec<String>,
+}
+
+/// A scope's resolution and render context, shared by every output fanned out over it.
+struct ScopeRender {
+    effective: EffectiveConfig,
+    ctx: RenderContext,
+    prompt_source_paths: Vec<String>,
+    assets: Vec<SkillAsset>,
+}
+
 fn evaluate_outputs(
     repo_root: &Path,
-    repo: &RepoConfig,
+    repo: &Arc<RepoConfig>,
     effective: &EffectiveConfig,
-    agent_id: &str,
     adapter: &Adapter,
     template_dir: Option<std::path::PathBuf>,
     render_ctx: &RenderContext,
-) -> Result<Vec<PlannedOutput>, PlanError> {
+    mut assets: Vec<SkillAsset>,
+) -> Result<(Vec<PlannedOutput>, BTreeMap<String, ScopedResolution>), PlanError> {
+    let agent_id = render_ctx.adapter.agent_id.as_str();
     let mut planned: Vec<PlannedOutput> = vec![];
+    let mut scoped: BTreeMap<String, ScopedResolution> = BTreeMap::new();
+    let mut scope_renders: BTreeMap<String, ScopeRender> = BTreeMap::new();
 
     for out in &adapter.outputs {
-        if !condition_allows(out, effective) {
-            continue;
-        }
-
-        validate_renderer(out)?;
-
-        if out.path.contains(SCOPE_ID_PLACEHOLDER) {
-            let mut scope_ids: Vec<String> = repo.scopes.keys().cloned().collect();
-            scope_ids.sort();
+        let per_scope_dir = out.path.contains(SCOPE_DIR_PLACEHOLDER);
+        if per_scope_dir || out.path.contains(SCOPE_ID_PLACEHOLDER) {
+            // Scope ids are map keys, so this is sorted.
+            for scope in repo.scopes.values() {
+                let mut scoped_out = out.clone();
+                scoped_out.path = scoped_out
+                    .path
+                    .replace(SCOPE_ID_PLACEHOLDER, &sanitize_scope_id_for_path(&scope.id));
+
+                let dir = scope.dir();
+                if per_scope_dir {
+                    // Nested files need a directory.
+                    let Some(dir) = &dir else {
+                        continue;
+                    };
+                    scoped_out.path = scoped_out.path.replace(SCOPE_DIR_PLACEHOLDER, dir);
+                }
 
-            for scope_id in scope_ids {
-                let scope = repo
-                    .scopes
-                    .get(&scope_id)
-                    .expect("scope id collected from map");
+                // Each scoped output is rendered with the scope's own mode, policy, skills and
+                // snippets, as if the scope were the only one that matched.
+                if !scope_renders.contains_key(&scope.id) {
+                    let scope_effective = Resolver::new(Arc::clone(repo))
+                        .resolve_for_scope(effective, &scope.id)
+                        .map_err(|e| PlanError::ScopeResolution {
+                            scope: scope.id.clone(),
+                            message: e.to_string(),
+                        })?;
+                    let (mut ctx, prompt_source_paths, scope_skills) =
+                        build_render_context(repo_root, repo, &scope_effective, agent_id)?;
+                    ctx.scope = Some(ScopeCtx {
+                        id: scope.id.clone(),
+                        apply_to: scope.apply_to.clone(),
+                        dir,
+                    });
+                    scope_renders.insert(
+                        scope.id.clone(),
+                        ScopeRender {
+                            effective: scope_effective,
+                            ctx,
+                            prompt_source_paths,
+                            assets: plan_assets(repo_root, &scope_skills)?,
+                        },
+                    );
+                }
+                let render = &scope_renders[&scope.id];
 
-                let mut scoped_out = out.clone();
-                let safe = sanitize_scope_id_for_path(&scope.id);
-                scoped_out.path = scoped_out.path.replace(SCOPE_ID_PLACEHOLDER, &safe);
+                if !condition_allows(out, &render.effective, &render.ctx) {
+                    continue;
+                }
 
-                let mut scoped_ctx = render_ctx.clone();
-                scoped_ctx.scope = Some(ScopeCtx {
-                    id: scope.id.clone(),
-                    apply_to: scope.apply_to.clone(),
-                });
+                validate_renderer(out)?;
 
                 let planned_out = build_planned_output(
                     repo_root,
                     agent_id,
                     &scoped_out,
                     template_dir.clone(),
-                    scoped_ctx,
+                    render.ctx.clone(),
                 )?;
 
-                validate_renderer_sources(repo_root, repo, effective, &planned_out)?;
+                validate_renderer_sources(repo_root, repo, &render.effective, &planned_out)?;
+
+                // Skills only this scope enables still need their assets on disk.
+                for asset in &render.assets {
+                    if !assets.iter().any(|a| a.dest == asset.dest) {
+                        assets.push(asset.clone());
+                    }
+                }
+
+                scoped.insert(
+                    planned_out.path.as_str().to_string(),
+                    ScopedResolution {
+                        effective: render.effective.clone(),
+                        prompt_source_paths: render.prompt_source_paths.clone(),
+                    },
+                );
                 planned.push(planned_out);
             }
 
             continue;
         }
 
+        if !condition_allows(out, effective, render_ctx) {
+            continue;
+        }
+
+        validate_renderer(out)?;
+
         let planned_out = build_planned_output(
             repo_root,
             agent_id,
@@ -216,6 +323,8 @@ fn evaluate_outputs(
         planned.push(planned_out);
     }
 
+    planned.extend(assets.iter().map(|a| build_asset_output(a, render_ctx)));
+
     // Stable ordering by path then surface.
     planned.sort_by(|a, b| {
         a.path
@@ -227,7 +336,7 @@ fn evaluate_outputs(
     // Collision handling.
     let planned = resolve_collisions(repo, agent_id, planned)?;
 
-    Ok(planned)
+    Ok((planned, scoped))
 }
 
 fn sanitize_scope_id_for_path(id: &str) -> String {
@@ -249,8 +358,18 @@ fn sanitize_scope_id_for_path(id: &str) -> String {
     out
 }
 
-fn condition_allows(out: &AdapterOutput, effective: &EffectiveConfig) -> bool {
+fn condition_allows(
+    out: &AdapterOutput,
+    effective: &EffectiveConfig,
+    render_ctx: &RenderContext,
+) -> bool {
     if let Some(cond) = &out.condition {
+        if let Some(want) = cond.has_mcp_servers {
+            if want == render_ctx.effective.mcp.servers.is_empty() {
+                return false;
+            }
+        }
+
         if !cond.backend_in.is_empty() && !cond.backend_in.contains(&effective.backend) {
             return false;
         }
@@ -356,10 +475,7 @@ fn validate_renderer_sources(
             return Err(fail("renderer source must be non-empty".to_string()));
         }
 
-        let (kind, val) = match raw.split_once(':') {
-            Some((k, v)) => (Some(k), v),
-            None => (None, raw),
-        };
+        let (kind, val) = super::render::split_source(raw);
 
         match kind {
             Some("template") => {
@@ -409,6 +525,8 @@ fn validate_renderer_sources(
                 if !abs.exists() {
                     return Err(fail(format!("missing file source: {raw}")));
                 }
+                fsutil::repo_relpath(repo_root, Path::new(repo_rel.as_str()))
+                    .map_err(|e| fail(format!("invalid file source: {raw}: {e}")))?;
             }
             Some(other) => return Err(fail(format!("unknown renderer source kind: {other}"))),
         }
@@ -483,9 +601,54 @@ fn build_planned_output(
         template_dir,
         inline_template,
         render_context: render_ctx,
+        skill_asset: None,
     })
 }
 
+/// Skill assets are copied verbatim and stamped like any other output; JSON assets carry a
+/// `json_field` stamp, everything else a comment stamp.
+fn build_asset_output(asset: &SkillAsset, render_ctx: &RenderContext) -> PlannedOutput {
+    let ext = Path::new(&asset.rel_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    // Only formats that can hold a stamp without changing meaning get one; everything else
+    // (scripts, binaries, ...) is copied as-is and tracked in the unstamped ledger.
+    let (format, stamp) = match ext.as_str() {
+        "md" | "markdown" => (OutputFormat::Md, Some(StampMethod::Comment)),
+        "json" => (OutputFormat::Json, Some(StampMethod::JsonField)),
+        "jsonc" => (OutputFormat::Jsonc, None),
+        "yaml" | "yml" => (OutputFormat::Yaml, None),
+        _ => (OutputFormat::Text, None),
+    };
+
+    PlannedOutput {
+        path: asset.dest.clone(),
+        format,
+        surface: None,
+        collision: CollisionPolicy::Error,
+        renderer: OutputRenderer {
+            type_: RendererType::Copy,
+            template: None,
+            sources: vec![format!("skill:{}/{}", asset.skill_id, asset.rel_path)],
+            json_merge_strategy: None,
+        },
+        write_policy: WritePolicy {
+            mode: Some(WriteMode::IfGenerated),
+            gitignore: false,
+        },
+        drift_detection: DriftDetection {
+            method: Some(DriftMethod::Sha256),
+            stamp,
+        },
+        template_dir: None,
+        inline_template: None,
+        render_context: render_ctx.clone(),
+        skill_asset: Some(asset.clone()),
+    }
+}
+
 fn resolve_collisions(
     repo: &RepoConfig,
     agent_id: &str,
@@ -733,24 +896,72 @@ fn prompt_sources_to_repo_paths(
 }
 
 fn build_source_map_skeletons(
+    repo_root: &Path,
     effective: &EffectiveConfig,
     agent_id: &str,
     prompt_source_paths: &[String],
+    repo: &RepoConfig,
     planned: &[PlannedOutput],
+    scoped: &BTreeMap<String, ScopedResolution>,
 ) -> Vec<SourceMapSkeleton> {
     planned
         .iter()
-        .map(|p| SourceMapSkeleton {
-            adapter_id: agent_id.to_string(),
-            output_path: p.path.as_str().to_string(),
-            template: p.renderer.template.clone(),
-
-            prompt_source_paths: prompt_source_paths.to_vec(),
+        .map(|p| {
+            // Per-scope outputs were resolved separately.
+            let (effective, prompt_source_paths) = match scoped.get(p.path.as_str()) {
+                Some(r) => (&r.effective, r.prompt_source_paths.as_slice()),
+                None => (effective, prompt_source_paths),
+            };
+
+            let mut skel = SourceMapSkeleton {
+                adapter_id: agent_id.to_string(),
+                output_path: p.path.as_str().to_string(),
+                template: p.renderer.template.clone(),
+
+                prompt_source_paths: prompt_source_paths.to_vec(),
+
+                agent_id: effective.agent_id.clone(),
+                scope_id: p.render_context.scope.as_ref().map(|s| s.id.clone()),
+                mode_id: effective.mode_id.clone(),
+                mode_source_paths: mode_source_paths(repo_root, repo, &effective.mode_id),
+                policy_id: effective.policy_id.clone(),
+                policy_extends: repo.policy_chain(&effective.policy_id),
+                policy_merged_from: vec![],
+                policy_conflicts: vec![],
+                skill_ids: effective.skill_ids_enabled.clone(),
+                snippet_ids: effective.snippet_ids_included.clone(),
+            };
+            if let Some(m) = &effective.merged_policy {
+                skel.policy_merged_from = m.sources.clone();
+                skel.policy_conflicts = m.conflicts.clone();
+            }
 
-            mode_id: effective.mode_id.clone(),
-            policy_id: effective.policy_id.clone(),
-            skill_ids: effective.skill_ids_enabled.clone(),
-            snippet_ids: effective.snippet_ids_included.clone(),
+            // A skill asset comes from one file of one skill.
+            if let Some(asset) = &p.skill_asset {
+                skel.template = None;
+                skel.prompt_source_paths =
+                    vec![fsutil::display_repo_path(repo_root, &asset.source)
+                        .unwrap_or_else(|_| asset.source.display().to_string())];
+                skel.skill_ids = vec![asset.skill_id.clone()];
+                skel.snippet_ids = vec![];
+            }
+            skel
         })
         .collect()
 }
+
+/// Repo-relative mode files that make up `mode_id`, parents first.
+fn mode_source_paths(repo_root: &Path, repo: &RepoConfig, mode_id: &str) -> Vec<String> {
+    repo.modes
+        .get(mode_id)
+        .map(|m| {
+            m.sources
+                .iter()
+                .map(|p| {
+                    fsutil::display_repo_path(repo_root, p)
+                        .unwrap_or_else(|_| p.display().to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,9 +1,10 @@
 use std::path::Path;
 
 use crate::fsutil;
-use crate::model::{OutputFormat, RendererType, StampMethod};
+use crate::model::{JsonMergeStrategy, OutputFormat, RendererType};
 use crate::outputs::PlannedOutput;
 use crate::stamps::{apply_stamp, compute_sha256_hex, StampMeta};
+use crate::templ::helpers::normalize_json_value;
 use crate::templ::TemplateEngine;
 
 #[derive(Debug, thiserror::Error)]
@@ -20,8 +21,14 @@ pub enum RenderError {
     #[error("missing template_dir for template renderer")]
     MissingTemplateDir,
 
-    #[error("renderer not implemented: {0:?}")]
-    UnsupportedRenderer(RendererType),
+    #[error("invalid renderer source {source_ref}: {message}")]
+    InvalidSource { source_ref: String, message: String },
+
+    #[error("json_merge source {source_ref} is not valid JSON: {message}")]
+    InvalidJson { source_ref: String, message: String },
+
+    #[error("{path} is not UTF-8 text")]
+    NotText { path: String },
 }
 
 pub struct RenderedOutput {
@@ -31,33 +38,36 @@ pub struct RenderedOutput {
 }
 
 pub fn render_planned_output(
-    _repo_root: &Path,
+    repo_root: &Path,
     out: &PlannedOutput,
 ) -> Result<RenderedOutput, RenderError> {
-    // v1: template only (concat/copy/json_merge added later).
-    let content_without_stamp = match out.renderer.type_ {
-        RendererType::Template => {
-            let mut engine = TemplateEngine::new();
-
-            if let Some(inline) = out.inline_template.as_deref() {
-                engine.render_inline(inline, &out.render_context)?
-            } else {
-                let dir = out
-                    .template_dir
-                    .as_ref()
-                    .ok_or(RenderError::MissingTemplateDir)?;
-                engine.register_partials_from_dir(dir)?;
+    let content_without_stamp = render_planned_content(repo_root, out)?;
+    stamp_planned_output(out, content_without_stamp)
+}
 
-                let template_name = out.renderer.template.as_deref().unwrap_or("");
-                engine.render(template_name, &out.render_context)?
-            }
+/// Apply the output's stamp to already rendered content. Unstamped outputs come back unchanged.
+pub fn stamp_planned_output(
+    out: &PlannedOutput,
+    content_without_stamp: String,
+) -> Result<RenderedOutput, RenderError> {
+    let content_with_stamp = match out.stamp_method() {
+        Some(method) => {
+            let meta = planned_stamp_meta(out, compute_sha256_hex(&content_without_stamp));
+            apply_stamp(&content_without_stamp, &meta, method)?
         }
-        other => return Err(RenderError::UnsupportedRenderer(other)),
+        None => content_without_stamp.clone(),
     };
 
-    let stamp_method = out.drift_detection.stamp.unwrap_or(StampMethod::Comment);
+    Ok(RenderedOutput {
+        content_without_stamp,
+        content_with_stamp,
+        output_format: out.format,
+    })
+}
 
-    let meta = StampMeta {
+/// Stamp metadata for `out` with the given content hash.
+pub fn planned_stamp_meta(out: &PlannedOutput, content_sha256: String) -> StampMeta {
+    StampMeta {
         generator: "agents".to_string(),
         adapter_agent_id: out.render_context.adapter.agent_id.clone(),
         // v1: use manifest spec version once it is threaded through plan.
@@ -66,14 +76,337 @@ pub fn render_planned_output(
         policy: out.render_context.effective.policy.id.clone(),
         backend: crate::model::manifest::BackendKind::VfsContainer,
         profile: out.render_context.profile.clone(),
-        content_sha256: compute_sha256_hex(&content_without_stamp),
-    };
+        scope: out.render_context.scope.as_ref().map(|s| s.id.clone()),
+        content_sha256,
+    }
+}
 
-    let content_with_stamp = apply_stamp(&content_without_stamp, &meta, stamp_method)?;
+/// Render the planned output content (without any stamp).
+pub fn render_planned_content(
+    repo_root: &Path,
+    out: &PlannedOutput,
+) -> Result<String, RenderError> {
+    let engine = TemplateEngine::for_template_dir(out.template_dir.as_deref())?;
+    render_planned_content_with(&engine, repo_root, out)
+}
 
-    Ok(RenderedOutput {
-        content_without_stamp,
-        content_with_stamp,
-        output_format: out.format,
+/// Render the planned output to the exact bytes to write (without any stamp). Skill assets and
+/// `copy` outputs are read byte-for-byte, so this also covers binary files.
+pub fn render_planned_bytes(repo_root: &Path, out: &PlannedOutput) -> Result<Vec<u8>, RenderError> {
+    if let Some(asset) = &out.skill_asset {
+        return Ok(fsutil::read_bytes(&asset.source)?);
+    }
+    let engine = TemplateEngine::for_template_dir(out.template_dir.as_deref())?;
+    render_planned_bytes_with(&engine, repo_root, out)
+}
+
+/// Like `render_planned_bytes`, with an engine already holding `out.template_dir`'s templates.
+pub fn render_planned_bytes_with(
+    engine: &TemplateEngine,
+    repo_root: &Path,
+    out: &PlannedOutput,
+) -> Result<Vec<u8>, RenderError> {
+    if let Some(asset) = &out.skill_asset {
+        return Ok(fsutil::read_bytes(&asset.source)?);
+    }
+    if out.renderer.type_ == RendererType::Copy {
+        return copy_source(repo_root, out, engine);
+    }
+    Ok(render_planned_content_with(engine, repo_root, out)?.into_bytes())
+}
+
+/// Like `render_planned_content`, with an engine already holding `out.template_dir`'s templates.
+pub fn render_planned_content_with(
+    engine: &TemplateEngine,
+    repo_root: &Path,
+    out: &PlannedOutput,
+) -> Result<String, RenderError> {
+    if let Some(asset) = &out.skill_asset {
+        return String::from_utf8(fsutil::read_bytes(&asset.source)?).map_err(|_| {
+            RenderError::NotText {
+                path: out.path.as_str().to_string(),
+            }
+        });
+    }
+
+    match out.renderer.type_ {
+        RendererType::Template => {
+            if let Some(inline) = out.inline_template.as_deref() {
+                Ok(engine.render_inline(inline, &out.render_context)?)
+            } else {
+                if out.template_dir.is_none() {
+                    return Err(RenderError::MissingTemplateDir);
+                }
+
+                let template_name = out.renderer.template.as_deref().unwrap_or("");
+                Ok(engine.render(template_name, &out.render_context)?)
+            }
+        }
+        RendererType::Concat => {
+            let mut parts: Vec<String> = vec![];
+            for raw in &out.renderer.sources {
+                let s = resolve_source(repo_root, out, engine, raw)?;
+                let s = s.trim_end_matches('\n');
+                if !s.is_empty() {
+                    parts.push(s.to_string());
+                }
+            }
+
+            // Fragments are separated by a single blank line.
+            Ok(fsutil::ensure_trailing_newline(&parts.join("\n\n")))
+        }
+        RendererType::Copy => {
+            String::from_utf8(copy_source(repo_root, out, engine)?).map_err(|_| {
+                RenderError::NotText {
+                    path: out.path.as_str().to_string(),
+                }
+            })
+        }
+        RendererType::JsonMerge => {
+            let strategy = out
+                .renderer
+                .json_merge_strategy
+                .unwrap_or(JsonMergeStrategy::Deep);
+
+            let mut merged = serde_json::Value::Object(serde_json::Map::new());
+            for raw in &out.renderer.sources {
+                let s = resolve_source(repo_root, out, engine, raw)?;
+                let v: serde_json::Value = serde_json::from_str(&strip_jsonc(&s)).map_err(|e| {
+                    RenderError::InvalidJson {
+                        source_ref: raw.clone(),
+                        message: e.to_string(),
+                    }
+                })?;
+
+                if !v.is_object() {
+                    return Err(RenderError::InvalidJson {
+                        source_ref: raw.clone(),
+                        message: "json_merge sources must be JSON objects".to_string(),
+                    });
+                }
+
+                match strategy {
+                    JsonMergeStrategy::Deep => deep_merge(&mut merged, v),
+                    JsonMergeStrategy::Shallow => shallow_merge(&mut merged, v),
+                }
+            }
+
+            let normalized = normalize_json_value(&merged);
+            let s = serde_json::to_string_pretty(&normalized).map_err(|e| {
+                RenderError::InvalidJson {
+                    source_ref: out.path.as_str().to_string(),
+                    message: e.to_string(),
+                }
+            })?;
+            Ok(fsutil::ensure_trailing_newline(&s))
+        }
+    }
+}
+
+/// The `copy` renderer's single source, unchanged.
+fn copy_source(
+    repo_root: &Path,
+    out: &PlannedOutput,
+    engine: &TemplateEngine,
+) -> Result<Vec<u8>, RenderError> {
+    match out.renderer.sources.as_slice() {
+        [one] => resolve_source_bytes(repo_root, out, engine, one),
+        other => Err(RenderError::InvalidSource {
+            source_ref: other.join(", "),
+            message: "copy renderer requires exactly one source".to_string(),
+        }),
+    }
+}
+
+/// Split a renderer source into its kind and value. Only a scheme-like prefix (lowercase letters
+/// and `_`) counts as a kind, so plain repo paths may contain `:`.
+pub(crate) fn split_source(raw: &str) -> (Option<&str>, &str) {
+    let raw = raw.trim();
+    match raw.split_once(':') {
+        Some((k, v)) if !k.is_empty() && k.chars().all(|c| c.is_ascii_lowercase() || c == '_') => {
+            (Some(k), v.trim())
+        }
+        _ => (None, raw),
+    }
+}
+
+/// Repo-relative path of a `repo:`/`file:` (or unprefixed) renderer source; `None` for sources
+/// that do not read the working tree (`template:`, `prompt:`, `snippet:`).
+pub fn repo_file_source(raw: &str) -> Option<&str> {
+    match split_source(raw) {
+        (Some("repo" | "file") | None, v) => Some(v),
+        (Some(_), _) => None,
+    }
+}
+
+fn resolve_source(
+    repo_root: &Path,
+    out: &PlannedOutput,
+    engine: &TemplateEngine,
+    raw: &str,
+) -> Result<String, RenderError> {
+    String::from_utf8(resolve_source_bytes(repo_root, out, engine, raw)?).map_err(|_| {
+        RenderError::InvalidSource {
+            source_ref: raw.trim().to_string(),
+            message: "not UTF-8 text".to_string(),
+        }
     })
 }
+
+fn resolve_source_bytes(
+    repo_root: &Path,
+    out: &PlannedOutput,
+    engine: &TemplateEngine,
+    raw: &str,
+) -> Result<Vec<u8>, RenderError> {
+    let raw = raw.trim();
+    let fail = |message: String| RenderError::InvalidSource {
+        source_ref: raw.to_string(),
+        message,
+    };
+
+    let (kind, val) = split_source(raw);
+
+    let prompts = &out.render_context.effective.prompts;
+
+    match kind {
+        Some("template") => {
+            if out.template_dir.is_none() {
+                return Err(RenderError::MissingTemplateDir);
+            }
+            Ok(engine.render(val, &out.render_context)?.into_bytes())
+        }
+        Some("prompt") => match val {
+            "base" => Ok(prompts.base_md.clone().into_bytes()),
+            "project" => Ok(prompts.project_md.clone().into_bytes()),
+            "composed" => Ok(prompts.composed_md.clone().into_bytes()),
+            _ => Err(fail("unknown prompt source".to_string())),
+        },
+        Some("snippet") => prompts
+            .snippets
+            .iter()
+            .find(|s| s.id == val)
+            .map(|s| s.md.clone().into_bytes())
+            .ok_or_else(|| fail("snippet not included in effective config".to_string())),
+        Some("repo") | Some("file") | None => {
+            // Lexical check first, then again on the resolved path so symlinks cannot point
+            // outside the repo.
+            let rel = fsutil::repo_relpath_noexist(repo_root, Path::new(val))?;
+            let rel = fsutil::repo_relpath(repo_root, Path::new(rel.as_str()))?;
+            Ok(fsutil::read_bytes(&repo_root.join(rel.as_str()))?)
+        }
+        Some(other) => Err(fail(format!("unknown renderer source kind: {other}"))),
+    }
+}
+
+/// Objects merge recursively; any other value (including arrays) is replaced by the later source.
+fn deep_merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(b), serde_json::Value::Object(o)) => {
+            for (k, v) in o {
+                match b.get_mut(&k) {
+                    Some(existing) if existing.is_object() && v.is_object() => {
+                        deep_merge(existing, v)
+                    }
+                    _ => {
+                        b.insert(k, v);
+                    }
+                }
+            }
+        }
+        (b, o) => *b = o,
+    }
+}
+
+/// Top-level keys of later sources replace earlier ones wholesale.
+fn shallow_merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(b), serde_json::Value::Object(o)) => {
+            for (k, v) in o {
+                b.insert(k, v);
+            }
+        }
+        (b, o) => *b = o,
+    }
+}
+
+/// Strip JSONC comments and trailing commas so the result parses as plain JSON.
+pub fn strip_jsonc(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+
+    let mut i = 0;
+    let mut in_str = false;
+    let mut esc = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_str {
+            out.push(c);
+            if esc {
+                esc = false;
+            } else if c == '\\' {
+                esc = true;
+            } else if c == '"' {
+                in_str = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_str = true;
+                out.push(c);
+                i += 1;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            ',' => {
+                // Drop trailing commas before a closing bracket.
+                let mut j = i + 1;
+                loop {
+                    while j < chars.len() && chars[j].is_whitespace() {
+                        j += 1;
+                    }
+                    if chars.get(j) == Some(&'/') && chars.get(j + 1) == Some(&'/') {
+                        while j < chars.len() && chars[j] != '\n' {
+                            j += 1;
+                        }
+                    } else if chars.get(j) == Some(&'/') && chars.get(j + 1) == Some(&'*') {
+                        j += 2;
+                        while j < chars.len()
+                            && !(chars[j] == '*' && chars.get(j + 1) == Some(&'/'))
+                        {
+                            j += 1;
+                        }
+                        j += 2;
+                    } else {
+                        break;
+                    }
+                }
+                if !matches!(chars.get(j), Some('}') | Some(']')) {
+                    out.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
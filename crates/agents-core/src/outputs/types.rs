@@ -1,7 +1,11 @@
 use std::path::PathBuf;
 
 use crate::fsutil::RepoPath;
-use crate::model::{CollisionPolicy, DriftDetection, OutputFormat, OutputRenderer, WritePolicy};
+use crate::model::{
+    CollisionPolicy, DriftDetection, OutputFormat, OutputRenderer, RendererType, StampMethod,
+    WritePolicy,
+};
+use crate::skillpl::SkillAsset;
 use crate::templ::RenderContext;
 
 #[derive(Debug, Clone)]
@@ -21,6 +25,26 @@ pub struct PlannedOutput {
     pub inline_template: Option<String>,
 
     pub render_context: RenderContext,
+
+    /// Set for skill assets: the output is a copy of this file instead of renderer output.
+    pub skill_asset: Option<SkillAsset>,
+}
+
+impl PlannedOutput {
+    /// How the output carries its stamp. `None` for skill assets whose format cannot hold one;
+    /// those are copied byte-for-byte and tracked in the `UnstampedLedger` instead.
+    pub fn stamp_method(&self) -> Option<StampMethod> {
+        match (&self.skill_asset, self.drift_detection.stamp) {
+            (Some(_), None) => None,
+            (_, stamp) => Some(stamp.unwrap_or(StampMethod::Comment)),
+        }
+    }
+
+    /// Whether the output is a verbatim copy (skill asset or `copy` renderer), written exactly as
+    /// rendered instead of newline-normalized.
+    pub fn is_verbatim(&self) -> bool {
+        self.skill_asset.is_some() || self.renderer.type_ == RendererType::Copy
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -39,8 +63,26 @@ pub struct SourceMapSkeleton {
     /// Repo-relative prompt/snippet file paths that contributed to the effective prompt.
     pub prompt_source_paths: Vec<String>,
 
+    /// Agent the effective config was resolved for.
+    pub agent_id: Option<String>,
+
+    /// For a per-scope output, the scope it was resolved for.
+    pub scope_id: Option<String>,
+
     pub mode_id: String,
+
+    /// Repo-relative mode files that make up `mode_id`, parents first.
+    pub mode_source_paths: Vec<String>,
+
     pub policy_id: String,
+
+    /// Policies `policy_id` inherits from, nearest first.
+    pub policy_extends: Vec<String>,
+
+    /// For a merged policy, the scope policies it was merged from and the allow/deny conflicts.
+    pub policy_merged_from: Vec<String>,
+    pub policy_conflicts: Vec<String>,
+
     pub skill_ids: Vec<String>,
     pub snippet_ids: Vec<String>,
 }
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::fsutil;
+use crate::outputs::{repo_file_source, PlannedOutput};
+use crate::shared;
+
+/// Bumped whenever rendering changes in a way the key does not capture.
+const CACHE_VERSION: &str = "3";
+
+/// Entries not used for this long are evicted.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Per-adapter size cap; least recently used entries are evicted past it.
+const MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// How a `Pipeline` uses the render cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderCacheMode {
+    #[default]
+    Off,
+
+    /// Reuse entries but never write, for commands that only inspect (`preview`, `diff`).
+    ReadOnly,
+
+    ReadWrite,
+}
+
+/// Rendered output content (without stamp) keyed by a hash of everything that feeds the
+/// renderer: render context, renderer config, adapter templates and repo file sources.
+///
+/// Lives under `.agents/state/cache/render/<agent>/`, one file per key. Entries from other runs
+/// (another mode, profile or scope) are kept; `evict` only drops entries by age and total size.
+/// Cache failures are never errors; a miss just renders again.
+#[derive(Debug, Clone)]
+pub struct RenderCache {
+    dir: PathBuf,
+    writable: bool,
+}
+
+impl RenderCache {
+    pub fn for_agent(repo_root: &Path, agent_id: &str, mode: RenderCacheMode) -> Option<Self> {
+        if mode == RenderCacheMode::Off {
+            return None;
+        }
+        Some(Self {
+            dir: fsutil::agents_state_dir(repo_root)
+                .join("cache/render")
+                .join(agent_id),
+            writable: mode == RenderCacheMode::ReadWrite,
+        })
+    }
+
+    /// Cached content for `key`. A writable cache also marks the entry as recently used.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.dir.join(key);
+        let content = std::fs::read(&path).ok()?;
+        if self.writable {
+            let _ = std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|f| f.set_modified(SystemTime::now()));
+        }
+        Some(content)
+    }
+
+    pub fn put(&self, key: &str, content: &[u8]) {
+        if self.writable {
+            let _ = fsutil::atomic_write(&self.dir.join(key), content);
+        }
+    }
+
+    /// Drop entries unused for `MAX_AGE`, then the least recently used ones until the cache
+    /// fits in `MAX_BYTES`.
+    pub fn evict(&self) {
+        if !self.writable {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+
+        let now = SystemTime::now();
+        let mut kept: Vec<(SystemTime, u64, PathBuf)> = vec![];
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let used = meta.modified().unwrap_or(now);
+            if now.duration_since(used).unwrap_or_default() > MAX_AGE {
+                let _ = std::fs::remove_file(entry.path());
+            } else {
+                kept.push((used, meta.len(), entry.path()));
+            }
+        }
+
+        // Newest first; everything past the size budget goes.
+        kept.sort_by_key(|k| std::cmp::Reverse(k.0));
+        let mut total = 0u64;
+        for (_, len, path) in kept {
+            total += len;
+            if total > MAX_BYTES {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Hash of every file under an adapter's template dir, computed once per render.
+pub fn template_dir_fingerprint(dir: Option<&Path>) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(dir) = dir {
+        let mut files: Vec<PathBuf> = WalkDir::new(dir)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect();
+        files.sort();
+        for path in files {
+            let rel = path.strip_prefix(dir).unwrap_or(&path);
+            hasher.update(rel.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update(std::fs::read(&path).unwrap_or_default());
+            hasher.update([0]);
+        }
+    }
+    hex(&hasher.finalize())
+}
+
+/// Cache key for one planned output, or `None` if an input cannot be read (the output is then
+/// rendered uncached and the renderer reports the error).
+pub fn render_cache_key(
+    repo_root: &Path,
+    out: &PlannedOutput,
+    templates_fingerprint: &str,
+) -> Option<String> {
+    let mut hasher = Sha256::new();
+    let mut field = |bytes: &[u8]| {
+        hasher.update(bytes);
+        hasher.update([0]);
+    };
+
+    field(CACHE_VERSION.as_bytes());
+    field(env!("CARGO_PKG_VERSION").as_bytes());
+    field(out.path.as_str().as_bytes());
+    field(&serde_json::to_vec(&out.format).ok()?);
+    field(&serde_json::to_vec(&out.renderer).ok()?);
+    field(out.inline_template.as_deref().unwrap_or("").as_bytes());
+    field(templates_fingerprint.as_bytes());
+    // Built-in partials ship with the binary but can change between builds of one version.
+    for (name, src) in shared::builtin_partials() {
+        field(name.as_bytes());
+        field(src.as_bytes());
+    }
+    field(&serde_json::to_vec(&out.render_context).ok()?);
+
+    if let Some(asset) = &out.skill_asset {
+        field(&std::fs::read(&asset.source).ok()?);
+    }
+
+    // Plain repo files are read at render time, so their content is part of the key.
+    for path in out
+        .renderer
+        .sources
+        .iter()
+        .filter_map(|s| repo_file_source(s))
+    {
+        let rel = fsutil::repo_relpath_noexist(repo_root, Path::new(path)).ok()?;
+        field(&std::fs::read(repo_root.join(rel.as_str())).ok()?);
+    }
+
+    Some(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,7 @@
+pub mod cache;
+pub mod run;
+pub mod types;
+
+pub use cache::{RenderCache, RenderCacheMode};
+pub use run::*;
+pub use types::*;
@@ -0,0 +1,457 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::driftx::{diff_planned_output, diff_unstamped_output, stale_entries, DiffReport};
+use crate::loadag::{apply_user_overlay, user_overlay_dir, RepoConfig};
+use crate::matwiz::{ApplyBatch, RenderedOutput};
+use crate::model::CollisionPolicy;
+use crate::outputs::{
+    plan_outputs, planned_stamp_meta, render_planned_bytes, render_planned_bytes_with,
+    stamp_planned_output, PlanResult, PlannedOutput, RenderError,
+};
+use crate::resolv::{EffectiveConfig, ResolutionRequest, ResolveError, Resolver};
+use crate::stamps::{
+    classify, classify_unstamped, compute_bytes_sha256_hex, parse_stamp, UnstampedLedger,
+};
+use crate::templ::TemplateEngine;
+use crate::vfsmnt::OverlayFile;
+
+use super::cache::{render_cache_key, template_dir_fingerprint, RenderCache, RenderCacheMode};
+use super::{PipelineError, PipelineOutput, PipelineResult};
+
+/// Shared "resolve → plan → render → classify" pipeline for one adapter.
+///
+/// Every command that produces planned bytes goes through this so preview, diff, sync and run
+/// all agree on the content of each output.
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    repo_root: PathBuf,
+    repo: Arc<RepoConfig>,
+    agent_id: String,
+    request: ResolutionRequest,
+
+    /// Reuse rendered content from `.agents/state/cache/render/` for unchanged outputs.
+    render_cache: RenderCacheMode,
+}
+
+impl Pipeline {
+    /// `repo` may be an `Arc` shared by several pipelines (e.g. one per adapter).
+    pub fn for_agent(repo_root: &Path, repo: impl Into<Arc<RepoConfig>>, agent_id: &str) -> Self {
+        Self {
+            repo_root: repo_root.to_path_buf(),
+            repo: repo.into(),
+            agent_id: agent_id.to_string(),
+            request: ResolutionRequest {
+                repo_root: repo_root.to_path_buf(),
+                ..Default::default()
+            },
+            render_cache: RenderCacheMode::Off,
+        }
+    }
+
+    /// Replace the resolution request (mode/profile/backend overrides etc.).
+    pub fn with_request(mut self, request: ResolutionRequest) -> Self {
+        self.request = request;
+        self
+    }
+
+    /// Use the on-disk render cache (off by default so library callers never write).
+    pub fn with_render_cache(mut self, mode: RenderCacheMode) -> Self {
+        self.render_cache = mode;
+        self
+    }
+
+    pub fn agent_id(&self) -> &str {
+        &self.agent_id
+    }
+
+    pub fn repo(&self) -> &RepoConfig {
+        &self.repo
+    }
+
+    pub fn resolve(&self) -> Result<EffectiveConfig, PipelineError> {
+        let resolver = Resolver::new(self.request_repo()?);
+        Ok(resolver.resolve_for_agent(&self.request, &self.agent_id)?)
+    }
+
+    pub fn plan(&self) -> Result<(EffectiveConfig, PlanResult), PipelineError> {
+        let repo = self.request_repo()?;
+        let effective =
+            Resolver::new(Arc::clone(&repo)).resolve_for_agent(&self.request, &self.agent_id)?;
+        let plan = plan_outputs(&self.repo_root, repo, &effective, &self.agent_id)?;
+        Ok((effective, plan))
+    }
+
+    /// The repo with the user overlay merged in when the request enables it, so planning sees
+    /// the overlay modes and policies that resolution may pick.
+    fn request_repo(&self) -> Result<Arc<RepoConfig>, PipelineError> {
+        if !self.request.enable_user_overlay || self.repo.user_overlay.is_some() {
+            return Ok(Arc::clone(&self.repo));
+        }
+        let Some(dir) = user_overlay_dir(self.request.user_overlay_root.as_deref()) else {
+            return Ok(Arc::clone(&self.repo));
+        };
+        let mut repo = (*self.repo).clone();
+        apply_user_overlay(&mut repo, &dir).map_err(ResolveError::UserOverlay)?;
+        Ok(Arc::new(repo))
+    }
+
+    pub fn render(&self) -> Result<PipelineResult, PipelineError> {
+        let (effective, plan) = self.plan()?;
+        let outputs = &plan.plan.outputs;
+
+        let cache = RenderCache::for_agent(&self.repo_root, &self.agent_id, self.render_cache);
+
+        // Look every output up before compiling anything, so a fully cached adapter never builds
+        // a template engine.
+        let mut fingerprints: BTreeMap<Option<PathBuf>, String> = BTreeMap::new();
+        let lookups: Vec<(Option<String>, Option<Vec<u8>>)> = outputs
+            .iter()
+            .map(|out| {
+                let Some(cache) = &cache else {
+                    return (None, None);
+                };
+                let fingerprint = fingerprints
+                    .entry(out.template_dir.clone())
+                    .or_insert_with(|| template_dir_fingerprint(out.template_dir.as_deref()));
+                let key = render_cache_key(&self.repo_root, out, fingerprint);
+                let cached = key.as_deref().and_then(|k| cache.get(k));
+                (key, cached)
+            })
+            .collect();
+
+        // Compile each adapter template dir that has a miss once; outputs only differ in their
+        // context. Skill assets are copied and need no engine.
+        let mut engines: BTreeMap<Option<PathBuf>, TemplateEngine> = BTreeMap::new();
+        for (out, (_, cached)) in outputs.iter().zip(&lookups) {
+            if cached.is_some()
+                || out.skill_asset.is_some()
+                || engines.contains_key(&out.template_dir)
+            {
+                continue;
+            }
+            let engine =
+                TemplateEngine::for_template_dir(out.template_dir.as_deref()).map_err(|e| {
+                    PipelineError::Render {
+                        path: out.path.as_str().to_string(),
+                        source: e.into(),
+                    }
+                })?;
+            engines.insert(out.template_dir.clone(), engine);
+        }
+
+        let ledger = UnstampedLedger::load(&self.repo_root);
+
+        let work: Vec<_> = outputs.iter().zip(lookups).collect();
+        let rendered = par_map(&work, |(out, (key, cached))| {
+            self.render_one(
+                engines.get(&out.template_dir),
+                cache.as_ref(),
+                key.as_deref(),
+                cached.clone(),
+                &ledger,
+                out,
+            )
+        })?;
+
+        let mut outputs: Vec<PipelineOutput> = Vec::with_capacity(rendered.len());
+        let mut cache_hits = 0;
+        for r in rendered {
+            let (output, hit) = r?;
+            cache_hits += usize::from(hit);
+            outputs.push(output);
+        }
+
+        if let Some(cache) = &cache {
+            cache.evict();
+        }
+
+        Ok(PipelineResult {
+            repo_root: self.repo_root.clone(),
+            effective,
+            plan,
+            outputs,
+            cache_hits,
+        })
+    }
+
+    /// Render (unless `cached` already holds the content), stamp and classify one output.
+    /// Returns whether the content came from the cache.
+    fn render_one(
+        &self,
+        engine: Option<&TemplateEngine>,
+        cache: Option<&RenderCache>,
+        key: Option<&str>,
+        cached: Option<Vec<u8>>,
+        ledger: &UnstampedLedger,
+        out: &PlannedOutput,
+    ) -> Result<(PipelineOutput, bool), PipelineError> {
+        let path = out.path.as_str().to_string();
+        let render_err = |source| PipelineError::Render {
+            path: path.clone(),
+            source,
+        };
+        let classify_err = |source| PipelineError::Classify {
+            path: path.clone(),
+            source,
+        };
+
+        let hit = cached.is_some();
+        let content = match (cached, engine) {
+            (Some(content), _) => content,
+            (None, Some(engine)) => {
+                let content =
+                    render_planned_bytes_with(engine, &self.repo_root, out).map_err(render_err)?;
+                if let (Some(c), Some(k)) = (cache, key) {
+                    c.put(k, &content);
+                }
+                content
+            }
+            (None, None) => render_planned_bytes(&self.repo_root, out).map_err(render_err)?,
+        };
+        let target = self.repo_root.join(out.path.as_str());
+
+        let output = if out.stamp_method().is_none() {
+            // Copied as-is; ownership lives in the unstamped ledger instead of the file.
+            let stamp_meta = planned_stamp_meta(out, compute_bytes_sha256_hex(&content));
+            let drift_status =
+                classify_unstamped(&target, &content, ledger.get(&path), &out.drift_detection)
+                    .map_err(classify_err)?;
+
+            PipelineOutput {
+                planned: out.clone(),
+                content_without_stamp: content.clone(),
+                content_with_stamp: content,
+                stamp_meta,
+                drift_status,
+            }
+        } else {
+            let content = String::from_utf8(content)
+                .map_err(|_| render_err(RenderError::NotText { path: path.clone() }))?;
+            let rendered = stamp_planned_output(out, content).map_err(render_err)?;
+
+            let stamp = parse_stamp(&rendered.content_with_stamp)
+                .ok_or_else(|| PipelineError::MissingStamp { path: path.clone() })?;
+
+            let drift_status = classify(
+                &target,
+                &rendered.content_without_stamp,
+                &out.drift_detection,
+            )
+            .map_err(classify_err)?;
+
+            PipelineOutput {
+                planned: out.clone(),
+                content_without_stamp: rendered.content_without_stamp.into_bytes(),
+                content_with_stamp: rendered.content_with_stamp.into_bytes(),
+                stamp_meta: stamp.meta,
+                drift_status,
+            }
+        };
+
+        Ok((output, hit))
+    }
+}
+
+/// Map `f` over `items` on scoped worker threads, preserving order.
+fn par_map<T, R, F>(items: &[T], f: F) -> Result<Vec<R>, PipelineError>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(items.len());
+    if workers <= 1 {
+        return Ok(items.iter().map(f).collect());
+    }
+
+    let chunk = items.len().div_ceil(workers);
+    std::thread::scope(|s| {
+        let handles: Vec<_> = items
+            .chunks(chunk)
+            .map(|c| s.spawn(|| c.iter().map(&f).collect::<Vec<R>>()))
+            .collect();
+
+        let mut out = Vec::with_capacity(items.len());
+        for h in handles {
+            let part = h.join().map_err(|panic| PipelineError::RenderWorker {
+                message: panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string()),
+            })?;
+            out.extend(part);
+        }
+        Ok(out)
+    })
+}
+
+impl PipelineResult {
+    /// Outputs in the shape expected by `matwiz::Backend::apply`.
+    pub fn materialize_outputs(&self) -> Vec<RenderedOutput> {
+        self.outputs
+            .iter()
+            .map(|o| RenderedOutput {
+                path: o.planned.path.clone(),
+                bytes: o.content_with_stamp.clone(),
+                stamp_meta: o.stamp_meta.clone(),
+                drift_status: o.drift_status,
+            })
+            .collect()
+    }
+
+    /// This adapter's share of a multi-adapter `matwiz::apply_transaction`.
+    pub fn apply_batch(&self) -> ApplyBatch {
+        ApplyBatch {
+            plan: self.plan.plan.clone(),
+            outputs: self.materialize_outputs(),
+        }
+    }
+
+    /// Outputs in the shape expected by `vfsmnt::create_workspace`.
+    pub fn overlay_files(&self) -> Vec<OverlayFile> {
+        self.outputs
+            .iter()
+            .map(|o| OverlayFile {
+                rel_path: o.planned.path.as_str().to_string(),
+                bytes: o.content_with_stamp.clone(),
+                executable: false,
+            })
+            .collect()
+    }
+
+    /// Diff rendered outputs against the working tree, including stale generated files.
+    pub fn diff(&self) -> Result<DiffReport, PipelineError> {
+        let mut entries = vec![];
+        for o in &self.outputs {
+            entries.push(match std::str::from_utf8(&o.content_without_stamp) {
+                Ok(text) if o.planned.stamp_method().is_some() => {
+                    diff_planned_output(&self.repo_root, &o.planned, text, o.drift_status)?
+                }
+                _ => diff_unstamped_output(
+                    &self.repo_root,
+                    &o.planned,
+                    &o.content_without_stamp,
+                    o.drift_status,
+                )?,
+            });
+        }
+
+        entries.extend(stale_entries(&self.repo_root, &self.plan.plan)?);
+
+        Ok(DiffReport { entries })
+    }
+}
+
+/// Skill assets are planned identically by every adapter; keep only the first adapter's copy of
+/// each so a multi-adapter sync writes (and stamps) it once.
+pub fn dedupe_skill_assets(results: &mut [PipelineResult]) {
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    for r in results.iter_mut() {
+        drop_outputs(r, |o| {
+            o.skill_asset.is_some() && seen.contains(o.path.as_str())
+        });
+        seen.extend(
+            r.outputs
+                .iter()
+                .filter(|o| o.planned.skill_asset.is_some())
+                .map(|o| o.planned.path.as_str().to_string()),
+        );
+    }
+}
+
+/// Let the shared surfaces owner (`defaults.sharedSurfacesOwner`) keep the paths and surfaces it
+/// declares `collision: shared_owner`; other adapters' outputs for them are dropped, as the owner
+/// already writes the file they would have written.
+pub fn yield_shared_surfaces_to_owner(results: &mut [PipelineResult], owner: &str) {
+    let mut paths: BTreeSet<String> = BTreeSet::new();
+    let mut surfaces: BTreeSet<String> = BTreeSet::new();
+    for r in results.iter().filter(|r| r.plan.plan.agent_id == owner) {
+        for o in &r.outputs {
+            if o.planned.collision != CollisionPolicy::SharedOwner {
+                continue;
+            }
+            paths.insert(o.planned.path.as_str().to_string());
+            surfaces.extend(o.planned.surface.clone());
+        }
+    }
+    if paths.is_empty() {
+        return;
+    }
+
+    for r in results.iter_mut().filter(|r| r.plan.plan.agent_id != owner) {
+        drop_outputs(r, |o| {
+            paths.contains(o.path.as_str())
+                || o.surface.as_ref().is_some_and(|s| surfaces.contains(s))
+        });
+    }
+}
+
+/// Remove matching outputs from a result's rendered outputs, plan and source maps.
+fn drop_outputs(r: &mut PipelineResult, drop: impl Fn(&PlannedOutput) -> bool) {
+    r.outputs.retain(|o| !drop(&o.planned));
+    r.plan.plan.outputs.retain(|o| !drop(o));
+    let kept: BTreeSet<&str> = r
+        .plan
+        .plan
+        .outputs
+        .iter()
+        .map(|o| o.path.as_str())
+        .collect();
+    r.plan
+        .sources
+        .retain(|s| kept.contains(s.output_path.as_str()));
+}
+
+/// Reject outputs that collide across adapters rendered together.
+///
+/// Collisions within one adapter are resolved by `plan_outputs`; across adapters any shared path
+/// or logical surface left after `yield_shared_surfaces_to_owner` is an error since there is no
+/// single owner to merge them. Skill assets are the exception: they are identical for every
+/// adapter (see `dedupe_skill_assets`).
+pub fn check_cross_adapter_collisions(results: &[PipelineResult]) -> Result<(), PipelineError> {
+    let mut by_path: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut by_surface: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for r in results {
+        let agent_id = r.plan.plan.agent_id.as_str();
+        for o in r.outputs.iter().filter(|o| o.planned.skill_asset.is_none()) {
+            by_path
+                .entry(o.planned.path.as_str())
+                .or_default()
+                .push(agent_id);
+            if let Some(surface) = o.planned.surface.as_deref() {
+                by_surface.entry(surface).or_default().push(agent_id);
+            }
+        }
+    }
+
+    for (path, mut agents) in by_path {
+        agents.sort();
+        agents.dedup();
+        if agents.len() > 1 {
+            return Err(PipelineError::CrossAdapterPathCollision {
+                path: path.to_string(),
+                adapters: agents.join(", "),
+            });
+        }
+    }
+
+    for (surface, mut agents) in by_surface {
+        agents.sort();
+        agents.dedup();
+        if agents.len() > 1 {
+            return Err(PipelineError::CrossAdapterSurfaceCollision {
+                surface: surface.to_string(),
+                adapters: agents.join(", "),
+            });
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use crate::driftx::DriftxError;
+use crate::fsutil::FsError;
+use crate::outputs::{PlanError, PlanResult, PlannedOutput, RenderError};
+use crate::resolv::{EffectiveConfig, ResolveError};
+use crate::stamps::{DriftStatus, StampMeta};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PipelineError {
+    #[error("{0}")]
+    Resolve(#[from] ResolveError),
+
+    #[error("{0}")]
+    Plan(#[from] PlanError),
+
+    #[error("{source}")]
+    Render { path: String, source: RenderError },
+
+    #[error("{source}")]
+    Classify { path: String, source: FsError },
+
+    #[error("render worker panicked: {message}")]
+    RenderWorker { message: String },
+
+    #[error("rendered output missing stamp")]
+    MissingStamp { path: String },
+
+    #[error("{0}")]
+    Diff(#[from] DriftxError),
+
+    #[error("output collision across adapters at path: {path}: {adapters}")]
+    CrossAdapterPathCollision { path: String, adapters: String },
+
+    #[error("surface collision across adapters: {surface}: {adapters}")]
+    CrossAdapterSurfaceCollision { surface: String, adapters: String },
+}
+
+impl PipelineError {
+    /// Repo-relative output path the error is about, if any.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            PipelineError::Render { path, .. }
+            | PipelineError::Classify { path, .. }
+            | PipelineError::MissingStamp { path }
+            | PipelineError::CrossAdapterPathCollision { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// One planned output rendered to its final bytes.
+#[derive(Debug, Clone)]
+pub struct PipelineOutput {
+    pub planned: PlannedOutput,
+
+    pub content_without_stamp: Vec<u8>,
+
+    /// Fully rendered, stamped content to write to disk (the plain bytes for unstamped outputs).
+    pub content_with_stamp: Vec<u8>,
+
+    pub stamp_meta: StampMeta,
+
+    /// Classification of the current on-disk file against the planned content.
+    pub drift_status: DriftStatus,
+}
+
+#[derive(Debug)]
+pub struct PipelineResult {
+    pub repo_root: PathBuf,
+    pub effective: EffectiveConfig,
+    pub plan: PlanResult,
+    pub outputs: Vec<PipelineOutput>,
+
+    /// Outputs whose content came from the render cache.
+    pub cache_hits: usize,
+}
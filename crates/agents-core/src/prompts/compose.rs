@@ -1,5 +1,6 @@
 use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
@@ -20,14 +21,14 @@ pub enum PromptError {
 #[derive(Debug, Clone)]
 pub struct PromptComposer {
     repo_root: PathBuf,
-    repo: RepoConfig,
+    repo: Arc<RepoConfig>,
 }
 
 impl PromptComposer {
-    pub fn new(repo_root: &Path, repo: RepoConfig) -> Self {
+    pub fn new(repo_root: &Path, repo: impl Into<Arc<RepoConfig>>) -> Self {
         Self {
             repo_root: repo_root.to_path_buf(),
-            repo,
+            repo: repo.into(),
         }
     }
 
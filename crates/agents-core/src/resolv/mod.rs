@@ -1,7 +1,11 @@
+pub mod policy_merge;
 pub mod resolve;
 pub mod scope_match;
+pub mod trace;
 pub mod types;
 
+pub use policy_merge::*;
 pub use resolve::*;
 pub use scope_match::*;
+pub use trace::*;
 pub use types::*;
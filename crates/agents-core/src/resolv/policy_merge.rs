@@ -0,0 +1,228 @@
+use serde::Serialize;
+
+use crate::model::{
+    Capabilities, Confirmations, ExecCaps, FilesystemCaps, Limits, McpCaps, NetworkCaps, Paths,
+    Policy,
+};
+
+/// A policy built from the policies of several matched scopes
+/// (`resolution.mergeScopePolicies`).
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedPolicy {
+    /// The policies that were merged, in the order their scopes were applied.
+    pub sources: Vec<String>,
+
+    pub policy: Policy,
+
+    /// Entries one source allowed and another denied, as `"<list>: <entry>"` (e.g.
+    /// `"exec: curl"`). They are kept in the allow list, or dropped from it when
+    /// `denyOverridesAllow` is set.
+    pub conflicts: Vec<String>,
+}
+
+/// Merge `policies` into one: allow/deny lists union, boolean capabilities and limits take the
+/// most restrictive value, and confirmations union. A policy that leaves a capability block out
+/// takes part with the runtime default for it (network off; filesystem read/write only).
+pub fn merge_policies(policies: &[&Policy], deny_overrides_allow: bool) -> MergedPolicy {
+    let sources: Vec<String> = policies.iter().map(|p| p.id.clone()).collect();
+    let mut conflicts = vec![];
+
+    let filesystem = merge_opt(
+        policies,
+        |p| p.capabilities.filesystem.as_ref(),
+        Some(FilesystemCaps {
+            read: true,
+            write: true,
+            delete: false,
+            rename: false,
+        }),
+        |a, b| FilesystemCaps {
+            read: a.read && b.read,
+            write: a.write && b.write,
+            delete: a.delete && b.delete,
+            rename: a.rename && b.rename,
+        },
+    );
+
+    let mut exec = merge_opt(
+        policies,
+        |p| p.capabilities.exec.as_ref(),
+        None,
+        |a, b| ExecCaps {
+            enabled: a.enabled && b.enabled,
+            allow: union(&a.allow, &b.allow),
+            deny: union(&a.deny, &b.deny),
+        },
+    );
+    if let Some(e) = &mut exec {
+        settle(
+            "exec",
+            &mut e.allow,
+            &mut e.deny,
+            deny_overrides_allow,
+            &mut conflicts,
+        );
+    }
+
+    let mut network = merge_opt(
+        policies,
+        |p| p.capabilities.network.as_ref(),
+        Some(NetworkCaps {
+            enabled: false,
+            allow_hosts: vec![],
+            deny_hosts: vec![],
+        }),
+        |a, b| NetworkCaps {
+            enabled: a.enabled && b.enabled,
+            allow_hosts: union(&a.allow_hosts, &b.allow_hosts),
+            deny_hosts: union(&a.deny_hosts, &b.deny_hosts),
+        },
+    );
+    if let Some(n) = &mut network {
+        settle(
+            "network",
+            &mut n.allow_hosts,
+            &mut n.deny_hosts,
+            deny_overrides_allow,
+            &mut conflicts,
+        );
+    }
+
+    let mut mcp = merge_opt(
+        policies,
+        |p| p.capabilities.mcp.as_ref(),
+        None,
+        |a, b| McpCaps {
+            enabled: a.enabled && b.enabled,
+            allow_servers: union(&a.allow_servers, &b.allow_servers),
+            deny_servers: union(&a.deny_servers, &b.deny_servers),
+        },
+    );
+    if let Some(m) = &mut mcp {
+        settle(
+            "mcp",
+            &mut m.allow_servers,
+            &mut m.deny_servers,
+            deny_overrides_allow,
+            &mut conflicts,
+        );
+    }
+
+    let mut paths = Paths {
+        allow: vec![],
+        deny: vec![],
+        redact: vec![],
+    };
+    let mut required_for = vec![];
+    for p in policies {
+        paths.allow = union(&paths.allow, &p.paths.allow);
+        paths.deny = union(&paths.deny, &p.paths.deny);
+        paths.redact = union(&paths.redact, &p.paths.redact);
+        for c in &p.confirmations.required_for {
+            if !required_for.contains(c) {
+                required_for.push(*c);
+            }
+        }
+    }
+    settle(
+        "paths",
+        &mut paths.allow,
+        &mut paths.deny,
+        deny_overrides_allow,
+        &mut conflicts,
+    );
+
+    let limits = merge_opt(
+        policies,
+        |p| p.limits.as_ref(),
+        None,
+        |a, b| Limits {
+            max_files_changed: min_opt(a.max_files_changed, b.max_files_changed),
+            max_patch_lines: min_opt(a.max_patch_lines, b.max_patch_lines),
+            max_command_runtime_sec: min_opt(a.max_command_runtime_sec, b.max_command_runtime_sec),
+        },
+    );
+
+    let policy = Policy {
+        id: sources.join("+"),
+        extends: vec![],
+        description: format!("merged from {}", sources.join(", ")),
+        capabilities: Capabilities {
+            filesystem,
+            exec,
+            network,
+            mcp,
+        },
+        paths,
+        confirmations: Confirmations { required_for },
+        limits,
+        x: None,
+    };
+
+    MergedPolicy {
+        sources,
+        policy,
+        conflicts,
+    }
+}
+
+/// Fold the blocks the policies set. A policy that leaves a block out contributes `missing`, the
+/// runtime default for it, or is skipped when the default is unrestricted (`None`); either way
+/// it does not loosen the result. The block stays out when no policy sets it.
+fn merge_opt<T: Clone>(
+    policies: &[&Policy],
+    get: impl Fn(&Policy) -> Option<&T>,
+    missing: Option<T>,
+    merge: impl Fn(&T, &T) -> T,
+) -> Option<T> {
+    if policies.iter().all(|p| get(p).is_none()) {
+        return None;
+    }
+
+    policies
+        .iter()
+        .filter_map(|p| get(p).or(missing.as_ref()))
+        .fold(None, |acc, b| {
+            Some(match acc {
+                None => b.clone(),
+                Some(a) => merge(&a, b),
+            })
+        })
+}
+
+fn union(a: &[String], b: &[String]) -> Vec<String> {
+    let mut out = a.to_vec();
+    for s in b {
+        if !out.contains(s) {
+            out.push(s.clone());
+        }
+    }
+    out
+}
+
+fn min_opt(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Record entries that are both allowed and denied, then drop them from one side.
+fn settle(
+    list: &str,
+    allow: &mut Vec<String>,
+    deny: &mut Vec<String>,
+    deny_overrides_allow: bool,
+    conflicts: &mut Vec<String>,
+) {
+    let both: Vec<String> = allow.iter().filter(|a| deny.contains(a)).cloned().collect();
+    if both.is_empty() {
+        return;
+    }
+    conflicts.extend(both.iter().map(|e| format!("{list}: {e}")));
+    if deny_overrides_allow {
+        allow.retain(|a| !both.contains(a));
+    } else {
+        deny.retain(|d| !both.contains(d));
+    }
+}
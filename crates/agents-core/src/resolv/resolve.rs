@@ -1,47 +1,109 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use crate::loadag::{load_repo_config, RepoConfig};
-use crate::model::BackendKind;
-use crate::resolv::{match_scopes, EffectiveConfig, ResolutionRequest, ScopeMatch};
+use crate::loadag::{apply_user_overlay, user_overlay_dir, LoadError, RepoConfig};
+use crate::model::{BackendKind, OnConflict, ScopeOverrides, UserOverlayDefault};
+use crate::resolv::{
+    match_scopes, merge_policies, EffectiveConfig, Layered, ResolutionRequest, ResolutionTrace,
+    ScopeMatch,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ResolveError {
     #[error("missing id {id} in {kind}")]
     MissingId { kind: &'static str, id: String },
+
+    #[error("merged policy {policy} both allows and denies: {conflicts}")]
+    PolicyConflict { policy: String, conflicts: String },
+
+    #[error("user overlay: {0}")]
+    UserOverlay(#[source] LoadError),
 }
 
 #[derive(Debug)]
 pub struct Resolver {
-    pub repo: RepoConfig,
+    pub repo: Arc<RepoConfig>,
 }
 
 impl Resolver {
-    pub fn new(repo: RepoConfig) -> Self {
-        Self { repo }
+    pub fn new(repo: impl Into<Arc<RepoConfig>>) -> Self {
+        Self { repo: repo.into() }
     }
 
+    /// Like `resolve`, plus the agent's `byAgent` overrides from the manifest and from each
+    /// matched scope.
     pub fn resolve_for_agent(
         &self,
         req: &ResolutionRequest,
-        _agent_id: &str,
+        agent_id: &str,
+    ) -> Result<EffectiveConfig, ResolveError> {
+        self.resolve_inner(req, Some(agent_id))
+    }
+
+    /// Re-resolve `effective`'s request with `scope_id` as the only scope, for outputs generated
+    /// once per scope.
+    pub fn resolve_for_scope(
+        &self,
+        effective: &EffectiveConfig,
+        scope_id: &str,
     ) -> Result<EffectiveConfig, ResolveError> {
-        // v1: agent_id does not affect resolution yet.
-        self.resolve(req)
+        let req = ResolutionRequest {
+            override_scopes: vec![scope_id.to_string()],
+            ..effective.request.clone()
+        };
+        self.resolve_inner(&req, effective.agent_id.as_deref())
     }
 
+    /// Precedence, lowest first: manifest defaults, user overlay defaults (only the fields the
+    /// manifest lists in `resolution.userOverlayDefaults`), matched scopes (each followed by its
+    /// `byAgent` entry), the manifest's `byAgent` entry, profile, state, CLI overrides. Mode frontmatter then adds its skills and snippets and may set the policy.
+    /// With `resolution.mergeScopePolicies`, the policies of all matched scopes are merged (see
+    /// `merge_policies`) instead of the most specific scope's replacing the others'.
+    /// `EffectiveConfig::trace` records which of these layers set each value.
+    ///
+    /// With `enable_user_overlay` set, the overlay's modes, policies and snippets are also
+    /// addressable (see `loadag::apply_user_overlay`).
     pub fn resolve(&self, req: &ResolutionRequest) -> Result<EffectiveConfig, ResolveError> {
+        self.resolve_inner(req, None)
+    }
+
+    fn resolve_inner(
+        &self,
+        req: &ResolutionRequest,
+        agent_id: Option<&str>,
+    ) -> Result<EffectiveConfig, ResolveError> {
+        if req.enable_user_overlay && self.repo.user_overlay.is_none() {
+            if let Some(dir) = user_overlay_dir(req.user_overlay_root.as_deref()) {
+                let mut repo = (*self.repo).clone();
+                apply_user_overlay(&mut repo, &dir).map_err(ResolveError::UserOverlay)?;
+                if repo.user_overlay.is_some() {
+                    return Resolver::new(repo).resolve_inner(req, agent_id);
+                }
+            }
+        }
+
         let target_path = req.target_path.clone().unwrap_or_else(|| ".".to_string());
 
-        let scopes_matched = if req.override_scopes.is_empty() {
+        // Explicit scopes: CLI overrides win over scopes pinned in state.
+        let explicit_scopes: &[String] = if !req.override_scopes.is_empty() {
+            &req.override_scopes
+        } else {
+            self.repo
+                .state
+                .as_ref()
+                .map(|s| s.scopes.as_slice())
+                .unwrap_or(&[])
+        };
+
+        let scopes_matched = if explicit_scopes.is_empty() {
             match_scopes(&self.repo, &target_path).map_err(|e| ResolveError::MissingId {
                 kind: "scope_glob",
                 id: e.to_string(),
             })?
         } else {
             // Explicit list: preserve deterministic order (sorted by id).
-            let mut ms: Vec<ScopeMatch> = req
-                .override_scopes
+            let mut ms: Vec<ScopeMatch> = explicit_scopes
                 .iter()
                 .map(|id| {
                     let scope =
@@ -68,7 +130,6 @@ impl Resolver {
         // Start from manifest defaults.
         let mut mode_id = self.repo.manifest.defaults.mode.clone();
         let mut policy_id = self.repo.manifest.defaults.policy.clone();
-        let mut profile = self.repo.manifest.defaults.profile.clone();
         let mut backend = self
             .repo
             .manifest
@@ -76,6 +137,47 @@ impl Resolver {
             .backend
             .unwrap_or(BackendKind::VfsContainer);
 
+        // Every value each layer gives a field, for `EffectiveConfig::trace`.
+        let mut mode_trace = Layered::default();
+        let mut policy_trace = Layered::default();
+        let mut backend_trace = Layered::default();
+        let mut skill_trace: BTreeMap<String, Layered> = BTreeMap::new();
+        let mut snippet_trace: BTreeMap<String, Layered> = BTreeMap::new();
+        mode_trace.set("defaults", &mode_id);
+        policy_trace.set("defaults", &policy_id);
+        backend_trace.set("defaults", format!("{backend:?}"));
+
+        // User overlay defaults replace the repo's only where the manifest allows it.
+        let overlay = self.repo.user_overlay.as_ref();
+        let overlay_defaults_allowed = self
+            .repo
+            .manifest
+            .resolution
+            .as_ref()
+            .map(|r| r.user_overlay_defaults.as_slice())
+            .unwrap_or(&[]);
+        let overlay_default = |field: UserOverlayDefault| {
+            overlay.filter(|_| overlay_defaults_allowed.contains(&field))
+        };
+        if let Some(m) =
+            overlay_default(UserOverlayDefault::Mode).and_then(|o| o.defaults.mode.clone())
+        {
+            mode_trace.set("user_overlay", &m);
+            mode_id = m;
+        }
+        if let Some(p) =
+            overlay_default(UserOverlayDefault::Policy).and_then(|o| o.defaults.policy.clone())
+        {
+            policy_trace.set("user_overlay", &p);
+            policy_id = p;
+        }
+        if let Some(b) =
+            overlay_default(UserOverlayDefault::Backend).and_then(|o| o.defaults.backend)
+        {
+            backend_trace.set("user_overlay", format!("{b:?}"));
+            backend = b;
+        }
+
         // Apply scopes from least specific to most specific so more specific wins.
         let mut scopes_for_apply = scopes_matched.clone();
         scopes_for_apply.sort_by(|a, b| {
@@ -89,86 +191,150 @@ impl Resolver {
         let mut disable_skills: BTreeSet<String> = BTreeSet::new();
         let mut include_snippets: BTreeSet<String> = BTreeSet::new();
 
-        for m in &scopes_for_apply {
-            let scope = self.repo.scopes.get(&m.id).unwrap();
-            if let Some(mo) = &scope.overrides.mode {
+        let mut add_lists =
+            |layer: &str, enable: &[String], disable: &[String], snippets: &[String]| {
+                for s in enable {
+                    enable_skills.insert(s.clone());
+                    skill_trace
+                        .entry(s.clone())
+                        .or_default()
+                        .set(layer, "enabled");
+                }
+                for s in disable {
+                    disable_skills.insert(s.clone());
+                    skill_trace
+                        .entry(s.clone())
+                        .or_default()
+                        .set(layer, "disabled");
+                }
+                for s in snippets {
+                    include_snippets.insert(s.clone());
+                    snippet_trace
+                        .entry(s.clone())
+                        .or_default()
+                        .set(layer, "included");
+                }
+            };
+
+        let mut apply = |layer: &str, o: &ScopeOverrides| {
+            if let Some(mo) = &o.mode {
+                mode_trace.set(layer, mo);
                 mode_id = mo.clone();
             }
-            if let Some(po) = &scope.overrides.policy {
+            if let Some(po) = &o.policy {
+                policy_trace.set(layer, po);
                 policy_id = po.clone();
             }
-            for s in &scope.overrides.enable_skills {
-                enable_skills.insert(s.clone());
-            }
-            for s in &scope.overrides.disable_skills {
-                disable_skills.insert(s.clone());
-            }
-            for snip in &scope.overrides.include_snippets {
-                include_snippets.insert(snip.clone());
+            add_lists(
+                layer,
+                &o.enable_skills,
+                &o.disable_skills,
+                &o.include_snippets,
+            );
+        };
+
+        for m in &scopes_for_apply {
+            let scope = self.repo.scopes.get(&m.id).unwrap();
+            apply(&format!("scope:{}", m.id), &scope.overrides);
+            if let Some((a, o)) = agent_id.and_then(|a| Some((a, scope.by_agent.get(a)?))) {
+                apply(&format!("scope:{}/byAgent:{a}", m.id), o);
             }
         }
 
-        // Apply user overlay (lowest precedence).
-        if req.enable_user_overlay {
-            let overlay_root = req
-                .user_overlay_root
-                .clone()
-                .or_else(|| std::env::var_os("HOME").map(PathBuf::from));
-
-            if let Some(home) = overlay_root {
-                let overlay_repo_root = home.join(".agents");
-                let opts = crate::loadag::LoaderOptions {
-                    require_schemas_dir: false,
-                };
-
-                if let Ok((overlay_cfg, _report)) = load_repo_config(&overlay_repo_root, &opts) {
-                    // Lowest precedence: only fill if not already set (but we set defaults first).
-                    // So, overlay acts as a fallback if manifest defaults are missing (rare), or
-                    // can influence lists via union.
-
-                    // Scalars: only if empty
-                    if mode_id.is_empty() {
-                        mode_id = overlay_cfg.manifest.defaults.mode;
-                    }
-                    if policy_id.is_empty() {
-                        policy_id = overlay_cfg.manifest.defaults.policy;
-                    }
+        // The agent's manifest overrides sit above every scope.
+        let agent_overrides = agent_id.and_then(|a| Some((a, self.repo.manifest.by_agent.get(a)?)));
+        if let Some((a, o)) = agent_overrides {
+            apply(&format!("byAgent:{a}"), o);
+        }
 
-                    // Lists: union
-                    for s in overlay_cfg
-                        .modes
-                        .values()
-                        .filter_map(|m| m.frontmatter.as_ref())
-                        .flat_map(|fm| fm.enable_skills.iter())
-                    {
-                        enable_skills.insert(s.clone());
+        // With `mergeScopePolicies`, every policy a matched scope sets is merged into one.
+        let resolution = self.repo.manifest.resolution.as_ref();
+        let mut merge_sources: Vec<String> = vec![];
+        if resolution.is_some_and(|r| r.merge_scope_policies) {
+            for m in &scopes_for_apply {
+                let scope = &self.repo.scopes[&m.id];
+                let by_agent = agent_id.and_then(|a| scope.by_agent.get(a));
+                for p in std::iter::once(&scope.overrides)
+                    .chain(by_agent)
+                    .filter_map(|o| o.policy.as_ref())
+                {
+                    if !merge_sources.contains(p) {
+                        merge_sources.push(p.clone());
                     }
                 }
             }
         }
+        let merged_id = (merge_sources.len() > 1).then(|| merge_sources.join("+"));
+        // A merged scope policy still yields to a manifest `byAgent` policy.
+        let agent_policy = agent_overrides.is_some_and(|(_, o)| o.policy.is_some());
+        if let Some(id) = merged_id.as_ref().filter(|_| !agent_policy) {
+            policy_trace.set("scope_merge", id);
+            policy_id = id.clone();
+        }
+
+        // Apply the selected profile (CLI, then state, then manifest default): above scopes and
+        // the overlay, below state and CLI overrides.
+        let profile = self
+            .repo
+            .selected_profile(req.override_profile.as_deref())
+            .map(str::to_string);
+        let mut adapters_enabled = self.repo.manifest.enabled.adapters.clone();
+        let mut vars = BTreeMap::new();
+        if let Some(pr) = &profile {
+            let p = self
+                .repo
+                .profiles
+                .get(pr)
+                .ok_or_else(|| ResolveError::MissingId {
+                    kind: "profiles",
+                    id: pr.clone(),
+                })?;
+            let layer = format!("profile:{pr}");
+            if let Some(mo) = &p.mode {
+                mode_trace.set(&layer, mo);
+                mode_id = mo.clone();
+            }
+            if let Some(po) = &p.policy {
+                policy_trace.set(&layer, po);
+                policy_id = po.clone();
+            }
+            if let Some(b) = p.backend {
+                backend_trace.set(&layer, format!("{b:?}"));
+                backend = b;
+            }
+            if let Some(a) = &p.adapters {
+                adapters_enabled = a.clone();
+            }
+            add_lists(
+                &layer,
+                &p.enable_skills,
+                &p.disable_skills,
+                &p.include_snippets,
+            );
+            vars = p.vars.clone();
+        }
 
         // Apply state (if any) unless CLI overrides are provided.
         if let Some(state) = &self.repo.state {
+            mode_trace.set("state", &state.mode);
             mode_id = state.mode.clone();
-            if let Some(p) = &state.profile {
-                profile = Some(p.clone());
-            }
             if let Some(b) = state.backend {
+                backend_trace.set("state", format!("{b:?}"));
                 backend = b;
             }
         }
 
         // Apply CLI overrides.
         if let Some(mo) = &req.override_mode {
+            mode_trace.set("cli", mo);
             mode_id = mo.clone();
         }
         if let Some(po) = &req.override_policy {
+            policy_trace.set("cli", po);
             policy_id = po.clone();
         }
-        if let Some(pr) = &req.override_profile {
-            profile = Some(pr.clone());
-        }
         if let Some(b) = req.override_backend {
+            backend_trace.set("cli", format!("{b:?}"));
             backend = b;
         }
 
@@ -179,40 +345,96 @@ impl Resolver {
                 id: mode_id,
             });
         }
-        if !self.repo.policies.contains_key(&policy_id) {
+        if merged_id.as_ref() != Some(&policy_id) && !self.repo.policies.contains_key(&policy_id) {
             return Err(ResolveError::MissingId {
                 kind: "policies",
                 id: policy_id,
             });
         }
-        if let Some(pr) = &profile {
-            if !self.repo.profiles.contains_key(pr) {
-                return Err(ResolveError::MissingId {
-                    kind: "profiles",
-                    id: pr.clone(),
-                });
-            }
-        }
 
         // Mode frontmatter contributes enable/disable skills + include snippets.
         if let Some(mode) = self.repo.modes.get(&mode_id) {
             if let Some(fm) = &mode.frontmatter {
-                for s in &fm.enable_skills {
-                    enable_skills.insert(s.clone());
-                }
-                for s in &fm.disable_skills {
-                    disable_skills.insert(s.clone());
-                }
-                for snip in &fm.include_snippets {
-                    include_snippets.insert(snip.clone());
-                }
+                let layer = format!("mode:{mode_id}");
+                add_lists(
+                    &layer,
+                    &fm.enable_skills,
+                    &fm.disable_skills,
+                    &fm.include_snippets,
+                );
 
                 if let Some(p) = &fm.policy {
+                    policy_trace.set(&layer, p);
                     policy_id = p.clone();
                 }
             }
         }
 
+        // Later layers may have replaced the merged policy.
+        let merged_policy = match merged_id {
+            Some(id) if id == policy_id => {
+                let sources = merge_sources
+                    .iter()
+                    .map(|p| {
+                        self.repo
+                            .policies
+                            .get(p)
+                            .ok_or_else(|| ResolveError::MissingId {
+                                kind: "policies",
+                                id: p.clone(),
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let merged =
+                    merge_policies(&sources, resolution.is_some_and(|r| r.deny_overrides_allow));
+                if !merged.conflicts.is_empty()
+                    && resolution.and_then(|r| r.on_conflict) == Some(OnConflict::Error)
+                {
+                    return Err(ResolveError::PolicyConflict {
+                        policy: id,
+                        conflicts: merged.conflicts.join(", "),
+                    });
+                }
+                Some(merged)
+            }
+            _ => None,
+        };
+
+        let mut profile_trace = Layered::default();
+        for (layer, value) in [
+            ("defaults", self.repo.manifest.defaults.profile.as_ref()),
+            (
+                "state",
+                self.repo.state.as_ref().and_then(|s| s.profile.as_ref()),
+            ),
+            ("cli", req.override_profile.as_ref()),
+        ] {
+            if let Some(v) = value {
+                profile_trace.set(layer, v);
+            }
+        }
+        let trace = ResolutionTrace {
+            mode: mode_trace.last_wins(),
+            policy: policy_trace.last_wins(),
+            profile: profile_trace.last_wins(),
+            backend: backend_trace.last_wins(),
+            skills: skill_trace
+                .into_iter()
+                .filter_map(|(id, t)| {
+                    let value = if disable_skills.contains(&id) {
+                        "disabled"
+                    } else {
+                        "enabled"
+                    };
+                    Some((id, t.membership(value)?))
+                })
+                .collect(),
+            snippets: snippet_trace
+                .into_iter()
+                .filter_map(|(id, t)| Some((id, t.membership("included")?)))
+                .collect(),
+        };
+
         // Finalize skills: enabled minus disabled.
         let mut skill_ids_enabled: Vec<String> = enable_skills
             .into_iter()
@@ -241,7 +463,39 @@ impl Resolver {
             }
         }
 
+        let mut from_user_overlay = vec![];
+        if let Some(o) = overlay {
+            if overlay_default(UserOverlayDefault::Mode).is_some()
+                && o.defaults.mode.as_ref() == Some(&mode_id)
+            {
+                from_user_overlay.push(format!("defaults.mode: {mode_id}"));
+            }
+            if overlay_default(UserOverlayDefault::Policy).is_some()
+                && o.defaults.policy.as_ref() == Some(&policy_id)
+            {
+                from_user_overlay.push(format!("defaults.policy: {policy_id}"));
+            }
+            if overlay_default(UserOverlayDefault::Backend).is_some()
+                && o.defaults.backend == Some(backend)
+            {
+                from_user_overlay.push(format!("defaults.backend: {backend:?}"));
+            }
+            if o.modes.contains(&mode_id) {
+                from_user_overlay.push(format!("mode: {mode_id}"));
+            }
+            if o.policies.contains(&policy_id) {
+                from_user_overlay.push(format!("policy: {policy_id}"));
+            }
+            for snip in snippet_ids_included
+                .iter()
+                .filter(|s| o.snippets.contains(s))
+            {
+                from_user_overlay.push(format!("snippet: {snip}"));
+            }
+        }
+
         Ok(EffectiveConfig {
+            agent_id: agent_id.map(str::to_string),
             mode_id,
             policy_id,
             profile,
@@ -249,6 +503,12 @@ impl Resolver {
             scopes_matched,
             skill_ids_enabled,
             snippet_ids_included,
+            adapters_enabled,
+            vars,
+            from_user_overlay,
+            merged_policy,
+            trace,
+            request: req.clone(),
         })
     }
 }
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// A value one resolution layer gave a field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayerValue {
+    /// `defaults`, `user_overlay`, `byAgent:<agent>`, `scope:<id>`, `scope:<id>/byAgent:<agent>`,
+    /// `profile:<id>`, `state`, `cli` or `mode:<id>`.
+    pub layer: String,
+    pub value: String,
+}
+
+/// Which layer set a field, and the values of the layers it overrode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Provenance {
+    pub value: String,
+    pub set_by: String,
+
+    /// Lowest precedence first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overrode: Vec<LayerValue>,
+}
+
+/// Provenance for every resolved field (`EffectiveConfig::trace`).
+///
+/// Skills are `enabled` or `disabled` (a disable from any layer wins); snippets are `included`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ResolutionTrace {
+    pub mode: Option<Provenance>,
+    pub policy: Option<Provenance>,
+    pub profile: Option<Provenance>,
+    pub backend: Option<Provenance>,
+    pub skills: BTreeMap<String, Provenance>,
+    pub snippets: BTreeMap<String, Provenance>,
+}
+
+impl ResolutionTrace {
+    /// One line per field: `mode: x (set by scope:a; overrode defaults=y)`.
+    pub fn lines(&self) -> Vec<String> {
+        let scalars = [
+            ("mode", &self.mode),
+            ("policy", &self.policy),
+            ("profile", &self.profile),
+            ("backend", &self.backend),
+        ];
+        let mut out: Vec<String> = scalars
+            .into_iter()
+            .filter_map(|(field, p)| p.as_ref().map(|p| describe(field, p)))
+            .collect();
+        for (id, p) in &self.skills {
+            out.push(describe(&format!("skill {id}"), p));
+        }
+        for (id, p) in &self.snippets {
+            out.push(describe(&format!("snippet {id}"), p));
+        }
+        out
+    }
+}
+
+fn describe(field: &str, p: &Provenance) -> String {
+    let mut line = format!("{field}: {} (set by {}", p.value, p.set_by);
+    if !p.overrode.is_empty() {
+        let overrode: Vec<String> = p
+            .overrode
+            .iter()
+            .map(|lv| format!("{}={}", lv.layer, lv.value))
+            .collect();
+        line.push_str(&format!("; overrode {}", overrode.join(", ")));
+    }
+    line.push(')');
+    line
+}
+
+/// Every value a field was given, in the order layers were applied.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layered(Vec<LayerValue>);
+
+impl Layered {
+    pub(crate) fn set(&mut self, layer: &str, value: impl Into<String>) {
+        self.0.push(LayerValue {
+            layer: layer.to_string(),
+            value: value.into(),
+        });
+    }
+
+    /// The last layer is the one that counts.
+    pub(crate) fn last_wins(mut self) -> Option<Provenance> {
+        let winner = self.0.pop()?;
+        Some(Provenance {
+            value: winner.value,
+            set_by: winner.layer,
+            overrode: self.0,
+        })
+    }
+
+    /// For set membership: the last layer giving `value` counts, and only layers that gave a
+    /// different value were overridden.
+    pub(crate) fn membership(mut self, value: &str) -> Option<Provenance> {
+        let i = self.0.iter().rposition(|lv| lv.value == value)?;
+        let winner = self.0.remove(i);
+        self.0.retain(|lv| lv.value != value);
+        Some(Provenance {
+            value: winner.value,
+            set_by: winner.layer,
+            overrode: self.0,
+        })
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use crate::model::BackendKind;
+use crate::loadag::RepoConfig;
+use crate::model::{BackendKind, Policy};
 
 #[derive(Debug, Clone)]
 pub struct ResolutionRequest {
@@ -22,7 +24,8 @@ pub struct ResolutionRequest {
 
     /// Optional override for where to look for the user overlay directory.
     ///
-    /// When set, the overlay is loaded from `<user_overlay_root>/.agents/manifest.yaml`.
+    /// When set, the overlay is loaded from `<user_overlay_root>/.agents` instead of
+    /// `$HOME/.agents`.
     pub user_overlay_root: Option<PathBuf>,
 }
 
@@ -35,7 +38,12 @@ pub struct ScopeMatch {
 
 #[derive(Debug, Clone)]
 pub struct EffectiveConfig {
+    /// The agent resolution was done for (`Resolver::resolve_for_agent`), if any.
+    pub agent_id: Option<String>,
+
     pub mode_id: String,
+
+    /// For a merged policy, the source ids joined with `+` (see `merged_policy`).
     pub policy_id: String,
     pub profile: Option<String>,
     pub backend: BackendKind,
@@ -44,4 +52,33 @@ pub struct EffectiveConfig {
 
     pub skill_ids_enabled: Vec<String>,
     pub snippet_ids_included: Vec<String>,
+
+    /// `manifest.enabled.adapters`, or the profile's `adapters` when it sets them.
+    pub adapters_enabled: Vec<String>,
+
+    /// Template variables from the profile.
+    pub vars: BTreeMap<String, serde_json::Value>,
+
+    /// Values that came from the user overlay, e.g. `mode: personal` or `snippet: my-tips`.
+    pub from_user_overlay: Vec<String>,
+
+    /// Set when `resolution.mergeScopePolicies` merged the policies of several matched scopes
+    /// and no later layer replaced the result.
+    pub merged_policy: Option<crate::resolv::MergedPolicy>,
+
+    /// Which layer set each of the values above, and what it overrode.
+    pub trace: crate::resolv::ResolutionTrace,
+
+    /// The request this config was resolved from (see `Resolver::resolve_for_scope`).
+    pub request: ResolutionRequest,
+}
+
+impl EffectiveConfig {
+    /// The effective policy: the merged one if scope policies were merged, else `policy_id`'s.
+    pub fn policy<'a>(&'a self, repo: &'a RepoConfig) -> Option<&'a Policy> {
+        match &self.merged_policy {
+            Some(m) => Some(&m.policy),
+            None => repo.policies.get(&self.policy_id),
+        }
+    }
 }
@@ -42,6 +42,19 @@ pub fn validate_repo_config(repo_root: &Path, cfg: &RepoConfig) -> Result<(), Sc
         }
     }
 
+    // Profiles; repos initialized before profiles were typed have no schema for them.
+    let profile_schema = repo_root
+        .join(".agents/schemas")
+        .join(SchemaKind::Profile.schema_file_name());
+    if profile_schema.is_file() {
+        for id in cfg.profiles.keys() {
+            let path = repo_root.join(format!(".agents/profiles/{id}.yaml"));
+            if path.is_file() {
+                validate_yaml_file(&mut store, SchemaKind::Profile, &path)?;
+            }
+        }
+    }
+
     // Adapters
     for adapter in cfg.adapters.values() {
         // Adapter file path is not stored in cfg, so derive from agent_id.
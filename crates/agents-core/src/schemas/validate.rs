@@ -20,6 +20,7 @@ pub enum SchemaKind {
     Policy,
     Skill,
     Scope,
+    Profile,
     Adapter,
     State,
     ModeFrontmatter,
@@ -32,6 +33,7 @@ impl SchemaKind {
             SchemaKind::Policy => "policy.schema.json",
             SchemaKind::Skill => "skill.schema.json",
             SchemaKind::Scope => "scope.schema.json",
+            SchemaKind::Profile => "profile.schema.json",
             SchemaKind::Adapter => "adapter.schema.json",
             SchemaKind::State => "state.schema.json",
             SchemaKind::ModeFrontmatter => "mode-frontmatter.schema.json",
@@ -8,6 +8,14 @@ pub const AGENTS_MD_PATH: &str = "AGENTS.md";
 pub const AGENTS_MD_SURFACE: &str = "shared:AGENTS.md";
 pub const AGENTS_MD_TEMPLATE: &str = "AGENTS.md.hbs";
 
+/// Partial for the `## Skills` section, available to every template as `{{> agents/skills}}`.
+pub const SKILLS_PARTIAL: &str = "agents/skills";
+
+/// Partials registered in every template engine; an adapter template with the same name wins.
+pub fn builtin_partials() -> [(&'static str, &'static str); 1] {
+    [(SKILLS_PARTIAL, include_str!("skills.md.hbs"))]
+}
+
 pub fn builtin_template(agent_id: &str, template_name: &str) -> Option<&'static str> {
     if agent_id == CORE_ADAPTER_ID && template_name == AGENTS_MD_TEMPLATE {
         return Some(include_str!("AGENTS.md.hbs"));
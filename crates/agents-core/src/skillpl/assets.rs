@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::fsutil::{self, RepoPath};
+use crate::skillpl::EffectiveSkills;
+
+/// Repo-relative root for skill assets: `<SKILL_ASSETS_DIR>/<skill id>/<asset path>`.
+pub const SKILL_ASSETS_DIR: &str = ".agents-skills";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SkillAssetError {
+    #[error("skill {skill_id}: asset path escapes the skill dir: {path}")]
+    EscapesSkillDir { skill_id: String, path: String },
+
+    #[error("skill {skill_id}: asset not found: {path}")]
+    Missing { skill_id: String, path: String },
+
+    #[error("skill {skill_id}: walkdir error: {message}")]
+    Walkdir { skill_id: String, message: String },
+}
+
+/// One file from a skill's `assets.mount` or `assets.materialize` list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillAsset {
+    pub skill_id: String,
+
+    /// Absolute path of the file in the skill dir.
+    pub source: PathBuf,
+
+    /// Path relative to the skill dir, with `/` separators.
+    pub rel_path: String,
+
+    /// Where the asset is delivered in the repo (or workspace).
+    pub dest: RepoPath,
+
+    /// Listed under `assets.mount`: bind-mounted read-only by `vfs_container` instead of copied.
+    pub mount: bool,
+}
+
+/// Expand every enabled skill's asset lists into files. Directory entries include every file
+/// below them; a path listed in both lists is treated as `materialize`.
+pub fn plan_skill_assets(
+    repo_root: &Path,
+    skills: &EffectiveSkills,
+) -> Result<Vec<SkillAsset>, SkillAssetError> {
+    let mut out: Vec<SkillAsset> = vec![];
+
+    for sr in &skills.enabled {
+        let Some(assets) = &sr.skill.assets else {
+            continue;
+        };
+
+        let entries = assets
+            .materialize
+            .iter()
+            .map(|p| (p, false))
+            .chain(assets.mount.iter().map(|p| (p, true)));
+        for (entry, mount) in entries {
+            for rel in expand_entry(&sr.id, &sr.dir, entry)? {
+                if out
+                    .iter()
+                    .any(|a| a.skill_id == sr.id && a.rel_path == rel.as_str())
+                {
+                    continue;
+                }
+                let dest = Path::new(SKILL_ASSETS_DIR).join(&sr.id).join(rel.as_str());
+                let dest = fsutil::repo_relpath_noexist(repo_root, &dest).map_err(|_| {
+                    SkillAssetError::EscapesSkillDir {
+                        skill_id: sr.id.clone(),
+                        path: entry.clone(),
+                    }
+                })?;
+                out.push(SkillAsset {
+                    skill_id: sr.id.clone(),
+                    source: sr.dir.join(rel.as_str()),
+                    dest,
+                    rel_path: rel.as_str().to_string(),
+                    mount,
+                });
+            }
+        }
+    }
+
+    out.sort_by(|a, b| a.dest.as_str().cmp(b.dest.as_str()));
+    Ok(out)
+}
+
+fn expand_entry(
+    skill_id: &str,
+    skill_dir: &Path,
+    entry: &str,
+) -> Result<Vec<RepoPath>, SkillAssetError> {
+    let rel = fsutil::repo_relpath_noexist(skill_dir, Path::new(entry)).map_err(|_| {
+        SkillAssetError::EscapesSkillDir {
+            skill_id: skill_id.to_string(),
+            path: entry.to_string(),
+        }
+    })?;
+
+    let abs = skill_dir.join(rel.as_str());
+    if abs.is_file() {
+        return Ok(vec![rel]);
+    }
+    if !abs.is_dir() {
+        return Err(SkillAssetError::Missing {
+            skill_id: skill_id.to_string(),
+            path: entry.to_string(),
+        });
+    }
+
+    let mut files = vec![];
+    for e in WalkDir::new(&abs).follow_links(false) {
+        let e = e.map_err(|e| SkillAssetError::Walkdir {
+            skill_id: skill_id.to_string(),
+            message: e.to_string(),
+        })?;
+        if !e.file_type().is_file() {
+            continue;
+        }
+        let sub = e.path().strip_prefix(skill_dir).unwrap_or(e.path());
+        if let Ok(p) = fsutil::repo_relpath_noexist(skill_dir, sub) {
+            files.push(p);
+        }
+    }
+    files.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    Ok(files)
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::fsutil;
+use crate::model::{McpCaps, Policy, SkillActivation, SkillInterfaceType};
+use crate::skillpl::{EffectiveSkills, SkillRef};
+
+/// One MCP server, in the `command`/`args`/`env` shape shared by Claude, Cursor and Gemini.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct McpServer {
+    pub command: String,
+
+    pub args: Vec<String>,
+
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct McpPlan {
+    /// Servers to generate, keyed by skill id.
+    pub servers: BTreeMap<String, McpServer>,
+
+    /// `(skill id, reason)` for MCP skills left out of the generated config.
+    pub excluded: Vec<(String, String)>,
+}
+
+/// MCP server definitions for the enabled `mcp_tool` skills, filtered through the policy's
+/// `capabilities.mcp` (`enabled`, `denyServers`, then `allowServers`; `*` matches any server).
+pub fn plan_mcp_servers(repo_root: &Path, skills: &EffectiveSkills, policy: &Policy) -> McpPlan {
+    let mut plan = McpPlan::default();
+
+    for sr in &skills.enabled {
+        let skill = &sr.skill;
+        if skill.activation != SkillActivation::McpTool
+            && skill.interface.type_ != SkillInterfaceType::Mcp
+        {
+            continue;
+        }
+
+        let Some(entrypoint) = skill.interface.entrypoint.as_deref() else {
+            plan.excluded
+                .push((sr.id.clone(), "interface has no entrypoint".to_string()));
+            continue;
+        };
+
+        if let Some(reason) = policy
+            .capabilities
+            .mcp
+            .as_ref()
+            .and_then(|caps| policy_denies(caps, &sr.id))
+        {
+            plan.excluded.push((sr.id.clone(), reason));
+            continue;
+        }
+
+        plan.servers.insert(
+            sr.id.clone(),
+            McpServer {
+                command: resolve_entrypoint(repo_root, sr, entrypoint),
+                args: skill.interface.args.clone(),
+                env: skill.interface.env.clone(),
+            },
+        );
+    }
+
+    plan
+}
+
+fn policy_denies(caps: &McpCaps, server: &str) -> Option<String> {
+    let matches = |list: &[String]| list.iter().any(|s| s == "*" || s == server);
+
+    if !caps.enabled {
+        return Some("mcp disabled by policy".to_string());
+    }
+    if matches(&caps.deny_servers) {
+        return Some("denied by policy denyServers".to_string());
+    }
+    if !caps.allow_servers.is_empty() && !matches(&caps.allow_servers) {
+        return Some("not in policy allowServers".to_string());
+    }
+    None
+}
+
+/// Entrypoints starting with `./` or `../` are relative to the skill dir; agents launch servers
+/// (and shims) from the repo root, so those become repo-relative. Anything else is a command on
+/// `PATH`.
+pub(crate) fn resolve_entrypoint(repo_root: &Path, sr: &SkillRef, entrypoint: &str) -> String {
+    if !(entrypoint.starts_with("./") || entrypoint.starts_with("../")) {
+        return entrypoint.to_string();
+    }
+
+    // Skills from outside the repo (e.g. the user overlay) keep an absolute path.
+    let Ok(dir) = sr.dir.strip_prefix(repo_root) else {
+        return sr.dir.join(entrypoint).to_string_lossy().to_string();
+    };
+    match fsutil::repo_relpath_noexist(repo_root, &dir.join(entrypoint)) {
+        Ok(rel) => format!("./{}", rel.as_str()),
+        Err(_) => sr.dir.join(entrypoint).to_string_lossy().to_string(),
+    }
+}
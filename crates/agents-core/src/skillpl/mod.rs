@@ -1,5 +1,13 @@
+pub mod assets;
+pub mod mcp;
 pub mod planner;
+pub mod requirements;
+pub mod shims;
 pub mod types;
 
+pub use assets::*;
+pub use mcp::*;
 pub use planner::*;
+pub use requirements::*;
+pub use shims::*;
 pub use types::*;
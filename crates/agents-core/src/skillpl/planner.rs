@@ -1,9 +1,13 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::{json, Value};
 
 use crate::loadag::RepoConfig;
-use crate::model::{BackendKind, Skill};
+use crate::model::{BackendKind, OnConflict, Skill, SkillActivation};
 use crate::resolv::{EffectiveConfig, ScopeMatch};
-use crate::skillpl::{EffectiveSkills, SkillRef, SkillRequirementsSummary};
+use crate::skillpl::{policy_violations, EffectiveSkills, SkillRef, SkillRequirementsSummary};
 
 #[derive(Debug, thiserror::Error)]
 pub enum SkillPlanError {
@@ -21,16 +25,23 @@ pub enum SkillPlanError {
         backend: BackendKind,
         skill_id: String,
     },
+
+    #[error("skill {skill_id} not allowed by policy {policy_id}: {reason}")]
+    PolicyConflict {
+        skill_id: String,
+        policy_id: String,
+        reason: String,
+    },
 }
 
 #[derive(Debug)]
 pub struct SkillPlanner {
-    repo: RepoConfig,
+    repo: Arc<RepoConfig>,
 }
 
 impl SkillPlanner {
-    pub fn new(repo: RepoConfig) -> Self {
-        Self { repo }
+    pub fn new(repo: impl Into<Arc<RepoConfig>>) -> Self {
+        Self { repo: repo.into() }
     }
 
     pub fn plan(
@@ -81,7 +92,20 @@ impl SkillPlanner {
         // Convert to stable Vec.
         let skill_ids: Vec<String> = candidate.into_iter().collect();
 
+        // Requirements the policy forbids disable the skill with a warning, or fail planning
+        // when `resolution.onConflict: error`.
+        let policy = effective.policy(&self.repo);
+        let strict = self
+            .repo
+            .manifest
+            .resolution
+            .as_ref()
+            .and_then(|r| r.on_conflict)
+            == Some(OnConflict::Error);
+
         let mut enabled: Vec<SkillRef> = Vec::new();
+        let mut disabled: Vec<(String, String)> = Vec::new();
+        let mut warnings: Vec<String> = Vec::new();
         for id in &skill_ids {
             // Enforced: skill must be listed in manifest enabled set.
             if !self.repo.manifest.enabled.skills.contains(id) {
@@ -121,6 +145,26 @@ impl SkillPlanner {
                 }
             }
 
+            if let Some(policy) = policy {
+                let violations = policy_violations(&skill, policy);
+                if !violations.is_empty() {
+                    let reason = violations.join("; ");
+                    if strict {
+                        return Err(SkillPlanError::PolicyConflict {
+                            skill_id: id.clone(),
+                            policy_id: policy.id.clone(),
+                            reason,
+                        });
+                    }
+                    warnings.push(format!(
+                        "skill {id} disabled by policy {}: {reason}",
+                        policy.id
+                    ));
+                    disabled.push((id.clone(), reason));
+                    continue;
+                }
+            }
+
             enabled.push(SkillRef {
                 id: id.clone(),
                 dir,
@@ -136,8 +180,8 @@ impl SkillPlanner {
         // Already stable-sorted by BTreeSet collection.
         Ok(EffectiveSkills {
             enabled,
-            disabled: vec![],
-            warnings: vec![],
+            disabled,
+            warnings,
             requirements,
             backend: effective.backend,
             agent_id: agent_id.map(|s| s.to_string()),
@@ -158,10 +202,90 @@ fn summarize_requirements(skill: &Skill) -> SkillRequirementsSummary {
     }
 
     SkillRequirementsSummary {
-        filesystem: format!("{:?}", caps.filesystem),
-        exec: format!("{:?}", caps.exec),
-        network: format!("{:?}", caps.network),
+        filesystem: serde_name(&caps.filesystem),
+        exec: serde_name(&caps.exec),
+        network: serde_name(&caps.network),
         needs_paths,
         writes_paths,
     }
 }
+
+/// Template-facing summary of each enabled skill (`effective.skills.summaries`): what it is,
+/// what it needs, and an `invocation` sentence telling the agent how to use it.
+pub fn skill_summaries(skills: &EffectiveSkills) -> Vec<BTreeMap<String, Value>> {
+    skills
+        .enabled
+        .iter()
+        .zip(&skills.requirements)
+        .map(|(sr, req)| {
+            let skill = &sr.skill;
+            let mut m = BTreeMap::new();
+            m.insert("id".to_string(), json!(sr.id));
+            m.insert("title".to_string(), json!(skill.title));
+            m.insert("description".to_string(), json!(skill.description.trim()));
+            m.insert("tags".to_string(), json!(skill.tags));
+            m.insert(
+                "activation".to_string(),
+                json!(serde_name(&skill.activation)),
+            );
+            m.insert(
+                "interface".to_string(),
+                json!({
+                    "type": serde_name(&skill.interface.type_),
+                    "entrypoint": skill.interface.entrypoint.as_deref().unwrap_or(""),
+                    "args": skill.interface.args,
+                }),
+            );
+            m.insert(
+                "contract".to_string(),
+                json!({
+                    "inputs": skill.contract.inputs,
+                    "outputs": skill.contract.outputs,
+                }),
+            );
+            m.insert(
+                "requirements".to_string(),
+                json!({
+                    "filesystem": req.filesystem,
+                    "exec": req.exec,
+                    "network": req.network,
+                    "needsPaths": req.needs_paths,
+                    "writesPaths": req.writes_paths,
+                }),
+            );
+            m.insert("invocation".to_string(), json!(invocation(sr)));
+            m
+        })
+        .collect()
+}
+
+fn invocation(sr: &SkillRef) -> String {
+    let contract = &sr.skill.contract;
+    match sr.skill.activation {
+        SkillActivation::InstructionOnly => {
+            "no tool; apply the description when it is relevant".to_string()
+        }
+        SkillActivation::McpTool => format!("call the tools of MCP server `{}`", sr.id),
+        SkillActivation::CliShim => {
+            let mut s = format!(
+                "run `{}` (on PATH via `.agents-bin/` in sandboxed runs)",
+                sr.id
+            );
+            if !crate::skillpl::contract_is_open(&contract.inputs) {
+                s.push_str(&format!("; stdin JSON must match `{}`", contract.inputs));
+            }
+            if !crate::skillpl::contract_is_open(&contract.outputs) {
+                s.push_str(&format!("; prints JSON matching `{}`", contract.outputs));
+            }
+            s
+        }
+    }
+}
+
+/// Spelling used in the YAML/JSON schema (e.g. `cli_shim`, `read`).
+fn serde_name<T: Serialize>(v: &T) -> String {
+    serde_json::to_value(v)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
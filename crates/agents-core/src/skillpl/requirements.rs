@@ -0,0 +1,98 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::model::{Policy, RequiredLevel, Skill};
+
+/// Reasons `skill`'s requirements are not satisfied by `policy` (empty if it may run).
+///
+/// Missing capability blocks follow the runtime defaults: filesystem read and write and exec
+/// are allowed, network is off.
+pub fn policy_violations(skill: &Skill, policy: &Policy) -> Vec<String> {
+    let caps = &skill.requirements.capabilities;
+    let mut out = vec![];
+
+    let fs = policy.capabilities.filesystem.as_ref();
+    let fs_read = fs.map(|f| f.read).unwrap_or(true);
+    let fs_write = fs.map(|f| f.write).unwrap_or(true);
+    let fs_delete = fs.map(|f| f.delete).unwrap_or(false);
+    match caps.filesystem {
+        RequiredLevel::None => {}
+        RequiredLevel::Read | RequiredLevel::Restricted => {
+            if !fs_read {
+                out.push("needs filesystem read; policy disables it".to_string());
+            }
+        }
+        RequiredLevel::Write => {
+            if !fs_read || !fs_write {
+                out.push("needs filesystem write; policy disables it".to_string());
+            }
+        }
+        RequiredLevel::Full => {
+            if !fs_read || !fs_write || !fs_delete {
+                out.push("needs full filesystem access; policy limits it".to_string());
+            }
+        }
+    }
+
+    let exec = policy.capabilities.exec.as_ref();
+    let exec_enabled = exec.map(|e| e.enabled).unwrap_or(true);
+    let exec_restricted = exec.is_some_and(|e| !e.allow.is_empty());
+    match caps.exec {
+        RequiredLevel::None => {}
+        RequiredLevel::Full if exec_enabled && exec_restricted => {
+            out.push("needs unrestricted exec; policy has an exec allow list".to_string());
+        }
+        _ if !exec_enabled => out.push("needs exec; policy disables it".to_string()),
+        _ => {}
+    }
+
+    let net = policy.capabilities.network.as_ref();
+    let net_enabled = net.map(|n| n.enabled).unwrap_or(false);
+    let net_restricted = net.is_some_and(|n| !n.allow_hosts.is_empty());
+    match caps.network {
+        RequiredLevel::None => {}
+        RequiredLevel::Full if net_enabled && net_restricted => {
+            out.push("needs unrestricted network; policy has allowHosts".to_string());
+        }
+        _ if !net_enabled => out.push("needs network; policy disables it".to_string()),
+        _ => {}
+    }
+
+    if let Some(paths) = &skill.requirements.paths {
+        let deny = glob_set(&policy.paths.deny);
+        let allow = glob_set(&policy.paths.allow);
+
+        for p in &paths.needs {
+            if covers(&deny, p) {
+                out.push(format!("needs path {p}; policy denies it"));
+            }
+        }
+        for p in &paths.writes {
+            if !fs_write {
+                out.push(format!("writes path {p}; policy disables filesystem write"));
+            } else if covers(&deny, p) {
+                out.push(format!("writes path {p}; policy denies it"));
+            } else if !policy.paths.allow.is_empty() && !covers(&allow, p) {
+                out.push(format!("writes path {p}; not in policy paths.allow"));
+            }
+        }
+    }
+
+    out
+}
+
+/// Invalid globs are skipped here; they are reported when the policy is validated.
+fn glob_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pat in patterns {
+        if let Ok(glob) = Glob::new(pat) {
+            builder.add(glob);
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// A path entry (file or dir, e.g. `src/`) is covered if the set matches it or a file below it.
+fn covers(set: &GlobSet, path: &str) -> bool {
+    let p = path.trim_end_matches('/');
+    set.is_match(p) || set.is_match(format!("{p}/_"))
+}
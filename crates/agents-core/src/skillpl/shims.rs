@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use jsonschema::{Draft, JSONSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{SkillActivation, SkillInterfaceType};
+use crate::skillpl::mcp::resolve_entrypoint;
+use crate::skillpl::EffectiveSkills;
+use crate::vfsmnt::OverlayFile;
+
+/// Workspace-relative dir holding one wrapper per `cli_shim` skill; prepended to `PATH` by
+/// `agents run` on sandboxed backends.
+pub const SHIM_BIN_DIR: &str = ".agents-bin";
+
+/// Everything the wrapper needs at call time, written to `.agents-bin/.spec/<id>.json` and read
+/// by `agents shim-exec`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CliShimSpec {
+    pub skill_id: String,
+
+    /// Command to run: `./`-prefixed and relative to the workspace root for skills in the repo,
+    /// otherwise an absolute path or a command on `PATH`.
+    pub entrypoint: String,
+
+    pub args: Vec<String>,
+
+    pub env: BTreeMap<String, String>,
+
+    /// JSON schema for stdin (`contract.inputs`); `{}` means stdin is passed through unchecked.
+    pub inputs: serde_json::Value,
+
+    /// JSON schema for stdout (`contract.outputs`); `{}` means stdout is passed through unchecked.
+    pub outputs: serde_json::Value,
+
+    /// JSON Lines file each invocation is appended to (set by `shim_overlay_files`).
+    #[serde(default)]
+    pub log: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CliShimPlan {
+    pub shims: Vec<CliShimSpec>,
+
+    /// `(skill id, reason)` for `cli_shim` skills that get no wrapper.
+    pub excluded: Vec<(String, String)>,
+}
+
+/// Wrapper specs for the enabled `cli_shim` skills.
+pub fn plan_cli_shims(repo_root: &Path, skills: &EffectiveSkills) -> CliShimPlan {
+    let mut plan = CliShimPlan::default();
+
+    for sr in &skills.enabled {
+        let skill = &sr.skill;
+        if skill.activation != SkillActivation::CliShim {
+            continue;
+        }
+
+        if !matches!(
+            skill.interface.type_,
+            SkillInterfaceType::Cli | SkillInterfaceType::Script
+        ) {
+            plan.excluded.push((
+                sr.id.clone(),
+                "interface type is not cli or script".to_string(),
+            ));
+            continue;
+        }
+
+        let Some(entrypoint) = skill.interface.entrypoint.as_deref() else {
+            plan.excluded
+                .push((sr.id.clone(), "interface has no entrypoint".to_string()));
+            continue;
+        };
+
+        plan.shims.push(CliShimSpec {
+            skill_id: sr.id.clone(),
+            entrypoint: resolve_entrypoint(repo_root, sr, entrypoint),
+            args: skill.interface.args.clone(),
+            env: skill.interface.env.clone(),
+            inputs: skill.contract.inputs.clone(),
+            outputs: skill.contract.outputs.clone(),
+            log: String::new(),
+        });
+    }
+
+    plan
+}
+
+/// Wrapper scripts (executable) and their specs, relative to the workspace root. Each wrapper
+/// hands off to `<agents_exe> shim-exec`, which does the env, contract checks and logging to
+/// `log`. Both paths are as seen from inside the sandbox.
+pub fn shim_overlay_files(shims: &[CliShimSpec], agents_exe: &str, log: &str) -> Vec<OverlayFile> {
+    let mut out = vec![];
+    for shim in shims {
+        let spec_rel = format!("{SHIM_BIN_DIR}/.spec/{}.json", shim.skill_id);
+
+        // The spec is found next to the wrapper, so the workspace can live anywhere.
+        let script = format!(
+            "#!/bin/sh\n# Generated by agents for skill `{id}`; do not edit.\n\
+             exec {exe} shim-exec \"$(dirname \"$0\")/.spec/{id}.json\" -- \"$@\"\n",
+            id = shim.skill_id,
+            exe = sh_quote(agents_exe),
+        );
+        out.push(OverlayFile {
+            rel_path: format!("{SHIM_BIN_DIR}/{}", shim.skill_id),
+            bytes: script.into_bytes(),
+            executable: true,
+        });
+
+        let shim = CliShimSpec {
+            log: log.to_string(),
+            ..shim.clone()
+        };
+        let mut spec = serde_json::to_string_pretty(&shim).expect("shim spec serializes");
+        spec.push('\n');
+        out.push(OverlayFile {
+            rel_path: spec_rel,
+            bytes: spec.into_bytes(),
+            executable: false,
+        });
+    }
+    out
+}
+
+/// Wrapper scripts for sandboxes that cannot run `agents` (the container image may not be able
+/// to execute the host binary). Each wrapper sets the env, runs the entrypoint with stdin and
+/// stdout passed through, and appends the invocation to `log` itself. Contracts are not checked,
+/// so callers should only pass shims whose contracts are open (see `contract_is_open`).
+pub fn standalone_shim_files(shims: &[CliShimSpec], log: &str) -> Vec<OverlayFile> {
+    shims
+        .iter()
+        .map(|shim| {
+            let mut script = format!(
+                "#!/bin/sh\n# Generated by agents for skill `{}`; do not edit.\n",
+                shim.skill_id
+            );
+            for (k, v) in &shim.env {
+                script.push_str(&format!("export {k}={}\n", sh_quote(v)));
+            }
+
+            // `./` entrypoints are relative to the workspace root, one level above the wrapper.
+            let entrypoint = match shim.entrypoint.strip_prefix("./") {
+                Some(rel) => format!("\"$(dirname \"$0\")\"/../{}", sh_quote(rel)),
+                None => sh_quote(&shim.entrypoint),
+            };
+            let args: Vec<String> = shim.args.iter().map(|a| sh_quote(a)).collect();
+            script.push_str("started=$(date +%s)\n");
+            script.push_str(&format!("{entrypoint} {} \"$@\"\n", args.join(" ")));
+            script.push_str("code=$?\n");
+
+            // Same record as `agents shim-exec` writes, with the contracts left unchecked.
+            script.push_str(
+                r#"args=''
+for a in "$@"; do
+  a=$(printf '%s' "$a" | tr -d '\000-\037' | sed 's/\\/\\\\/g; s/"/\\"/g')
+  args="$args${args:+,}\"$a\""
+done
+"#,
+            );
+            script.push_str(&format!(
+                r#"printf '{{"ts":%s,"skill":"{id}","args":[%s],"exit_code":%s,"duration_ms":%s,"input":"unchecked","output":"unchecked"}}\n' \
+  "$started" "$args" "$code" "$(( ($(date +%s) - started) * 1000 ))" >> {log} \
+  || echo 'warning: failed to log skill invocation' >&2
+exit $code
+"#,
+                id = shim.skill_id,
+                log = sh_quote(log),
+            ));
+
+            OverlayFile {
+                rel_path: format!("{SHIM_BIN_DIR}/{}", shim.skill_id),
+                bytes: script.into_bytes(),
+                executable: true,
+            }
+        })
+        .collect()
+}
+
+/// A contract of `{}` (or `null`) accepts anything, including non-JSON data.
+pub fn contract_is_open(schema: &serde_json::Value) -> bool {
+    schema.is_null() || schema.as_object().is_some_and(|o| o.is_empty())
+}
+
+/// Validate `instance` against a contract schema; errors are `"<pointer>: <message>"`.
+pub fn check_contract(
+    schema: &serde_json::Value,
+    instance: &serde_json::Value,
+) -> Result<(), Vec<String>> {
+    let compiled = JSONSchema::options()
+        .with_draft(Draft::Draft7)
+        .compile(schema)
+        .map_err(|e| vec![format!("invalid contract schema: {e}")])?;
+
+    compiled.validate(instance).map_err(|errors| {
+        errors
+            .map(|e| {
+                let pointer = e.instance_path.to_string();
+                let pointer = if pointer.is_empty() { "/" } else { &pointer };
+                format!("{pointer}: {e}")
+            })
+            .collect()
+    })
+}
+
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
@@ -22,7 +22,9 @@ pub struct SkillRequirementsSummary {
 #[derive(Debug, Clone)]
 pub struct EffectiveSkills {
     pub enabled: Vec<SkillRef>,
-    pub disabled: Vec<String>,
+
+    /// `(skill id, reason)` for skills the effective policy does not allow.
+    pub disabled: Vec<(String, String)>,
     pub warnings: Vec<String>,
 
     pub requirements: Vec<SkillRequirementsSummary>,
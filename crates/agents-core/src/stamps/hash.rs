@@ -6,8 +6,14 @@ pub fn normalize_newlines(text: &str) -> String {
 
 pub fn compute_sha256_hex(content_without_stamp: &str) -> String {
     let normalized = normalize_newlines(content_without_stamp);
+    compute_bytes_sha256_hex(normalized.as_bytes())
+}
+
+/// sha256 of raw bytes, without newline normalization (used for unstamped outputs, which are
+/// written byte-for-byte).
+pub fn compute_bytes_sha256_hex(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(normalized.as_bytes());
+    hasher.update(bytes);
     let out = hasher.finalize();
     hex_lower(&out)
 }
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fsutil;
+use crate::model::{DriftDetection, DriftMethod};
+
+use super::{compute_bytes_sha256_hex, DriftStatus, StampMeta};
+
+/// Ownership record for generated files that cannot carry an in-file stamp (scripts, binaries
+/// and other skill assets copied byte-for-byte).
+///
+/// Lives at `.agents/state/unstamped.json`, keyed by repo-relative path. A missing or unreadable
+/// ledger is treated as empty, so unknown files are reported as unmanaged rather than overwritten.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct UnstampedLedger {
+    #[serde(default)]
+    pub files: BTreeMap<String, StampMeta>,
+}
+
+pub fn ledger_path(repo_root: &Path) -> PathBuf {
+    fsutil::agents_state_dir(repo_root).join("unstamped.json")
+}
+
+impl UnstampedLedger {
+    pub fn load(repo_root: &Path) -> Self {
+        std::fs::read(ledger_path(repo_root))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, repo_root: &Path) -> fsutil::FsResult<()> {
+        let path = ledger_path(repo_root);
+        if self.files.is_empty() && !path.exists() {
+            return Ok(());
+        }
+        let mut json = serde_json::to_string_pretty(self).unwrap_or_default();
+        json.push('\n');
+        fsutil::atomic_write(&path, json.as_bytes())
+    }
+
+    pub fn get(&self, path: &str) -> Option<&StampMeta> {
+        self.files.get(path)
+    }
+
+    pub fn record(&mut self, path: &str, meta: StampMeta) {
+        self.files.insert(path.to_string(), meta);
+    }
+
+    pub fn forget(&mut self, path: &str) -> bool {
+        self.files.remove(path).is_some()
+    }
+}
+
+/// `classify` for an unstamped output, using the ledger entry in place of the in-file stamp.
+///
+/// A file is clean when it still holds either the planned bytes or the bytes agents last wrote;
+/// anything else was edited after generation.
+pub fn classify_unstamped(
+    path: &Path,
+    planned: &[u8],
+    recorded: Option<&StampMeta>,
+    drift: &DriftDetection,
+) -> fsutil::FsResult<DriftStatus> {
+    if !path.exists() {
+        return Ok(DriftStatus::Missing);
+    }
+
+    let Some(recorded) = recorded else {
+        return Ok(DriftStatus::Unmanaged);
+    };
+
+    if drift.method.unwrap_or(DriftMethod::Sha256) == DriftMethod::None {
+        return Ok(DriftStatus::Clean);
+    }
+
+    let existing_hash = compute_bytes_sha256_hex(&fsutil::read_bytes(path)?);
+    if existing_hash == compute_bytes_sha256_hex(planned)
+        || existing_hash == recorded.content_sha256
+    {
+        Ok(DriftStatus::Clean)
+    } else {
+        Ok(DriftStatus::Drifted)
+    }
+}
@@ -2,12 +2,15 @@ mod apply;
 mod drift;
 mod encoding;
 mod hash;
+mod ledger;
 mod types;
 
 pub use apply::{
     apply_stamp, parse_stamp, stamp_rendered_output, strip_existing_stamp, StampError,
 };
 pub use drift::{classify, DriftStatus};
-pub use hash::{compute_sha256_hex, normalize_newlines};
+pub use encoding::{COMMENT_STAMP_PREFIX, FRONTMATTER_STAMP_KEY, JSON_STAMP_FIELD};
+pub use hash::{compute_bytes_sha256_hex, compute_sha256_hex, normalize_newlines};
+pub use ledger::{classify_unstamped, ledger_path, UnstampedLedger};
 
 pub use types::{Stamp, StampMeta};
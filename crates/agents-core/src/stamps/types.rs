@@ -21,6 +21,10 @@ pub struct StampMeta {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub profile: Option<String>,
 
+    /// The scope a per-scope output was resolved for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
     #[serde(rename = "contentSha256")]
     pub content_sha256: String,
 }
@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+
+use crate::fsutil;
+use crate::model::State;
+use crate::schemas::{validate_json, SchemaInvalid, SchemaKind, SchemaStore};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StateError {
+    #[error("fs error: {0}")]
+    Fs(#[from] fsutil::FsError),
+
+    #[error("state parse error: {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("state serialize error: {0}")]
+    Serialize(String),
+
+    #[error("state schema invalid: {} ({})", .0.path.display(), .0.message)]
+    Schema(SchemaInvalid),
+}
+
+pub fn state_path(repo_root: &Path) -> PathBuf {
+    fsutil::agents_state_dir(repo_root).join("state.yaml")
+}
+
+pub fn read_state(repo_root: &Path) -> Result<Option<State>, StateError> {
+    let path = state_path(repo_root);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let text = fsutil::read_to_string(&path)?;
+    let state: State = serde_yaml::from_str(&text).map_err(|e| StateError::Parse {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+
+    Ok(Some(state))
+}
+
+/// Validate `state` against `state.schema.json` (when schemas are present) and write it
+/// atomically to `.agents/state/state.yaml`.
+pub fn write_state(repo_root: &Path, state: &State) -> Result<PathBuf, StateError> {
+    let path = state_path(repo_root);
+
+    let json = serde_json::to_value(state).map_err(|e| StateError::Serialize(e.to_string()))?;
+    if repo_root
+        .join(".agents/schemas")
+        .join(SchemaKind::State.schema_file_name())
+        .is_file()
+    {
+        let mut store = SchemaStore::load(repo_root).map_err(StateError::Schema)?;
+        validate_json(&mut store, SchemaKind::State, &path, &json).map_err(StateError::Schema)?;
+    }
+
+    let yaml = serde_yaml::to_string(state).map_err(|e| StateError::Serialize(e.to_string()))?;
+    fsutil::atomic_write(&path, yaml.as_bytes())?;
+
+    Ok(path)
+}
+
+/// Remove `.agents/state/state.yaml`. Returns whether a file was removed.
+pub fn clear_state(repo_root: &Path) -> Result<bool, StateError> {
+    let path = state_path(repo_root);
+    if !path.is_file() {
+        return Ok(false);
+    }
+
+    std::fs::remove_file(&path).map_err(|e| fsutil::FsError::Io {
+        path: path.clone(),
+        source: e,
+    })?;
+
+    Ok(true)
+}
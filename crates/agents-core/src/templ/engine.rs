@@ -31,10 +31,24 @@ impl TemplateEngine {
         let mut hb = Handlebars::new();
         hb.set_strict_mode(true);
         register_helpers(&mut hb);
+        for (name, partial) in crate::shared::builtin_partials() {
+            hb.register_partial(name, partial)
+                .expect("built-in partials are valid templates");
+        }
 
         Self { hb }
     }
 
+    /// Engine with every template under `templates_dir` registered, compiled once and shared by
+    /// all outputs that use the same adapter templates.
+    pub fn for_template_dir(templates_dir: Option<&Path>) -> Result<Self, TemplateError> {
+        let mut engine = Self::new();
+        if let Some(dir) = templates_dir {
+            engine.register_partials_from_dir(dir)?;
+        }
+        Ok(engine)
+    }
+
     pub fn register_partials_from_dir(
         &mut self,
         templates_dir: &Path,
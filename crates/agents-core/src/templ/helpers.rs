@@ -10,6 +10,7 @@ pub fn register_helpers(hb: &mut Handlebars<'_>) {
     hb.register_helper("toYaml", Box::new(ToYamlHelper));
     hb.register_helper("frontmatter", Box::new(FrontmatterHelper));
     hb.register_helper("generatedStamp", Box::new(GeneratedStampHelper));
+    hb.register_helper("mcpServers", Box::new(McpServersHelper));
 }
 
 struct IndentHelper;
@@ -177,7 +178,63 @@ impl HelperDef for GeneratedStampHelper {
     }
 }
 
-fn normalize_json_value(v: &serde_json::Value) -> serde_json::Value {
+/// `{{mcpServers effective.mcp.servers}}`: the servers as a JSON object in the
+/// `{command, args, env}` shape most agents read. `format="opencode"` writes opencode's
+/// `{type, command, environment, enabled}` shape instead; `indent=<n>` pads every line after the
+/// first so the object can sit inside a larger document.
+struct McpServersHelper;
+
+impl HelperDef for McpServersHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let servers = h.param(0).map(|p| p.value()).cloned().unwrap_or_default();
+        let opencode = h.hash_get("format").and_then(|v| v.value().as_str()) == Some("opencode");
+        let indent = h
+            .hash_get("indent")
+            .and_then(|v| v.value().as_u64())
+            .unwrap_or(0) as usize;
+
+        let mut map = serde_json::Map::new();
+        for (name, server) in servers.as_object().into_iter().flatten() {
+            let field = |k: &str| server.get(k).cloned().unwrap_or_default();
+            let env = match field("env") {
+                serde_json::Value::Null => serde_json::json!({}),
+                env => env,
+            };
+            let entry = if opencode {
+                let mut command = vec![field("command")];
+                command.extend(field("args").as_array().cloned().unwrap_or_default());
+                serde_json::json!({
+                    "type": "local",
+                    "command": command,
+                    "environment": env,
+                    "enabled": true,
+                })
+            } else {
+                serde_json::json!({
+                    "command": field("command"),
+                    "args": field("args"),
+                    "env": env,
+                })
+            };
+            map.insert(name.clone(), entry);
+        }
+
+        let json = serde_json::to_string_pretty(&normalize_json_value(&map.into()))
+            .unwrap_or_else(|_| "{}".to_string());
+        let pad = " ".repeat(indent);
+        out.write(&json.replace('\n', &format!("\n{pad}")))?;
+        Ok(())
+    }
+}
+
+pub(crate) fn normalize_json_value(v: &serde_json::Value) -> serde_json::Value {
     match v {
         serde_json::Value::Object(map) => {
             let mut b: BTreeMap<String, serde_json::Value> = BTreeMap::new();
@@ -4,6 +4,7 @@ use serde::Serialize;
 
 use crate::model::{BackendKind, ModeFrontmatter, Policy};
 use crate::prompts::EffectivePrompts;
+use crate::skillpl::McpServer;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ScopeCtx {
@@ -11,6 +12,10 @@ pub struct ScopeCtx {
 
     #[serde(rename = "applyTo")]
     pub apply_to: Vec<String>,
+
+    /// `Scope::dir`, the directory `{{scopeDir}}` expands to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -29,6 +34,12 @@ pub struct EffectiveSkillsCtx {
     pub summaries: Vec<BTreeMap<String, serde_json::Value>>,
 }
 
+/// MCP servers generated from enabled `mcp_tool` skills, after policy filtering.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct McpCtx {
+    pub servers: BTreeMap<String, McpServer>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct GenerationStampCtx {
     pub generator: String,
@@ -48,6 +59,7 @@ pub struct EffectiveCtx {
     pub mode: EffectiveModeCtx,
     pub policy: Policy,
     pub skills: EffectiveSkillsCtx,
+    pub mcp: McpCtx,
     pub prompts: EffectivePrompts,
 }
 
@@ -70,6 +82,10 @@ pub struct RenderContext {
 
     pub adapter: AdapterCtx,
 
+    /// Template variables from the active profile.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, serde_json::Value>,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub x: Option<serde_json::Value>,
 }
@@ -86,6 +86,23 @@ impl DockerRuntime {
 
         Ok(status)
     }
+
+    pub fn spawn(&self, args: &[String]) -> Result<std::process::Child, DockerError> {
+        let child = Command::new(&self.docker_bin)
+            .args(args)
+            .stdin(std::process::Stdio::inherit())
+            .stdout(std::process::Stdio::inherit())
+            .stderr(std::process::Stdio::inherit())
+            .spawn()?;
+
+        Ok(child)
+    }
+
+    /// Best-effort `docker kill <name>`.
+    pub fn kill(&self, name: &str) -> Result<(), DockerError> {
+        self.run(&["kill".to_string(), name.to_string()])?;
+        Ok(())
+    }
 }
 
 impl Default for DockerRuntime {
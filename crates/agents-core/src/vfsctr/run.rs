@@ -10,6 +10,10 @@ use crate::vfsctr::docker::{DockerError, DockerRuntime};
 /// - Generated outputs are mounted read-only at `/__agents_out`.
 /// - Container creates a writable `/workspace` by copying the repo contents.
 /// - Container then overlays generated outputs into `/workspace`.
+/// - Mounted skill assets are bind-mounted read-only at `/workspace/<path>` and skipped by the
+///   repo copy.
+/// - With skill shims, `/workspace/.agents-bin` is prepended to `PATH` and the shim log dir is
+///   mounted writable at `/__agents_log`.
 /// - Agent command is executed with CWD `/workspace`.
 #[derive(Debug, Clone)]
 pub struct VfsContainerInvocation {
@@ -25,6 +29,23 @@ pub struct VfsContainerInvocation {
 
     /// Best-effort: make `/workspace` read-only inside the container (chmod -R a-w).
     pub deny_writes: bool,
+
+    /// Host directory bind-mounted as `/workspace` so the result can be inspected after the
+    /// container exits. When unset, `/workspace` only exists inside the container.
+    pub workspace_dir: Option<PathBuf>,
+
+    /// Container name (`--name`); needed to stop the container from the host.
+    pub name: Option<String>,
+
+    /// `(host file, workspace-relative path)` pairs bind-mounted read-only into `/workspace`.
+    pub ro_mounts: Vec<(PathBuf, String)>,
+
+    /// Host dir for skill shim invocation logs; set when the outputs include shims.
+    pub shim_log_dir: Option<PathBuf>,
+
+    /// `uid:gid` to run the container as (`--user`), so files written into a bind-mounted
+    /// `workspace_dir` are owned by the invoking user rather than root.
+    pub user: Option<String>,
 }
 
 impl VfsContainerInvocation {
@@ -48,6 +69,45 @@ impl VfsContainerInvocation {
             ),
         ];
 
+        if let Some(dir) = &self.workspace_dir {
+            args.push("--mount".to_string());
+            args.push(format!(
+                "type=bind,source={},target=/workspace",
+                dir.display()
+            ));
+        }
+
+        for (source, target) in &self.ro_mounts {
+            args.push("--mount".to_string());
+            args.push(format!(
+                "type=bind,source={},target=/workspace/{target},readonly",
+                source.display()
+            ));
+        }
+
+        if let Some(dir) = &self.shim_log_dir {
+            args.push("--mount".to_string());
+            args.push(format!(
+                "type=bind,source={},target={SHIM_LOG_DIR}",
+                dir.display()
+            ));
+        }
+
+        if let Some(name) = &self.name {
+            args.push("--name".to_string());
+            args.push(name.clone());
+        }
+
+        if let Some(user) = &self.user {
+            args.push("--user".to_string());
+            args.push(user.clone());
+            // An arbitrary uid has no passwd entry, so give it a writable home.
+            if !self.env.contains_key("HOME") {
+                args.push("-e".to_string());
+                args.push("HOME=/tmp".to_string());
+            }
+        }
+
         if self.deny_network {
             args.push("--network".to_string());
             args.push("none".to_string());
@@ -63,7 +123,13 @@ impl VfsContainerInvocation {
         // Use /bin/sh for maximum compatibility.
         args.push("sh".to_string());
         args.push("-c".to_string());
-        args.push(entry_script(self.verbose, self.deny_writes));
+        let mounted: Vec<&str> = self.ro_mounts.iter().map(|(_, t)| t.as_str()).collect();
+        args.push(entry_script(
+            self.verbose,
+            self.deny_writes,
+            &mounted,
+            self.shim_log_dir.is_some(),
+        ));
         args.push("--".to_string());
 
         args.extend(self.cmd.clone());
@@ -88,13 +154,25 @@ impl VfsContainerInvocation {
         let args = self.docker_args();
         docker.status(&args)
     }
+
+    /// Start the container with inherited stdio and return without waiting.
+    pub fn spawn(&self, docker: &DockerRuntime) -> Result<std::process::Child, DockerError> {
+        docker.check_available()?;
+        docker.check_daemon()?;
+
+        let args = self.docker_args();
+        docker.spawn(&args)
+    }
 }
 
+/// Where `shim_log_dir` is mounted inside the container.
+pub const SHIM_LOG_DIR: &str = "/__agents_log";
+
 pub fn default_image() -> String {
     std::env::var("AGENTS_VFSCTR_IMAGE").unwrap_or_else(|_| "alpine:3.19".to_string())
 }
 
-fn entry_script(verbose: bool, deny_writes: bool) -> String {
+fn entry_script(verbose: bool, deny_writes: bool, mounted: &[&str], shims: bool) -> String {
     // Use tar to preserve file modes and create nested directories.
     // Avoid bashisms: run under /bin/sh.
     let mut s = String::new();
@@ -105,7 +183,14 @@ fn entry_script(verbose: bool, deny_writes: bool) -> String {
         s.push_str("echo 'agents vfs_container: copying repo to /workspace' 1>&2\n");
     }
     s.push_str("cd /__agents_repo\n");
-    s.push_str("tar cf - . | (cd /workspace && tar xpf -)\n");
+    // Read-only mounts already occupy their paths; copying over them would fail.
+    let excludes: String = mounted
+        .iter()
+        .map(|p| format!("--exclude='./{}' ", p.replace('\'', "'\\''")))
+        .collect();
+    s.push_str(&format!(
+        "tar cf - {excludes}. | (cd /workspace && tar xpf -)\n"
+    ));
 
     if verbose {
         s.push_str("echo 'agents vfs_container: overlaying generated outputs' 1>&2\n");
@@ -125,12 +210,32 @@ fn entry_script(verbose: bool, deny_writes: bool) -> String {
         s.push_str("echo 'agents vfs_container: exec' 1>&2\n");
     }
 
+    if shims {
+        s.push_str("export PATH=\"/workspace/.agents-bin:$PATH\"\n");
+    }
+
     // $@ is the agent command.
     s.push_str("cd /workspace\n");
     s.push_str("exec \"$@\"\n");
     s
 }
 
+/// `uid:gid` of the owner of `dir`; pass a directory the current process created to get the
+/// invoking user. `None` on platforms without unix ownership.
+pub fn owner_of(dir: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = std::fs::metadata(dir).ok()?;
+        Some(format!("{}:{}", meta.uid(), meta.gid()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = dir;
+        None
+    }
+}
+
 pub fn normalize_repo_root(p: &Path) -> PathBuf {
     // Keep as-is for v1; caller is responsible for providing a valid repo root.
     p.to_path_buf()
@@ -13,6 +13,9 @@ const STALE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
 pub struct OverlayFile {
     pub rel_path: String,
     pub bytes: Vec<u8>,
+
+    /// Mark the file executable (e.g. skill shim wrappers); ignored on non-unix hosts.
+    pub executable: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -221,6 +224,36 @@ fn overlay_outputs(dest_root: &Path, outputs: &[OverlayFile]) -> Result<(), VfsM
             })?;
         }
         fsutil::atomic_write(&dest, &out.bytes)?;
+        if out.executable {
+            set_executable(&dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `chmod +x`, shared with callers that write overlay files themselves (e.g. a container's
+/// outputs dir).
+pub fn set_executable(path: &Path) -> Result<(), VfsMountError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(path)
+            .map_err(|e| VfsMountError::Io {
+                path: path.to_path_buf(),
+                source: e,
+            })?
+            .permissions();
+        perms.set_mode(perms.mode() | 0o111);
+        std::fs::set_permissions(path, perms).map_err(|e| VfsMountError::Io {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
     }
 
     Ok(())
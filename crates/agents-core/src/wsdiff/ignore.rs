@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobMatcher};
+
+/// The repo's ignore rules: the root `.gitignore` and `.git/info/exclude`.
+///
+/// Supports the common gitignore syntax (comments, `!` negation, trailing `/` for directories,
+/// patterns anchored by a `/`, `*`/`**` globs); nested `.gitignore` files are not read. `.git/`
+/// is always ignored.
+#[derive(Debug, Clone, Default)]
+pub struct RepoIgnore {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    matcher: GlobMatcher,
+    negate: bool,
+    dir_only: bool,
+}
+
+impl RepoIgnore {
+    pub fn load(repo_root: &Path) -> Self {
+        let mut ignore = Self::default();
+        for file in [".gitignore", ".git/info/exclude"] {
+            if let Ok(text) = std::fs::read_to_string(repo_root.join(file)) {
+                ignore.add_lines(&text);
+            }
+        }
+        ignore
+    }
+
+    fn add_lines(&mut self, text: &str) {
+        for line in text.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negate, pat) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, pat) = match pat.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, pat),
+            };
+
+            // A pattern with a `/` is relative to the repo root; otherwise it matches at any depth.
+            let glob = match pat.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if pat.contains('/') => pat.to_string(),
+                None => format!("**/{pat}"),
+            };
+
+            let Ok(glob) = GlobBuilder::new(&glob).literal_separator(true).build() else {
+                continue;
+            };
+            self.rules.push(Rule {
+                matcher: glob.compile_matcher(),
+                negate,
+                dir_only,
+            });
+        }
+    }
+
+    /// Whether the repo-relative `rel` (with `/` separators) is ignored. The last matching rule
+    /// wins, as in git.
+    pub fn is_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        if rel == ".git" || rel.ends_with("/.git") {
+            return true;
+        }
+
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if rule.matcher.is_match(rel) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
@@ -0,0 +1,84 @@
+use crate::model::Limits;
+
+use super::WorkspaceDiff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    MaxFilesChanged,
+    MaxPatchLines,
+    MaxCommandRuntimeSec,
+}
+
+impl LimitKind {
+    /// Policy field name, as written in policy YAML.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LimitKind::MaxFilesChanged => "maxFilesChanged",
+            LimitKind::MaxPatchLines => "maxPatchLines",
+            LimitKind::MaxCommandRuntimeSec => "maxCommandRuntimeSec",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitViolation {
+    pub limit: LimitKind,
+    pub actual: u64,
+    pub max: u64,
+}
+
+impl std::fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} exceeded: {} > {}",
+            self.limit.as_str(),
+            self.actual,
+            self.max
+        )
+    }
+}
+
+/// Limits that only make sense after the fact, i.e. everything except the runtime limit.
+pub fn has_diff_limits(limits: &Limits) -> bool {
+    limit_value(limits.max_files_changed).is_some() || limit_value(limits.max_patch_lines).is_some()
+}
+
+/// `maxCommandRuntimeSec` as a duration, if set.
+pub fn runtime_limit(limits: &Limits) -> Option<std::time::Duration> {
+    limit_value(limits.max_command_runtime_sec).map(std::time::Duration::from_secs)
+}
+
+/// Check file-count and patch-size limits against a workspace diff.
+pub fn check_diff_limits(limits: &Limits, diff: &WorkspaceDiff) -> Vec<LimitViolation> {
+    let mut out = vec![];
+
+    let checks = [
+        (
+            LimitKind::MaxFilesChanged,
+            limits.max_files_changed,
+            diff.files_changed(),
+        ),
+        (
+            LimitKind::MaxPatchLines,
+            limits.max_patch_lines,
+            diff.patch_lines(),
+        ),
+    ];
+    for (limit, max, actual) in checks {
+        let Some(max) = limit_value(max) else {
+            continue;
+        };
+        let actual = actual as u64;
+        if actual > max {
+            out.push(LimitViolation { limit, actual, max });
+        }
+    }
+
+    out
+}
+
+/// Negative values are treated as "no limit".
+fn limit_value(v: Option<i64>) -> Option<u64> {
+    v.and_then(|v| u64::try_from(v).ok())
+}
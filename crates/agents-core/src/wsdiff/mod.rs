@@ -0,0 +1,247 @@
+//! Diff a vfs workspace (vfs_mount copy or vfs_container bind dir) against the repo it was
+//! created from.
+
+pub mod ignore;
+pub mod limits;
+pub mod patch;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path};
+
+use similar::{ChangeTag, TextDiff};
+use walkdir::WalkDir;
+
+use crate::fsutil;
+use crate::stamps::compute_bytes_sha256_hex;
+
+pub use ignore::RepoIgnore;
+pub use limits::*;
+pub use patch::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WsDiffError {
+    #[error("fs error: {0}")]
+    Fs(#[from] fsutil::FsError),
+
+    #[error("walkdir error: {0}")]
+    Walkdir(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceChange {
+    /// Repo-relative path with `/` separators.
+    pub path: String,
+    pub kind: ChangeKind,
+
+    pub added_lines: usize,
+    pub removed_lines: usize,
+
+    /// Either side is not valid UTF-8; line counts are zero.
+    pub binary: bool,
+
+    /// The repo file also changed since the run's baseline was captured; applying the change
+    /// would overwrite that edit.
+    pub conflict: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceDiff {
+    /// Sorted by path.
+    pub changes: Vec<WorkspaceChange>,
+}
+
+impl WorkspaceDiff {
+    pub fn files_changed(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn patch_lines(&self) -> usize {
+        self.changes
+            .iter()
+            .map(|c| c.added_lines + c.removed_lines)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceDiffOptions {
+    /// Repo-relative paths to ignore (e.g. generated outputs overlaid into the workspace).
+    pub exclude: BTreeSet<String>,
+
+    /// The repo as it was when the workspace was created. With it, only files the agent changed
+    /// are reported (not edits made to the repo meanwhile), and those also edited in the repo
+    /// are flagged as conflicts.
+    pub baseline: Option<WorkspaceBaseline>,
+}
+
+/// sha256 of every repo file at run start, keyed by repo-relative path.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceBaseline {
+    files: BTreeMap<String, String>,
+}
+
+impl WorkspaceBaseline {
+    /// Hash the files `diff_workspace` would compare (same ignore rules and excludes).
+    pub fn capture(repo_root: &Path, exclude: &BTreeSet<String>) -> Result<Self, WsDiffError> {
+        let ignore = RepoIgnore::load(repo_root);
+        let mut files = BTreeMap::new();
+        for path in list_files(repo_root, exclude, &ignore)? {
+            let hash = compute_bytes_sha256_hex(&fsutil::read_bytes(&repo_root.join(&path))?);
+            files.insert(path, hash);
+        }
+        Ok(Self { files })
+    }
+
+    /// Whether `path` in `repo_root` still matches the baseline (both absent counts as a match).
+    pub fn matches(&self, repo_root: &Path, path: &str) -> Result<bool, WsDiffError> {
+        let abs = repo_root.join(path);
+        let current = if abs.is_file() {
+            Some(compute_bytes_sha256_hex(&fsutil::read_bytes(&abs)?))
+        } else {
+            None
+        };
+        Ok(current.as_ref() == self.files.get(path))
+    }
+
+    fn matches_bytes(&self, path: &str, bytes: Option<&[u8]>) -> bool {
+        bytes.map(compute_bytes_sha256_hex).as_ref() == self.files.get(path)
+    }
+}
+
+/// Compare every regular file under `workspace_root` with `repo_root`.
+///
+/// `.git/` and paths ignored by the repo's ignore rules (see `RepoIgnore`) are skipped on both
+/// sides.
+pub fn diff_workspace(
+    repo_root: &Path,
+    workspace_root: &Path,
+    opts: &WorkspaceDiffOptions,
+) -> Result<WorkspaceDiff, WsDiffError> {
+    let ignore = RepoIgnore::load(repo_root);
+    let before = list_files(repo_root, &opts.exclude, &ignore)?;
+    let after = list_files(workspace_root, &opts.exclude, &ignore)?;
+
+    let mut changes = vec![];
+    for path in before.union(&after) {
+        let old = before
+            .contains(path)
+            .then(|| fsutil::read_bytes(&repo_root.join(path)))
+            .transpose()?;
+        let new = after
+            .contains(path)
+            .then(|| fsutil::read_bytes(&workspace_root.join(path)))
+            .transpose()?;
+
+        let kind = match (&old, &new) {
+            (None, Some(_)) => ChangeKind::Added,
+            (Some(_), None) => ChangeKind::Deleted,
+            (Some(a), Some(b)) if a != b => ChangeKind::Modified,
+            _ => continue,
+        };
+
+        let conflict = match &opts.baseline {
+            // The agent left the file as it was at run start; only the repo moved on.
+            Some(b) if b.matches_bytes(path, new.as_deref()) => continue,
+            Some(b) => !b.matches_bytes(path, old.as_deref()),
+            None => false,
+        };
+
+        let old_text = old.as_deref().map(std::str::from_utf8).transpose();
+        let new_text = new.as_deref().map(std::str::from_utf8).transpose();
+        let (added_lines, removed_lines, binary) = match (old_text, new_text) {
+            (Ok(a), Ok(b)) => {
+                let (added, removed) = count_lines(a.unwrap_or(""), b.unwrap_or(""));
+                (added, removed, false)
+            }
+            _ => (0, 0, true),
+        };
+
+        changes.push(WorkspaceChange {
+            path: path.clone(),
+            kind,
+            added_lines,
+            removed_lines,
+            binary,
+            conflict,
+        });
+    }
+
+    Ok(WorkspaceDiff { changes })
+}
+
+fn count_lines(old: &str, new: &str) -> (usize, usize) {
+    let old = old.replace("\r\n", "\n");
+    let new = new.replace("\r\n", "\n");
+
+    let mut added = 0;
+    let mut removed = 0;
+    for change in TextDiff::from_lines(&old, &new).iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    (added, removed)
+}
+
+fn list_files(
+    root: &Path,
+    exclude: &BTreeSet<String>,
+    ignore: &RepoIgnore,
+) -> Result<BTreeSet<String>, WsDiffError> {
+    let mut out = BTreeSet::new();
+
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0 || !ignore.is_ignored(&rel_path(root, e.path()), e.file_type().is_dir())
+        })
+    {
+        let entry = entry.map_err(|e| WsDiffError::Walkdir(e.to_string()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let rel_s = rel_path(root, entry.path());
+        if exclude.contains(&rel_s) {
+            continue;
+        }
+        out.insert(rel_s);
+    }
+
+    Ok(out)
+}
+
+fn rel_path(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
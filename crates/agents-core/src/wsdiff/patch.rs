@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use similar::TextDiff;
+
+use crate::fsutil;
+
+use super::{ChangeKind, WorkspaceBaseline, WorkspaceDiff, WsDiffError};
+
+/// Render `diff` as a git-style unified patch (`a/` and `b/` prefixes), applicable with
+/// `git apply` from the repo root. Binary changes are listed but carry no content.
+pub fn render_patch(
+    repo_root: &Path,
+    workspace_root: &Path,
+    diff: &WorkspaceDiff,
+) -> Result<String, WsDiffError> {
+    let mut out = String::new();
+
+    for change in &diff.changes {
+        let path = change.path.as_str();
+        out.push_str(&format!("diff --git a/{path} b/{path}\n"));
+        match change.kind {
+            ChangeKind::Added => out.push_str("new file mode 100644\n"),
+            ChangeKind::Deleted => out.push_str("deleted file mode 100644\n"),
+            ChangeKind::Modified => {}
+        }
+
+        if change.binary {
+            out.push_str(&format!("Binary files a/{path} and b/{path} differ\n"));
+            continue;
+        }
+
+        let old = match change.kind {
+            ChangeKind::Added => String::new(),
+            _ => fsutil::read_to_string(&repo_root.join(path))?,
+        };
+        let new = match change.kind {
+            ChangeKind::Deleted => String::new(),
+            _ => fsutil::read_to_string(&workspace_root.join(path))?,
+        };
+        let old_name = match change.kind {
+            ChangeKind::Added => "/dev/null".to_string(),
+            _ => format!("a/{path}"),
+        };
+        let new_name = match change.kind {
+            ChangeKind::Deleted => "/dev/null".to_string(),
+            _ => format!("b/{path}"),
+        };
+
+        let old = old.replace("\r\n", "\n");
+        let new = new.replace("\r\n", "\n");
+        let text = TextDiff::from_lines(&old, &new)
+            .unified_diff()
+            .context_radius(3)
+            .header(&old_name, &new_name)
+            .to_string();
+        out.push_str(&text);
+    }
+
+    Ok(out)
+}
+
+/// Copy every change in `diff` from the workspace back into the repo: added and modified files
+/// are written, deleted files are removed.
+///
+/// With a `baseline`, a change is skipped when the repo file no longer matches it (edited since
+/// the run started); the skipped paths are returned.
+pub fn apply_workspace_changes(
+    repo_root: &Path,
+    workspace_root: &Path,
+    diff: &WorkspaceDiff,
+    baseline: Option<&WorkspaceBaseline>,
+) -> Result<Vec<String>, WsDiffError> {
+    let mut conflicts = vec![];
+
+    for change in &diff.changes {
+        let edited = match baseline {
+            Some(b) => !b.matches(repo_root, &change.path)?,
+            None => false,
+        };
+        if change.conflict || edited {
+            conflicts.push(change.path.clone());
+            continue;
+        }
+
+        let dest = repo_root.join(&change.path);
+        match change.kind {
+            ChangeKind::Added | ChangeKind::Modified => {
+                let bytes = fsutil::read_bytes(&workspace_root.join(&change.path))?;
+                fsutil::atomic_write(&dest, &bytes)?;
+            }
+            ChangeKind::Deleted => {
+                std::fs::remove_file(&dest).map_err(|e| {
+                    WsDiffError::Fs(fsutil::FsError::Io {
+                        path: dest.clone(),
+                        source: e,
+                    })
+                })?;
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
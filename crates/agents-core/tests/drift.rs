@@ -13,6 +13,7 @@ fn meta_for(content_without_stamp: &str) -> StampMeta {
         policy: "safe".to_string(),
         backend: BackendKind::VfsContainer,
         profile: None,
+        scope: None,
         content_sha256: compute_sha256_hex(content_without_stamp),
     }
 }
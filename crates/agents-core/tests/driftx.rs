@@ -68,6 +68,7 @@ fn stamped_comment_for(content_without_stamp: &str) -> String {
         policy: "safe".to_string(),
         backend: agents_core::model::manifest::BackendKind::VfsContainer,
         profile: None,
+        scope: None,
         content_sha256: compute_sha256_hex(content_without_stamp),
     };
 
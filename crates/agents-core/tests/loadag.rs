@@ -93,3 +93,168 @@ fn deterministic_ordering_of_policies_map() {
     let keys: Vec<_> = cfg.policies.keys().cloned().collect();
     assert_eq!(keys, vec!["p1".to_string(), "p2".to_string()]);
 }
+
+fn policy_repo(repo: &std::path::Path, resolution: &str) {
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        &format!(
+            "specVersion: '0.1'\ndefaults: {{ mode: default, policy: base }}\n\
+             enabled: {{ modes: [default], policies: [base], skills: [], adapters: [] }}\n{resolution}"
+        ),
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/base.yaml"),
+        "id: base\ndescription: base\n\
+         capabilities: { exec: { enabled: true, deny: [rm, curl] }, network: { enabled: true } }\n\
+         paths: { deny: ['secrets/**'], redact: ['.env'] }\n\
+         confirmations: { requiredFor: [push] }\nlimits: { maxFilesChanged: 200 }\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/ci.yaml"),
+        "id: ci\nextends: base\n\
+         capabilities: { exec: { allow: [curl] }, network: { enabled: false } }\n\
+         paths: { redact: ['*.pem'] }\nconfirmations: { requiredFor: [delete] }\n",
+    );
+}
+
+#[test]
+fn policy_extends_merges_parent() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    policy_repo(repo, "");
+
+    let (cfg, _report) = load_repo_config(repo, &LoaderOptions::default()).unwrap();
+    let ci = &cfg.policies["ci"];
+
+    assert_eq!(ci.id, "ci");
+    assert_eq!(ci.extends, vec!["base".to_string()]);
+    assert_eq!(ci.description, "base");
+
+    let exec = ci.capabilities.exec.as_ref().unwrap();
+    assert!(exec.enabled);
+    assert_eq!(exec.allow, vec!["curl".to_string()]);
+    // The child's allow wins over the inherited deny.
+    assert_eq!(exec.deny, vec!["rm".to_string()]);
+    assert!(!ci.capabilities.network.as_ref().unwrap().enabled);
+
+    assert_eq!(ci.paths.deny, vec!["secrets/**".to_string()]);
+    assert_eq!(
+        ci.paths.redact,
+        vec![".env".to_string(), "*.pem".to_string()]
+    );
+    assert_eq!(ci.confirmations.required_for.len(), 2);
+    assert_eq!(ci.limits.as_ref().unwrap().max_files_changed, Some(200));
+
+    assert_eq!(cfg.policy_chain("ci"), vec!["base".to_string()]);
+    assert!(cfg.policy_chain("base").is_empty());
+}
+
+#[test]
+fn policy_extends_honors_deny_overrides_allow() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    policy_repo(repo, "resolution: { denyOverridesAllow: true }\n");
+
+    let (cfg, _report) = load_repo_config(repo, &LoaderOptions::default()).unwrap();
+    let exec = cfg.policies["ci"].capabilities.exec.clone().unwrap();
+
+    assert!(exec.allow.is_empty());
+    assert_eq!(exec.deny, vec!["rm".to_string(), "curl".to_string()]);
+}
+
+#[test]
+fn policy_extends_rejects_cycles_and_missing_parents() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    policy_repo(repo, "");
+
+    write_file(
+        &repo.join(".agents/policies/a.yaml"),
+        "id: a\nextends: [b]\n",
+    );
+    write_file(&repo.join(".agents/policies/b.yaml"), "id: b\nextends: a\n");
+    let err = load_repo_config(repo, &LoaderOptions::default()).unwrap_err();
+    assert_eq!(err.to_string(), "extends cycle in policies: a -> b -> a");
+
+    write_file(
+        &repo.join(".agents/policies/b.yaml"),
+        "id: b\nextends: nope\n",
+    );
+    let err = load_repo_config(repo, &LoaderOptions::default()).unwrap_err();
+    assert_eq!(err.to_string(), "missing required id nope in policies");
+}
+
+#[test]
+fn mode_extends_merges_frontmatter_and_composes_body() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    policy_repo(repo, "");
+
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\ntitle: Default\npolicy: base\nincludeSnippets: [a]\n\
+         toolIntent: { deny: [network] }\n---\n\nShared rules.\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/strict.md"),
+        "---\nid: strict\nextends: [default]\nincludeSnippets: [b]\ntoolIntent: { deny: [exec] }\n---\n\nStrict rules.\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/wrapped.md"),
+        "---\nid: wrapped\nextends: strict\ntitle: Wrapped\n---\n\nIntro.\n\n{{> parent}}\n\nOutro.\n",
+    );
+
+    let (cfg, _report) = load_repo_config(repo, &LoaderOptions::default()).unwrap();
+
+    let strict = &cfg.modes["strict"];
+    let fm = strict.frontmatter.as_ref().unwrap();
+    assert_eq!(fm.id.as_deref(), Some("strict"));
+    assert_eq!(fm.title.as_deref(), Some("Default"));
+    assert_eq!(fm.policy.as_deref(), Some("base"));
+    assert_eq!(fm.include_snippets, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(
+        fm.tool_intent.as_ref().unwrap().deny,
+        vec!["network".to_string(), "exec".to_string()]
+    );
+    assert_eq!(strict.body, "Shared rules.\n\nStrict rules.\n");
+
+    let wrapped = &cfg.modes["wrapped"];
+    assert_eq!(
+        wrapped.frontmatter.as_ref().unwrap().title.as_deref(),
+        Some("Wrapped")
+    );
+    assert_eq!(
+        wrapped.body,
+        "Intro.\n\nShared rules.\n\nStrict rules.\n\nOutro.\n"
+    );
+    let names: Vec<String> = wrapped
+        .sources
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["default.md", "strict.md", "wrapped.md"]);
+}
+
+#[test]
+fn mode_extends_rejects_cycles() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    policy_repo(repo, "");
+
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\nextends: default\n---\n\n",
+    );
+
+    let err = load_repo_config(repo, &LoaderOptions::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "extends cycle in modes: default -> default"
+    );
+}
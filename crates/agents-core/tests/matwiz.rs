@@ -1,7 +1,9 @@
 use std::fs;
 
 use agents_core::fsutil;
-use agents_core::matwiz::{Backend, ConflictReason, MaterializeBackend, RenderedOutput};
+use agents_core::matwiz::{
+    apply_transaction, ApplyBatch, Backend, ConflictReason, MaterializeBackend, RenderedOutput,
+};
 use agents_core::model::{
     CollisionPolicy, DriftDetection, DriftMethod, JsonMergeStrategy, OutputFormat, OutputRenderer,
     RendererType, StampMethod, WriteMode, WritePolicy,
@@ -29,6 +31,7 @@ fn dummy_render_context() -> RenderContext {
             },
             policy: agents_core::model::Policy {
                 id: "safe".to_string(),
+                extends: vec![],
                 description: "".to_string(),
                 capabilities: agents_core::model::Capabilities {
                     filesystem: None,
@@ -51,6 +54,7 @@ fn dummy_render_context() -> RenderContext {
                 ids: vec![],
                 summaries: vec![],
             },
+            mcp: Default::default(),
             prompts: agents_core::prompts::EffectivePrompts {
                 base_md: "".to_string(),
                 project_md: "".to_string(),
@@ -73,6 +77,7 @@ fn dummy_render_context() -> RenderContext {
         adapter: AdapterCtx {
             agent_id: "a".to_string(),
         },
+        vars: Default::default(),
         x: None,
     }
 }
@@ -106,6 +111,7 @@ fn planned_output(path: fsutil::RepoPath, write_mode: WriteMode, gitignore: bool
         template_dir: None,
         inline_template: None,
         render_context: dummy_render_context(),
+        skill_asset: None,
     }
 }
 
@@ -118,6 +124,7 @@ fn stamp_meta(profile: Option<&str>, content_without_stamp: &str) -> StampMeta {
         policy: "safe".to_string(),
         backend: agents_core::model::manifest::BackendKind::Materialize,
         profile: profile.map(|s| s.to_string()),
+        scope: None,
         content_sha256: compute_sha256_hex(content_without_stamp),
     }
 }
@@ -282,3 +289,47 @@ fn gitignore_update_is_stable_and_idempotent() {
     assert!(second.contains("# BEGIN agents (generated)"));
     assert!(second.contains("out.txt"));
 }
+
+#[test]
+fn transaction_writes_nothing_when_any_batch_conflicts() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo_root = tmp.path();
+
+    let new_path = repo_path_for_existing(repo_root, "new.txt");
+    fs::remove_file(repo_root.join("new.txt")).unwrap();
+
+    let manual_path = repo_path_for_existing(repo_root, "manual.txt");
+    write_file(&repo_root.join("manual.txt"), "manual\n");
+
+    let batch = |agent_id: &str, path: &fsutil::RepoPath, drift: DriftStatus| ApplyBatch {
+        plan: OutputPlan {
+            agent_id: agent_id.to_string(),
+            backend: agents_core::model::manifest::BackendKind::Materialize,
+            outputs: vec![planned_output(path.clone(), WriteMode::IfGenerated, false)],
+        },
+        outputs: vec![rendered(
+            path.clone(),
+            stamp_meta(None, "hello\n"),
+            drift,
+            "hello\n",
+        )],
+    };
+
+    let report = apply_transaction(
+        repo_root,
+        &[
+            batch("a", &new_path, DriftStatus::Missing),
+            batch("b", &manual_path, DriftStatus::Unmanaged),
+        ],
+    )
+    .unwrap();
+
+    assert!(!report.committed);
+    assert!(report.has_conflicts());
+    assert_eq!(report.reports[1].1.conflicts, vec![manual_path]);
+    assert!(!repo_root.join("new.txt").exists());
+    assert_eq!(
+        fs::read_to_string(repo_root.join("manual.txt")).unwrap(),
+        "manual\n"
+    );
+}
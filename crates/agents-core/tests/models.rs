@@ -101,3 +101,33 @@ unknownKey: 123
     let err = serde_yaml::from_str::<model::Manifest>(y).unwrap_err();
     let _ = err;
 }
+
+#[test]
+fn scope_dir_is_root_or_shared_literal_prefix() {
+    let scope = |y: &str| serde_yaml::from_str::<model::Scope>(y).unwrap().dir();
+
+    assert_eq!(
+        scope("id: a\napplyTo: ['packages/foo/**']\noverrides: {}\n").as_deref(),
+        Some("packages/foo")
+    );
+    assert_eq!(
+        scope("id: a\napplyTo: ['apps/web/src/**', 'apps/web/*.json']\noverrides: {}\n").as_deref(),
+        Some("apps/web")
+    );
+    assert_eq!(
+        scope("id: a\napplyTo: ['apps/**', 'libs/**']\noverrides: {}\n"),
+        None
+    );
+    assert_eq!(
+        scope("id: a\napplyTo: ['packages/foo/README.md']\noverrides: {}\n").as_deref(),
+        Some("packages/foo")
+    );
+    assert_eq!(
+        scope("id: a\napplyTo: ['packages/foo', 'packages/foo/**']\noverrides: {}\n").as_deref(),
+        Some("packages/foo")
+    );
+    assert_eq!(
+        scope("id: a\napplyTo: ['**/*.rs']\nroot: ./crates/x/\noverrides: {}\n").as_deref(),
+        Some("crates/x")
+    );
+}
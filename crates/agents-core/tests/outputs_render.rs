@@ -0,0 +1,482 @@
+use std::fs;
+
+use agents_core::driftx::{diff_plan, DiffKind};
+use agents_core::loadag::{load_repo_config, LoaderOptions};
+use agents_core::outputs::{plan_outputs, render_planned_bytes, render_planned_output};
+use agents_core::resolv::{ResolutionRequest, Resolver};
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn base_repo(repo: &std::path::Path, adapter_yaml: &str) {
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [], adapters: [a] }\n",
+    );
+
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+
+    write_file(&repo.join(".agents/adapters/a/adapter.yaml"), adapter_yaml);
+}
+
+fn render_all(repo: &std::path::Path) -> Vec<(String, String)> {
+    let (cfg, _report) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+
+    let resolver = Resolver::new(cfg.clone());
+    let req = ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        ..Default::default()
+    };
+    let eff = resolver.resolve(&req).unwrap();
+
+    let plan = plan_outputs(repo, cfg, &eff, "a").unwrap().plan;
+    plan.outputs
+        .iter()
+        .map(|o| {
+            let r = render_planned_output(repo, o).unwrap();
+            (o.path.as_str().to_string(), r.content_without_stamp)
+        })
+        .collect()
+}
+
+#[test]
+fn merge_collision_renders_concat_of_templates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: b.md
+    surface: s
+    collision: merge
+    renderer: { type: template, template: b.hbs }
+  - path: a.md
+    surface: s
+    collision: merge
+    renderer: { type: template, template: a.hbs }
+"#,
+    );
+    write_file(&repo.join(".agents/adapters/a/templates/a.hbs"), "a\n");
+    write_file(
+        &repo.join(".agents/adapters/a/templates/b.hbs"),
+        "b {{adapter.agentId}}\n\n",
+    );
+
+    let rendered = render_all(repo);
+    assert_eq!(
+        rendered,
+        vec![("a.md".to_string(), "a\n\nb a\n".to_string())]
+    );
+}
+
+#[test]
+fn concat_and_copy_resolve_prompt_and_repo_sources() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: out/concat.md
+    renderer: { type: concat, sources: ["prompt:base", "repo:docs/extra.md", "prompt:project"] }
+  - path: out/copy.txt
+    renderer: { type: copy, sources: ["docs/extra.md"] }
+"#,
+    );
+    write_file(&repo.join("docs/extra.md"), "extra");
+
+    let rendered = render_all(repo);
+    assert_eq!(
+        rendered,
+        vec![
+            (
+                "out/concat.md".to_string(),
+                "base\n\nextra\n\nproject\n".to_string()
+            ),
+            // Copies are byte-exact: no trailing newline is added.
+            ("out/copy.txt".to_string(), "extra".to_string()),
+        ]
+    );
+}
+
+fn plan_for(repo: &std::path::Path) -> agents_core::outputs::OutputPlan {
+    let (cfg, _report) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let eff = Resolver::new(cfg.clone())
+        .resolve(&ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+    plan_outputs(repo, cfg, &eff, "a").unwrap().plan
+}
+
+#[test]
+fn copy_renderer_copies_raw_bytes_and_accepts_colons_in_paths() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: out/crlf.txt
+    renderer: { type: copy, sources: ["docs/v1:crlf.txt"] }
+  - path: out/logo.png
+    renderer: { type: copy, sources: ["repo:docs/logo.png"] }
+"#,
+    );
+    write_file(&repo.join("docs/v1:crlf.txt"), "a\r\nb");
+    let png: &[u8] = &[0x89, b'P', b'N', b'G', 0xff, 0x00];
+    fs::write(repo.join("docs/logo.png"), png).unwrap();
+
+    let plan = plan_for(repo);
+    assert_eq!(
+        render_planned_bytes(repo, &plan.outputs[0]).unwrap(),
+        b"a\r\nb"
+    );
+    assert_eq!(render_planned_bytes(repo, &plan.outputs[1]).unwrap(), png);
+
+    // Stamped outputs need text; binary content is reported, not mangled.
+    let err = render_planned_output(repo, &plan.outputs[1])
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("not UTF-8"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn repo_sources_cannot_escape_the_repo_through_a_symlink() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    write_file(&tmp.path().join("secret.txt"), "secret\n");
+    std::os::unix::fs::symlink(tmp.path().join("secret.txt"), repo.join("link.txt")).unwrap();
+
+    base_repo(
+        &repo,
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: out/copy.txt
+    renderer: { type: copy, sources: ["repo:link.txt"] }
+"#,
+    );
+
+    let (cfg, _report) = load_repo_config(
+        &repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let eff = Resolver::new(cfg.clone())
+        .resolve(&ResolutionRequest {
+            repo_root: repo.clone(),
+            ..Default::default()
+        })
+        .unwrap();
+    let err = plan_outputs(&repo, cfg, &eff, "a").unwrap_err().to_string();
+    assert!(err.contains("escapes repo root"), "{err}");
+}
+
+#[test]
+fn json_merge_deep_and_shallow_strategies() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: deep.json
+    format: json
+    renderer: { type: json_merge, jsonMergeStrategy: deep, sources: ["base.jsonc", "template:over.json.hbs"] }
+  - path: shallow.json
+    format: json
+    renderer: { type: json_merge, jsonMergeStrategy: shallow, sources: ["base.jsonc", "template:over.json.hbs"] }
+"#,
+    );
+    write_file(
+        &repo.join("base.jsonc"),
+        "{\n  // comment\n  \"z\": 1,\n  \"obj\": { \"keep\": true, \"list\": [1, 2], \"url\": \"http://x/*y*/\" },\n}\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/over.json.hbs"),
+        "{ \"obj\": { \"list\": [3], \"agent\": \"{{adapter.agentId}}\" } }\n",
+    );
+
+    let rendered = render_all(repo);
+
+    let deep: serde_json::Value = serde_json::from_str(&rendered[0].1).unwrap();
+    assert_eq!(
+        deep,
+        serde_json::json!({
+            "z": 1,
+            "obj": { "keep": true, "list": [3], "url": "http://x/*y*/", "agent": "a" }
+        })
+    );
+
+    let shallow: serde_json::Value = serde_json::from_str(&rendered[1].1).unwrap();
+    assert_eq!(
+        shallow,
+        serde_json::json!({ "z": 1, "obj": { "list": [3], "agent": "a" } })
+    );
+
+    // Deterministic key ordering.
+    assert!(rendered[0].1.find("\"obj\"").unwrap() < rendered[0].1.find("\"z\"").unwrap());
+}
+
+#[test]
+fn driftx_diffs_non_template_renderers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        r#"agentId: a
+version: '0.1'
+backendDefaults: { preferred: vfs_container, fallback: materialize }
+outputs:
+  - path: out.md
+    renderer: { type: concat, sources: ["prompt:base", "prompt:project"] }
+"#,
+    );
+
+    let (cfg, _report) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let eff = Resolver::new(cfg.clone())
+        .resolve(&ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+    let plan = plan_outputs(repo, cfg, &eff, "a").unwrap().plan;
+
+    let report = diff_plan(repo, &plan).unwrap();
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].kind, DiffKind::Create);
+}
+
+#[test]
+fn scope_dir_outputs_are_nested_and_rendered_with_the_scope_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: vfs_container, fallback: materialize }\n\
+         outputs:\n  - path: '{{scopeDir}}/AGENTS.md'\n    renderer: { type: template, template: t.hbs }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "{{scope.id}} {{scope.dir}} mode={{generation.stamp.mode}}\n",
+    );
+    write_file(
+        &repo.join(".agents/modes/review.md"),
+        "---\nid: review\n---\n\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/foo.yaml"),
+        "id: foo\napplyTo: ['packages/foo/**']\noverrides: { mode: review }\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/bar.yaml"),
+        "id: bar\napplyTo: ['**/*.rs']\nroot: crates/bar/\noverrides: {}\n",
+    );
+    // No literal directory and no `root`: skipped rather than written at the repo root.
+    write_file(
+        &repo.join(".agents/scopes/docs.yaml"),
+        "id: docs\napplyTo: ['**/*.md']\noverrides: {}\n",
+    );
+
+    let rendered = render_all(repo);
+    assert_eq!(
+        rendered,
+        vec![
+            (
+                "crates/bar/AGENTS.md".to_string(),
+                "bar crates/bar mode=default\n".to_string()
+            ),
+            (
+                "packages/foo/AGENTS.md".to_string(),
+                "foo packages/foo mode=review\n".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn scope_outputs_plan_assets_of_skills_only_the_scope_enables() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: vfs_container, fallback: materialize }\n\
+         outputs:\n  - path: '{{scopeDir}}/AGENTS.md'\n    renderer: { type: template, template: t.hbs }\n",
+    );
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [lint], adapters: [a] }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "{{#each effective.skills.ids}}{{this}} {{/each}}\n",
+    );
+    write_file(
+        &repo.join(".agents/skills/lint/skill.yaml"),
+        "id: lint\nversion: '0.1.0'\ntitle: lint\ndescription: test\nactivation: instruction_only\n\
+         interface: { type: cli }\n\
+         contract: { inputs: {}, outputs: {} }\n\
+         requirements: { capabilities: { filesystem: read, exec: none, network: none } }\n\
+         assets: { materialize: [run.sh] }\n",
+    );
+    write_file(&repo.join(".agents/skills/lint/run.sh"), "#!/bin/sh\n");
+    // A literal file pattern scopes its parent directory.
+    write_file(
+        &repo.join(".agents/scopes/foo.yaml"),
+        "id: foo\napplyTo: ['packages/foo/README.md']\noverrides: { enableSkills: [lint] }\n",
+    );
+
+    let rendered = render_all(repo);
+    assert_eq!(
+        rendered,
+        vec![
+            (
+                ".agents-skills/lint/run.sh".to_string(),
+                "#!/bin/sh\n".to_string()
+            ),
+            ("packages/foo/AGENTS.md".to_string(), "lint \n".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn scope_id_outputs_are_rendered_with_the_scope_config_and_record_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(
+        repo,
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: vfs_container, fallback: materialize }\n\
+         outputs:\n  - path: '{{scopeId}}.instructions.md'\n    renderer: { type: template, template: t.hbs }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "{{#each effective.prompts.snippets}}{{this.id}} {{/each}}policy={{effective.policy.id}}\n",
+    );
+    write_file(&repo.join(".agents/prompts/snippets/pkg.md"), "pkg\n");
+    write_file(
+        &repo.join(".agents/policies/strict.yaml"),
+        "id: strict\ndescription: strict\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/packages.yaml"),
+        "id: packages\napplyTo: ['packages/**']\noverrides: { policy: strict, includeSnippets: [pkg] }\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/docs.yaml"),
+        "id: docs\napplyTo: ['docs/**']\noverrides: {}\n",
+    );
+
+    assert_eq!(
+        render_all(repo),
+        vec![
+            (
+                "docs.instructions.md".to_string(),
+                "policy=safe\n".to_string()
+            ),
+            (
+                "packages.instructions.md".to_string(),
+                "pkg policy=strict\n".to_string()
+            ),
+        ]
+    );
+
+    let (cfg, _report) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let eff = Resolver::new(cfg.clone())
+        .resolve(&ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+    let result = plan_outputs(repo, cfg, &eff, "a").unwrap();
+
+    let skel = result
+        .sources
+        .iter()
+        .find(|s| s.output_path == "packages.instructions.md")
+        .unwrap();
+    assert_eq!(skel.scope_id.as_deref(), Some("packages"));
+    assert_eq!(skel.policy_id, "strict");
+    assert_eq!(skel.snippet_ids, vec!["pkg".to_string()]);
+    assert!(skel
+        .prompt_source_paths
+        .contains(&".agents/prompts/snippets/pkg.md".to_string()));
+
+    let out = result
+        .plan
+        .outputs
+        .iter()
+        .find(|o| o.path.as_str() == "packages.instructions.md")
+        .unwrap();
+    let rendered = render_planned_output(repo, out).unwrap();
+    let stamp = agents_core::stamps::parse_stamp(&rendered.content_with_stamp).unwrap();
+    assert_eq!(stamp.meta.scope.as_deref(), Some("packages"));
+    assert_eq!(stamp.meta.policy, "strict");
+}
@@ -0,0 +1,254 @@
+use std::fs;
+
+use agents_core::driftx::DiffKind;
+use agents_core::loadag::{load_repo_config, LoaderOptions};
+use agents_core::model::BackendKind;
+use agents_core::outputs::render_planned_output;
+use agents_core::pipeline::{Pipeline, RenderCacheMode};
+use agents_core::resolv::ResolutionRequest;
+use agents_core::stamps::DriftStatus;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn base_repo(repo: &std::path::Path) {
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default], policies: [safe], skills: [], adapters: [a, core] }\n",
+    );
+
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\n\n",
+    );
+
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+
+    write_file(
+        &repo.join(".agents/adapters/a/adapter.yaml"),
+        "agentId: a\nversion: '0.1'\nbackendDefaults: { preferred: vfs_container, fallback: materialize }\noutputs:\n  - path: out.md\n    renderer: { type: template, template: t.hbs }\n",
+    );
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "backend={{backend}}\n",
+    );
+}
+
+fn load(repo: &std::path::Path) -> agents_core::loadag::RepoConfig {
+    load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap()
+    .0
+}
+
+#[test]
+fn pipeline_renders_with_stamps_and_drift_status() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let res = Pipeline::for_agent(repo, load(repo), "a")
+        .with_request(ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            override_backend: Some(BackendKind::Materialize),
+            ..Default::default()
+        })
+        .render()
+        .unwrap();
+
+    assert_eq!(res.effective.backend, BackendKind::Materialize);
+    assert_eq!(res.outputs.len(), 1);
+
+    let out = &res.outputs[0];
+    assert_eq!(out.planned.path.as_str(), "out.md");
+    assert_eq!(out.content_without_stamp, b"backend=materialize\n");
+    assert_eq!(out.stamp_meta.adapter_agent_id, "a");
+    assert_eq!(out.drift_status, DriftStatus::Missing);
+
+    // Same bytes as the low-level renderer.
+    let direct = render_planned_output(repo, &out.planned).unwrap();
+    assert_eq!(direct.content_with_stamp.as_bytes(), out.content_with_stamp);
+
+    assert_eq!(res.materialize_outputs().len(), 1);
+    assert_eq!(res.overlay_files()[0].rel_path, "out.md");
+}
+
+#[test]
+fn pipeline_diff_handles_builtin_inline_templates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let res = Pipeline::for_agent(repo, load(repo), "core")
+        .render()
+        .unwrap();
+    let report = res.diff().unwrap();
+
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].path, "AGENTS.md");
+    assert_eq!(report.entries[0].kind, DiffKind::Create);
+
+    std::fs::write(repo.join("AGENTS.md"), &res.outputs[0].content_with_stamp).unwrap();
+
+    let res = Pipeline::for_agent(repo, load(repo), "core")
+        .render()
+        .unwrap();
+    assert_eq!(res.outputs[0].drift_status, DriftStatus::Clean);
+    assert_eq!(res.diff().unwrap().entries[0].kind, DiffKind::Noop);
+}
+
+#[test]
+fn pipeline_render_cache_hits_on_unchanged_inputs_and_misses_on_template_change() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let render = || {
+        Pipeline::for_agent(repo, load(repo), "a")
+            .with_request(ResolutionRequest {
+                repo_root: repo.to_path_buf(),
+                override_backend: Some(BackendKind::Materialize),
+                ..Default::default()
+            })
+            .with_render_cache(RenderCacheMode::ReadWrite)
+            .render()
+            .unwrap()
+    };
+
+    let first = render();
+    assert_eq!(first.cache_hits, 0);
+
+    let second = render();
+    assert_eq!(second.cache_hits, second.outputs.len());
+    assert_eq!(
+        second.outputs[0].content_with_stamp,
+        first.outputs[0].content_with_stamp
+    );
+
+    write_file(
+        &repo.join(".agents/adapters/a/templates/t.hbs"),
+        "changed backend={{backend}}\n",
+    );
+    let third = render();
+    assert_eq!(third.cache_hits, 0);
+    assert_eq!(
+        third.outputs[0].content_without_stamp,
+        b"changed backend=materialize\n"
+    );
+}
+
+fn cache_entries(repo: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(repo.join(".agents/state/cache/render/a"))
+        .map(|d| {
+            d.flatten()
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+#[test]
+fn read_only_render_cache_reuses_entries_without_writing_or_dropping_any() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let render = |backend, mode| {
+        Pipeline::for_agent(repo, load(repo), "a")
+            .with_request(ResolutionRequest {
+                repo_root: repo.to_path_buf(),
+                override_backend: Some(backend),
+                ..Default::default()
+            })
+            .with_render_cache(mode)
+            .render()
+            .unwrap()
+    };
+
+    render(BackendKind::Materialize, RenderCacheMode::ReadWrite);
+    let entries = cache_entries(repo);
+    assert!(!entries.is_empty());
+
+    // Another run (different backend) misses, but neither stores nor evicts anything.
+    let other = render(BackendKind::VfsContainer, RenderCacheMode::ReadOnly);
+    assert_eq!(other.cache_hits, 0);
+    assert_eq!(cache_entries(repo), entries);
+
+    let again = render(BackendKind::Materialize, RenderCacheMode::ReadOnly);
+    assert_eq!(again.cache_hits, again.outputs.len());
+
+    // Writable runs keep entries from other runs too.
+    render(BackendKind::VfsContainer, RenderCacheMode::ReadWrite);
+    let synced = render(BackendKind::Materialize, RenderCacheMode::ReadWrite);
+    assert_eq!(synced.cache_hits, synced.outputs.len());
+}
+
+#[test]
+fn render_cache_evicts_entries_unused_for_a_month() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let stale = repo.join(".agents/state/cache/render/a/stale");
+    write_file(&stale, "old\n");
+    let forty_days = std::time::Duration::from_secs(40 * 24 * 60 * 60);
+    fs::File::options()
+        .write(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() - forty_days)
+        .unwrap();
+
+    let res = Pipeline::for_agent(repo, load(repo), "a")
+        .with_render_cache(RenderCacheMode::ReadWrite)
+        .render()
+        .unwrap();
+
+    assert!(!stale.exists());
+    assert_eq!(cache_entries(repo).len(), res.outputs.len());
+}
+
+#[test]
+fn pipeline_plans_against_the_user_overlay_it_resolves_with() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    base_repo(repo);
+
+    let home = tempfile::tempdir().unwrap();
+    write_file(
+        &home.path().join(".agents/modes/personal.md"),
+        "---\nid: personal\n---\nPersonal.\n",
+    );
+
+    let res = Pipeline::for_agent(repo, load(repo), "a")
+        .with_request(ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            override_mode: Some("personal".to_string()),
+            enable_user_overlay: true,
+            user_overlay_root: Some(home.path().to_path_buf()),
+            ..Default::default()
+        })
+        .render()
+        .unwrap();
+
+    assert_eq!(res.effective.mode_id, "personal");
+    assert_eq!(res.effective.from_user_overlay, vec!["mode: personal"]);
+}
@@ -188,3 +188,445 @@ fn specificity_and_priority_break_ties() {
 
     assert_eq!(eff2.scopes_matched[0].id, "c");
 }
+
+#[test]
+fn profile_beats_scope_and_cli_beats_profile() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+
+    write_file(
+        &repo.join(".agents/scopes/a.yaml"),
+        "id: a\napplyTo: ['apps/**']\noverrides: { mode: default }\n",
+    );
+    write_file(
+        &repo.join(".agents/profiles/ci.yaml"),
+        "description: locked down\nmode: refactor\nbackend: materialize\nadapters: []\n\
+         includeSnippets: [s1]\nvars: { ci: true }\n",
+    );
+
+    let (cfg, _r) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let resolver = Resolver::new(cfg);
+
+    let req = ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        target_path: Some("apps/web".to_string()),
+        override_profile: Some("ci".to_string()),
+        ..Default::default()
+    };
+
+    let eff = resolver.resolve(&req).unwrap();
+    assert_eq!(eff.profile.as_deref(), Some("ci"));
+    assert_eq!(eff.mode_id, "refactor");
+    assert_eq!(eff.backend, BackendKind::Materialize);
+    assert_eq!(eff.snippet_ids_included, vec!["s1".to_string()]);
+    assert!(eff.adapters_enabled.is_empty());
+    assert_eq!(eff.vars.get("ci"), Some(&serde_json::json!(true)));
+
+    let eff = resolver
+        .resolve(&ResolutionRequest {
+            override_mode: Some("default".to_string()),
+            override_backend: Some(BackendKind::VfsMount),
+            ..req
+        })
+        .unwrap();
+    assert_eq!(eff.mode_id, "default");
+    assert_eq!(eff.backend, BackendKind::VfsMount);
+}
+
+#[test]
+fn profile_with_missing_reference_fails_to_load() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(&repo.join(".agents/profiles/ci.yaml"), "policy: nope\n");
+
+    let err = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing required id nope in profile.policy"
+    );
+}
+
+#[test]
+fn agent_overrides_apply_only_for_that_agent() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default, refactor], policies: [safe, strict], skills: [], adapters: [] }\n\
+         byAgent: { core: { policy: strict, includeSnippets: [s1] } }\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/strict.yaml"),
+        "id: strict\ndescription: strict\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(&repo.join(".agents/prompts/snippets/s2.md"), "Snippet 2\n");
+    write_file(
+        &repo.join(".agents/scopes/a.yaml"),
+        "id: a\napplyTo: ['apps/**']\noverrides: {}\n\
+         byAgent: { core: { mode: refactor, includeSnippets: [s2] } }\n",
+    );
+
+    let (cfg, _r) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let resolver = Resolver::new(cfg);
+    let req = ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        target_path: Some("apps/web".to_string()),
+        ..Default::default()
+    };
+
+    let plain = resolver.resolve(&req).unwrap();
+    assert_eq!(plain.agent_id, None);
+    assert_eq!(plain.mode_id, "default");
+    assert_eq!(plain.policy_id, "safe");
+    assert!(plain.snippet_ids_included.is_empty());
+
+    let core = resolver.resolve_for_agent(&req, "core").unwrap();
+    assert_eq!(core.agent_id.as_deref(), Some("core"));
+    assert_eq!(core.mode_id, "refactor");
+    assert_eq!(core.policy_id, "strict");
+    assert_eq!(
+        core.snippet_ids_included,
+        vec!["s1".to_string(), "s2".to_string()]
+    );
+
+    let other = resolver.resolve_for_agent(&req, "other").unwrap();
+    assert_eq!(other.policy_id, "safe");
+}
+
+#[test]
+fn manifest_agent_overrides_apply_above_scopes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        "specVersion: '0.1'\n\
+         defaults: { mode: default, policy: safe }\n\
+         enabled: { modes: [default, refactor], policies: [safe, strict], skills: [], adapters: [] }\n\
+         byAgent: { core: { policy: strict } }\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/strict.yaml"),
+        "id: strict\ndescription: strict\ncapabilities: {}\npaths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/a.yaml"),
+        "id: a\napplyTo: ['apps/**']\noverrides: { mode: refactor, policy: safe }\n",
+    );
+
+    let (cfg, _r) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let resolver = Resolver::new(cfg);
+    let req = ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        target_path: Some("apps/web".to_string()),
+        ..Default::default()
+    };
+
+    let core = resolver.resolve_for_agent(&req, "core").unwrap();
+    assert_eq!(core.mode_id, "refactor");
+    assert_eq!(core.policy_id, "strict");
+    assert_eq!(core.trace.policy.as_ref().unwrap().set_by, "byAgent:core");
+}
+
+#[test]
+fn scope_agent_overrides_must_name_a_known_adapter() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/scopes/a.yaml"),
+        "id: a\napplyTo: ['apps/**']\noverrides: {}\nbyAgent: { nope: { mode: refactor } }\n",
+    );
+
+    let err = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "missing required id nope in scope.byAgent");
+}
+
+#[test]
+fn scope_root_must_stay_inside_the_repo() {
+    for root in ["../elsewhere", "/abs", "packages/../../x"] {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+
+        base_repo(repo);
+        write_file(
+            &repo.join(".agents/scopes/a.yaml"),
+            &format!("id: a\napplyTo: ['**/*.rs']\nroot: '{root}'\noverrides: {{}}\n"),
+        );
+
+        let err = load_repo_config(
+            repo,
+            &LoaderOptions {
+                require_schemas_dir: false,
+            },
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("scope a has invalid root {root}")),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn trace_records_which_layer_set_each_value() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+
+    write_file(
+        &repo.join(".agents/scopes/a.yaml"),
+        "id: a\napplyTo: ['apps/**']\noverrides: { mode: refactor, includeSnippets: [s2] }\n",
+    );
+    write_file(&repo.join(".agents/prompts/snippets/s2.md"), "Snippet 2\n");
+
+    let (cfg, _r) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let resolver = Resolver::new(cfg);
+
+    let req = ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        target_path: Some("apps/web".to_string()),
+        override_mode: Some("default".to_string()),
+        ..Default::default()
+    };
+
+    let trace = resolver.resolve(&req).unwrap().trace;
+
+    let mode = trace.mode.unwrap();
+    assert_eq!(mode.value, "default");
+    assert_eq!(mode.set_by, "cli");
+    let overrode: Vec<(&str, &str)> = mode
+        .overrode
+        .iter()
+        .map(|lv| (lv.layer.as_str(), lv.value.as_str()))
+        .collect();
+    assert_eq!(
+        overrode,
+        vec![("defaults", "default"), ("scope:a", "refactor")]
+    );
+
+    let policy = trace.policy.unwrap();
+    assert_eq!(
+        (policy.set_by.as_str(), policy.overrode.len()),
+        ("defaults", 0)
+    );
+    assert!(trace.profile.is_none());
+    assert_eq!(trace.backend.unwrap().set_by, "defaults");
+    assert_eq!(trace.snippets["s2"].set_by, "scope:a");
+}
+
+fn merge_repo(repo: &Path, resolution: &str) -> Resolver {
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        &format!(
+            "specVersion: '0.1'\n\
+             defaults: {{ mode: default, policy: safe }}\n\
+             enabled: {{ modes: [default, refactor], policies: [safe, open, locked], skills: [], adapters: [] }}\n\
+             resolution: {resolution}\n"
+        ),
+    );
+    write_file(
+        &repo.join(".agents/policies/open.yaml"),
+        "id: open\ndescription: open\n\
+         capabilities: { exec: { allow: [git, curl] }, network: { enabled: true } }\n\
+         paths: { allow: ['apps/**'] }\nconfirmations: {}\nlimits: { maxFilesChanged: 50 }\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/locked.yaml"),
+        "id: locked\ndescription: locked\n\
+         capabilities: { exec: { deny: [curl] }, network: { enabled: false } }\n\
+         paths: { deny: ['apps/web/secrets/**'] }\nconfirmations: { requiredFor: [delete] }\n\
+         limits: { maxFilesChanged: 10 }\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/apps.yaml"),
+        "id: apps\napplyTo: ['apps/**']\noverrides: { policy: open }\n",
+    );
+    write_file(
+        &repo.join(".agents/scopes/web.yaml"),
+        "id: web\napplyTo: ['apps/web/**']\noverrides: { policy: locked }\n",
+    );
+
+    let (cfg, _r) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    Resolver::new(cfg)
+}
+
+fn web_request(repo: &Path) -> ResolutionRequest {
+    ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        target_path: Some("apps/web/src".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn scope_policies_merge_to_the_most_restrictive_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    // Without the option the most specific scope wins outright.
+    let resolver = merge_repo(repo, "{}");
+    let eff = resolver.resolve(&web_request(repo)).unwrap();
+    assert_eq!(eff.policy_id, "locked");
+    assert!(eff.merged_policy.is_none());
+
+    let resolver = merge_repo(repo, "{ mergeScopePolicies: true }");
+    let eff = resolver.resolve(&web_request(repo)).unwrap();
+    assert_eq!(eff.policy_id, "open+locked");
+    assert_eq!(eff.trace.policy.as_ref().unwrap().set_by, "scope_merge");
+
+    let merged = eff.merged_policy.clone().unwrap();
+    assert_eq!(merged.sources, vec!["open", "locked"]);
+    assert_eq!(merged.conflicts, vec!["exec: curl"]);
+
+    let policy = eff.policy(&resolver.repo).unwrap();
+    assert_eq!(policy.id, "open+locked");
+    let exec = policy.capabilities.exec.as_ref().unwrap();
+    assert_eq!(exec.allow, vec!["git", "curl"]);
+    assert!(exec.deny.is_empty());
+    assert!(!policy.capabilities.network.as_ref().unwrap().enabled);
+    assert_eq!(policy.paths.allow, vec!["apps/**"]);
+    assert_eq!(policy.paths.deny, vec!["apps/web/secrets/**"]);
+    assert_eq!(policy.confirmations.required_for.len(), 1);
+    assert_eq!(policy.limits.as_ref().unwrap().max_files_changed, Some(10));
+}
+
+#[test]
+fn merged_scope_policies_treat_missing_capability_blocks_as_runtime_defaults() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    let _ = merge_repo(repo, "{ mergeScopePolicies: true }");
+    // `locked` now leaves network out, which the runner treats as disabled.
+    write_file(
+        &repo.join(".agents/policies/locked.yaml"),
+        "id: locked\ndescription: locked\n\
+         capabilities: { filesystem: { write: false } }\n\
+         paths: {}\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/open.yaml"),
+        "id: open\ndescription: open\n\
+         capabilities: { network: { enabled: true, allowHosts: [example.com] } }\n\
+         paths: {}\nconfirmations: {}\n",
+    );
+    let (cfg, _r) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let resolver = Resolver::new(cfg);
+
+    let eff = resolver.resolve(&web_request(repo)).unwrap();
+    let policy = eff.policy(&resolver.repo).unwrap();
+    assert_eq!(policy.id, "open+locked");
+
+    let network = policy.capabilities.network.as_ref().unwrap();
+    assert!(!network.enabled);
+    assert_eq!(network.allow_hosts, vec!["example.com"]);
+
+    // `open` leaves filesystem out: read/write allowed by default, so `locked` decides write.
+    let fs = policy.capabilities.filesystem.as_ref().unwrap();
+    assert!(fs.read);
+    assert!(!fs.write);
+    assert!(!fs.delete);
+
+    // Blocks no policy sets stay out.
+    assert!(policy.capabilities.exec.is_none());
+    assert!(policy.capabilities.mcp.is_none());
+}
+
+#[test]
+fn merged_scope_policies_honor_deny_overrides_allow_and_on_conflict() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    let resolver = merge_repo(
+        repo,
+        "{ mergeScopePolicies: true, denyOverridesAllow: true }",
+    );
+    let eff = resolver.resolve(&web_request(repo)).unwrap();
+    let exec = eff
+        .policy(&resolver.repo)
+        .unwrap()
+        .capabilities
+        .exec
+        .clone()
+        .unwrap();
+    assert_eq!(exec.allow, vec!["git"]);
+    assert_eq!(exec.deny, vec!["curl"]);
+
+    let resolver = merge_repo(repo, "{ mergeScopePolicies: true, onConflict: error }");
+    let err = resolver.resolve(&web_request(repo)).unwrap_err();
+    assert!(
+        err.to_string().contains("open+locked") && err.to_string().contains("exec: curl"),
+        "{err}"
+    );
+
+    // A CLI policy replaces the merged one.
+    let eff = resolver
+        .resolve(&ResolutionRequest {
+            override_policy: Some("safe".to_string()),
+            ..web_request(repo)
+        })
+        .unwrap();
+    assert_eq!(eff.policy_id, "safe");
+    assert!(eff.merged_policy.is_none());
+}
@@ -80,3 +80,140 @@ fn user_overlay_is_lowest_precedence() {
     // Repo default should win over overlay.
     assert_eq!(eff.mode_id, "default");
 }
+
+fn overlay_repo(repo: &Path, resolution: &str) -> agents_core::loadag::RepoConfig {
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        &format!(
+            "specVersion: '0.1'\n\
+             defaults: {{ mode: default, policy: safe }}\n\
+             enabled: {{ modes: [default], policies: [safe], skills: [], adapters: [] }}\n\
+             {resolution}"
+        ),
+    );
+    write_file(&repo.join(".agents/prompts/base.md"), "base\n");
+    write_file(&repo.join(".agents/prompts/project.md"), "project\n");
+    write_file(
+        &repo.join(".agents/modes/default.md"),
+        "---\nid: default\n---\nRepo default.\n",
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: { exec: { allow: [git] } }\npaths: {}\nconfirmations: {}\n",
+    );
+
+    load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap()
+    .0
+}
+
+#[test]
+fn user_overlay_snippets_modes_and_policies_are_addressable() {
+    let repo_tmp = tempfile::tempdir().unwrap();
+    let cfg = overlay_repo(repo_tmp.path(), "");
+
+    // No manifest and no prompts/base.md: only the pieces the user wants to add.
+    let overlay_tmp = tempfile::tempdir().unwrap();
+    let overlay = overlay_tmp.path().join(".agents");
+    write_file(&overlay.join("prompts/snippets/tips.md"), "My tips\n");
+    write_file(
+        &overlay.join("modes/default.md"),
+        "---\nid: default\n---\nOverlay default.\n",
+    );
+    write_file(
+        &overlay.join("modes/personal.md"),
+        "---\nid: personal\nextends: default\npolicy: strict-me\nincludeSnippets: [tips]\n---\nPersonal.\n",
+    );
+    write_file(
+        &overlay.join("policies/strict-me.yaml"),
+        "id: strict-me\nextends: safe\ndescription: mine\ncapabilities: { exec: { deny: [curl] } }\n",
+    );
+
+    let resolver = Resolver::new(cfg);
+    let req = ResolutionRequest {
+        repo_root: repo_tmp.path().to_path_buf(),
+        override_mode: Some("personal".to_string()),
+        enable_user_overlay: true,
+        user_overlay_root: Some(overlay_tmp.path().to_path_buf()),
+        ..Default::default()
+    };
+    let eff = resolver.resolve(&req).unwrap();
+
+    assert_eq!(eff.mode_id, "personal");
+    assert_eq!(eff.policy_id, "strict-me");
+    assert_eq!(eff.snippet_ids_included, vec!["tips".to_string()]);
+    assert_eq!(
+        eff.from_user_overlay,
+        vec!["mode: personal", "policy: strict-me", "snippet: tips"]
+    );
+
+    // Without the overlay the personal mode does not exist.
+    let err = resolver
+        .resolve(&ResolutionRequest {
+            enable_user_overlay: false,
+            ..req.clone()
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("personal"));
+
+    // The repo's `default` mode wins over the overlay's, and is what `personal` extends.
+    let mut with_overlay = agents_core::loadag::RepoConfig::clone(&resolver.repo);
+    agents_core::loadag::apply_user_overlay(&mut with_overlay, &overlay).unwrap();
+    assert_eq!(with_overlay.modes["default"].body, "Repo default.\n");
+    assert_eq!(
+        with_overlay.modes["personal"].body,
+        "Repo default.\n\nPersonal.\n"
+    );
+    let exec = with_overlay.policies["strict-me"]
+        .capabilities
+        .exec
+        .clone()
+        .unwrap();
+    assert_eq!(exec.allow, vec!["git".to_string()]);
+    assert_eq!(exec.deny, vec!["curl".to_string()]);
+}
+
+#[test]
+fn user_overlay_defaults_apply_only_where_the_repo_allows() {
+    let overlay_tmp = tempfile::tempdir().unwrap();
+    let overlay = overlay_tmp.path().join(".agents");
+    write_file(
+        &overlay.join("manifest.yaml"),
+        "defaults: { mode: personal, policy: safe-me }\n",
+    );
+    write_file(
+        &overlay.join("modes/personal.md"),
+        "---\nid: personal\n---\nPersonal.\n",
+    );
+    write_file(
+        &overlay.join("policies/safe-me.yaml"),
+        "id: safe-me\nextends: safe\ndescription: mine\n",
+    );
+
+    let req = |repo: &Path| ResolutionRequest {
+        repo_root: repo.to_path_buf(),
+        enable_user_overlay: true,
+        user_overlay_root: Some(overlay_tmp.path().to_path_buf()),
+        ..Default::default()
+    };
+
+    let allowed_tmp = tempfile::tempdir().unwrap();
+    let allowed = overlay_repo(
+        allowed_tmp.path(),
+        "resolution: { userOverlayDefaults: [policy] }\n",
+    );
+    let eff = Resolver::new(allowed)
+        .resolve(&req(allowed_tmp.path()))
+        .unwrap();
+    assert_eq!(eff.mode_id, "default");
+    assert_eq!(eff.policy_id, "safe-me");
+    assert_eq!(
+        eff.from_user_overlay,
+        vec!["defaults.policy: safe-me", "policy: safe-me"]
+    );
+}
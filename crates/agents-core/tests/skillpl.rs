@@ -3,7 +3,7 @@ use std::path::Path;
 
 use agents_core::loadag::{load_repo_config, LoaderOptions};
 use agents_core::resolv::{ResolutionRequest, Resolver};
-use agents_core::skillpl::{SkillPlanError, SkillPlanner};
+use agents_core::skillpl::{skill_summaries, EffectiveSkills, SkillPlanError, SkillPlanner};
 
 fn write_file(path: &Path, content: &str) {
     if let Some(parent) = path.parent() {
@@ -168,3 +168,185 @@ fn ordering_is_stable() {
     let ids: Vec<_> = skills.enabled.iter().map(|s| s.id.clone()).collect();
     assert_eq!(ids, vec!["s1".to_string(), "s2".to_string()]);
 }
+
+#[test]
+fn summaries_describe_each_enabled_skill() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/skills/s1/skill.yaml"),
+        "id: s1\nversion: '0.0.1'\ntitle: S1\ndescription: test\ntags: [lint]\nactivation: cli_shim\n\
+         interface: { type: cli, entrypoint: ./run.sh }\n\
+         contract: { inputs: { type: object }, outputs: {} }\n\
+         requirements: { capabilities: { filesystem: read, exec: none, network: none }, paths: { needs: [src/] } }\n",
+    );
+
+    let (cfg, _report) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let eff = Resolver::new(cfg.clone())
+        .resolve(&ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+    let skills = SkillPlanner::new(cfg).plan(&eff, None).unwrap();
+
+    let summaries = skill_summaries(&skills);
+    assert_eq!(summaries.len(), 1);
+    let s = &summaries[0];
+    assert_eq!(s["id"], "s1");
+    assert_eq!(s["title"], "S1");
+    assert_eq!(s["activation"], "cli_shim");
+    assert_eq!(s["tags"], serde_json::json!(["lint"]));
+    assert_eq!(s["interface"]["entrypoint"], "./run.sh");
+    assert_eq!(s["requirements"]["filesystem"], "read");
+    assert_eq!(s["requirements"]["needsPaths"], serde_json::json!(["src/"]));
+    let invocation = s["invocation"].as_str().unwrap();
+    assert!(invocation.starts_with("run `s1`"), "{invocation}");
+    assert!(
+        invocation.contains(r#"stdin JSON must match `{"type":"object"}`"#),
+        "{invocation}"
+    );
+}
+
+fn plan_with_network_skill(on_conflict: &str) -> Result<EffectiveSkills, SkillPlanError> {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+
+    base_repo(repo);
+    write_file(
+        &repo.join(".agents/manifest.yaml"),
+        &format!(
+            "specVersion: '0.1'\n\
+             defaults: {{ mode: default, policy: safe }}\n\
+             enabled: {{ modes: [default], policies: [safe], skills: [s1], adapters: [] }}\n\
+             resolution: {{ onConflict: {on_conflict} }}\n"
+        ),
+    );
+    write_file(
+        &repo.join(".agents/policies/safe.yaml"),
+        "id: safe\ndescription: safe\ncapabilities: { network: { enabled: false } }\n\
+         paths: { deny: ['secrets/**'] }\nconfirmations: {}\n",
+    );
+    write_file(
+        &repo.join(".agents/skills/s1/skill.yaml"),
+        "id: s1\nversion: '0.0.1'\ntitle: S1\ndescription: test\nactivation: instruction_only\ninterface: { type: cli }\n\
+         contract: { inputs: {}, outputs: {} }\n\
+         requirements: { capabilities: { filesystem: read, exec: none, network: full }, paths: { needs: [secrets/] } }\n",
+    );
+
+    let (cfg, _report) = load_repo_config(
+        repo,
+        &LoaderOptions {
+            require_schemas_dir: false,
+        },
+    )
+    .unwrap();
+    let eff = Resolver::new(cfg.clone())
+        .resolve(&ResolutionRequest {
+            repo_root: repo.to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+    SkillPlanner::new(cfg).plan(&eff, None)
+}
+
+#[test]
+fn policy_conflicts_disable_skill_with_warning() {
+    let skills = plan_with_network_skill("warn").unwrap();
+
+    assert!(skills.enabled.is_empty());
+    assert_eq!(skills.disabled.len(), 1);
+    let (id, reason) = &skills.disabled[0];
+    assert_eq!(id, "s1");
+    assert_eq!(
+        reason,
+        "needs network; policy disables it; needs path secrets/; policy denies it"
+    );
+    assert_eq!(
+        skills.warnings,
+        vec![format!("skill s1 disabled by policy safe: {reason}")]
+    );
+}
+
+#[test]
+fn policy_conflicts_error_when_on_conflict_is_error() {
+    let err = plan_with_network_skill("error").unwrap_err();
+
+    match err {
+        SkillPlanError::PolicyConflict {
+            skill_id,
+            policy_id,
+            reason,
+        } => {
+            assert_eq!(skill_id, "s1");
+            assert_eq!(policy_id, "safe");
+            assert!(reason.starts_with("needs network"), "{reason}");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn standalone_shims_pass_stdio_through_and_log_the_call() {
+    use agents_core::skillpl::{standalone_shim_files, CliShimSpec};
+    use std::io::Write;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let ws = tmp.path();
+    write_file(
+        &ws.join("tool.sh"),
+        "#!/bin/sh\nprintf '%s|%s|' \"$GREETING\" \"$*\"\ncat\nexit 3\n",
+    );
+    agents_core::vfsmnt::set_executable(&ws.join("tool.sh")).unwrap();
+
+    let log = ws.join("log/invocations.jsonl");
+    fs::create_dir_all(log.parent().unwrap()).unwrap();
+    let shim = CliShimSpec {
+        skill_id: "greet".to_string(),
+        entrypoint: "./tool.sh".to_string(),
+        args: vec!["--json".to_string()],
+        env: [("GREETING".to_string(), "it's hi".to_string())].into(),
+        inputs: serde_json::json!({}),
+        outputs: serde_json::json!({}),
+        log: String::new(),
+    };
+    for f in standalone_shim_files(&[shim], &log.to_string_lossy()) {
+        fs::create_dir_all(ws.join(&f.rel_path).parent().unwrap()).unwrap();
+        fs::write(ws.join(&f.rel_path), &f.bytes).unwrap();
+    }
+
+    let mut child = std::process::Command::new("sh")
+        .arg(ws.join(".agents-bin/greet"))
+        .args(["a \"quoted\" arg", "b\\c"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"not json").unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "it's hi|--json a \"quoted\" arg b\\c|not json"
+    );
+
+    let record: serde_json::Value =
+        serde_json::from_str(fs::read_to_string(&log).unwrap().trim()).unwrap();
+    assert_eq!(record["skill"], "greet");
+    assert_eq!(
+        record["args"],
+        serde_json::json!(["a \"quoted\" arg", "b\\c"])
+    );
+    assert_eq!(record["exit_code"], 3);
+    assert_eq!(record["input"], "unchecked");
+}
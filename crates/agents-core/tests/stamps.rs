@@ -13,6 +13,7 @@ fn meta_for(hash: &str) -> StampMeta {
         policy: "safe".to_string(),
         backend: BackendKind::VfsContainer,
         profile: Some("ci".to_string()),
+        scope: None,
         content_sha256: hash.to_string(),
     }
 }
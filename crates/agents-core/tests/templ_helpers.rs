@@ -9,6 +9,7 @@ fn minimal_ctx() -> RenderContext {
             },
             policy: agents_core::model::Policy {
                 id: "p".to_string(),
+                extends: vec![],
                 description: "d".to_string(),
                 capabilities: agents_core::model::Capabilities {
                     filesystem: None,
@@ -31,6 +32,7 @@ fn minimal_ctx() -> RenderContext {
                 ids: vec!["a".to_string(), "b".to_string()],
                 summaries: vec![],
             },
+            mcp: Default::default(),
             prompts: agents_core::prompts::EffectivePrompts {
                 base_md: "Base".to_string(),
                 project_md: "Project".to_string(),
@@ -53,6 +55,7 @@ fn minimal_ctx() -> RenderContext {
         adapter: agents_core::templ::AdapterCtx {
             agent_id: "x".to_string(),
         },
+        vars: Default::default(),
         x: None,
     }
 }
@@ -124,3 +127,44 @@ fn render_is_deterministic_across_runs() {
 
     assert_eq!(a, b);
 }
+
+#[test]
+fn helper_mcp_servers_writes_standard_and_opencode_shapes() {
+    let engine = TemplateEngine::new();
+    let mut ctx = minimal_ctx();
+    ctx.effective.mcp.servers.insert(
+        "fs".to_string(),
+        agents_core::skillpl::McpServer {
+            command: "fs-mcp".to_string(),
+            args: vec!["--root".to_string(), ".".to_string()],
+            env: Default::default(),
+        },
+    );
+
+    let out = engine
+        .render_inline("{{mcpServers effective.mcp.servers}}", &ctx)
+        .unwrap();
+    let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        v,
+        serde_json::json!({ "fs": { "command": "fs-mcp", "args": ["--root", "."], "env": {} } })
+    );
+
+    let out = engine
+        .render_inline(
+            "{ \"mcp\": {{mcpServers effective.mcp.servers format=\"opencode\" indent=2}} }",
+            &ctx,
+        )
+        .unwrap();
+    assert!(out.contains("\n    \"fs\": {"), "{out}");
+    let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        v["mcp"]["fs"],
+        serde_json::json!({
+            "type": "local",
+            "command": ["fs-mcp", "--root", "."],
+            "environment": {},
+            "enabled": true
+        })
+    );
+}
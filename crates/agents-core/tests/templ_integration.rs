@@ -11,6 +11,7 @@ fn minimal_ctx() -> RenderContext {
             },
             policy: agents_core::model::Policy {
                 id: "p".to_string(),
+                extends: vec![],
                 description: "d".to_string(),
                 capabilities: agents_core::model::Capabilities {
                     filesystem: None,
@@ -33,6 +34,7 @@ fn minimal_ctx() -> RenderContext {
                 ids: vec![],
                 summaries: vec![],
             },
+            mcp: Default::default(),
             prompts: agents_core::prompts::EffectivePrompts {
                 base_md: "Base".to_string(),
                 project_md: "Project".to_string(),
@@ -55,6 +57,7 @@ fn minimal_ctx() -> RenderContext {
         adapter: agents_core::templ::AdapterCtx {
             agent_id: "codex".to_string(),
         },
+        vars: Default::default(),
         x: None,
     }
 }
@@ -70,3 +73,30 @@ fn render_template_from_dir_works() {
     let out = engine.render("hello.txt.hbs", &minimal_ctx()).unwrap();
     assert_eq!(out, "Hello codex\n");
 }
+
+#[test]
+fn builtin_skills_partial_is_available_to_every_template() {
+    let engine = TemplateEngine::new();
+    let mut ctx = minimal_ctx();
+
+    let template = "# Doc\n{{> agents/skills}}\n\n## Next\n";
+    let out = engine.render_inline(template, &ctx).unwrap();
+    assert_eq!(out, "# Doc\n\n## Next\n");
+
+    ctx.effective.skills.summaries.push(
+        serde_json::from_value(serde_json::json!({
+            "id": "greet",
+            "title": "Greeter",
+            "description": "Greets.",
+            "invocation": "run `greet`",
+            "requirements": { "filesystem": "read", "exec": "none", "network": "none" }
+        }))
+        .unwrap(),
+    );
+    let out = engine.render_inline(template, &ctx).unwrap();
+    assert_eq!(
+        out,
+        "# Doc\n\n## Skills\n- **Greeter** (`greet`): Greets.\n  - invoke: run `greet`\n  \
+         - requires: filesystem=read, exec=none, network=none\n\n## Next\n"
+    );
+}
@@ -22,6 +22,11 @@ fn docker_args_are_deterministic() {
         verbose: false,
         deny_network: false,
         deny_writes: false,
+        workspace_dir: None,
+        name: None,
+        ro_mounts: vec![],
+        shim_log_dir: None,
+        user: None,
     };
 
     let args1 = inv.docker_args();
@@ -46,6 +51,11 @@ fn entry_script_includes_copy_overlay_and_exec() {
         verbose: true,
         deny_network: false,
         deny_writes: true,
+        workspace_dir: None,
+        name: None,
+        ro_mounts: vec![],
+        shim_log_dir: None,
+        user: None,
     };
 
     let args = inv.docker_args();
@@ -57,6 +67,34 @@ fn entry_script_includes_copy_overlay_and_exec() {
     assert!(script.contains("exec \"$@\""));
 }
 
+#[test]
+fn workspace_dir_name_and_user_are_passed_to_docker() {
+    let inv = VfsContainerInvocation {
+        repo_root: PathBuf::from("/repo"),
+        outputs_dir: PathBuf::from("/out"),
+        image: "alpine:3.19".to_string(),
+        cmd: vec!["true".to_string()],
+        env: BTreeMap::new(),
+        verbose: false,
+        deny_network: false,
+        deny_writes: false,
+        workspace_dir: Some(PathBuf::from("/ws")),
+        name: Some("agents-run-1".to_string()),
+        ro_mounts: vec![],
+        shim_log_dir: None,
+        user: Some("1000:1000".to_string()),
+    };
+
+    let args = inv.docker_args();
+    assert!(args.contains(&"type=bind,source=/ws,target=/workspace".to_string()));
+    let pos = args.iter().position(|a| a == "--name").unwrap();
+    assert_eq!(args[pos + 1], "agents-run-1");
+    let pos = args.iter().position(|a| a == "--user").unwrap();
+    assert_eq!(args[pos + 1], "1000:1000");
+    assert!(args.contains(&"HOME=/tmp".to_string()));
+    assert!(pos < args.iter().position(|a| a == "alpine:3.19").unwrap());
+}
+
 #[test]
 fn docker_integration_overlay_works_when_enabled() {
     if std::env::var("AGENTS_DOCKER_TESTS").is_err() {
@@ -82,6 +120,11 @@ fn docker_integration_overlay_works_when_enabled() {
         verbose: true,
         deny_network: true,
         deny_writes: false,
+        workspace_dir: None,
+        name: None,
+        ro_mounts: vec![],
+        shim_log_dir: None,
+        user: None,
     };
 
     let docker = DockerRuntime::new();
@@ -93,3 +136,58 @@ fn docker_integration_overlay_works_when_enabled() {
 
     assert_eq!(String::from_utf8_lossy(&out.stdout), "generated\n");
 }
+
+#[test]
+fn ro_mounts_are_bind_mounted_and_skipped_by_repo_copy() {
+    let inv = VfsContainerInvocation {
+        repo_root: PathBuf::from("/repo"),
+        outputs_dir: PathBuf::from("/out"),
+        image: "alpine:3.19".to_string(),
+        cmd: vec!["true".to_string()],
+        env: BTreeMap::new(),
+        verbose: false,
+        deny_network: false,
+        deny_writes: false,
+        workspace_dir: None,
+        name: None,
+        ro_mounts: vec![(
+            PathBuf::from("/repo/.agents/skills/lint/data.json"),
+            ".agents-skills/lint/data.json".to_string(),
+        )],
+        shim_log_dir: None,
+        user: None,
+    };
+
+    let args = inv.docker_args();
+    assert!(args.contains(
+        &"type=bind,source=/repo/.agents/skills/lint/data.json,target=/workspace/.agents-skills/lint/data.json,readonly"
+            .to_string()
+    ));
+    let pos = args.iter().position(|a| a == "-c").unwrap();
+    assert!(args[pos + 1].contains("--exclude='./.agents-skills/lint/data.json'"));
+}
+
+#[test]
+fn shim_log_dir_is_mounted_and_shims_are_put_on_path() {
+    let inv = VfsContainerInvocation {
+        repo_root: PathBuf::from("/repo"),
+        outputs_dir: PathBuf::from("/out"),
+        image: "alpine:3.19".to_string(),
+        cmd: vec!["true".to_string()],
+        env: BTreeMap::new(),
+        verbose: false,
+        deny_network: false,
+        deny_writes: false,
+        workspace_dir: None,
+        name: None,
+        ro_mounts: vec![],
+        shim_log_dir: Some(PathBuf::from("/repo/.agents/state/shims")),
+        user: None,
+    };
+
+    let args = inv.docker_args();
+    assert!(args
+        .contains(&"type=bind,source=/repo/.agents/state/shims,target=/__agents_log".to_string()));
+    let pos = args.iter().position(|a| a == "-c").unwrap();
+    assert!(args[pos + 1].contains("export PATH=\"/workspace/.agents-bin:$PATH\""));
+}
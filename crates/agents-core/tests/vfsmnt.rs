@@ -12,6 +12,7 @@ fn vfs_mount_workspace_overlays_outputs() {
     let overlays = vec![OverlayFile {
         rel_path: "out/gen.txt".to_string(),
         bytes: b"generated".to_vec(),
+        executable: false,
     }];
 
     let workspace = create_workspace(
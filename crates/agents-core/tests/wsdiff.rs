@@ -0,0 +1,180 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use agents_core::model::Limits;
+use agents_core::wsdiff::{
+    apply_workspace_changes, check_diff_limits, diff_workspace, render_patch, ChangeKind,
+    LimitKind, RepoIgnore, WorkspaceBaseline, WorkspaceDiffOptions,
+};
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn diff_workspace_classifies_changes_and_counts_lines() {
+    let repo = tempfile::tempdir().unwrap();
+    let ws = tempfile::tempdir().unwrap();
+
+    write_file(&repo.path().join("keep.txt"), "same\n");
+    write_file(&ws.path().join("keep.txt"), "same\n");
+    write_file(&repo.path().join("edit.txt"), "a\nb\n");
+    write_file(&ws.path().join("edit.txt"), "a\nc\n");
+    write_file(&repo.path().join("gone.txt"), "x\n");
+    write_file(&ws.path().join("src/new.txt"), "1\n2\n3\n");
+
+    // Ignored: overlay outputs and git internals.
+    write_file(&ws.path().join("AGENTS.md"), "generated\n");
+    write_file(&ws.path().join(".git/HEAD"), "ref\n");
+
+    let diff = diff_workspace(
+        repo.path(),
+        ws.path(),
+        &WorkspaceDiffOptions {
+            exclude: BTreeSet::from(["AGENTS.md".to_string()]),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let summary: Vec<(&str, ChangeKind, usize, usize)> = diff
+        .changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.kind, c.added_lines, c.removed_lines))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("edit.txt", ChangeKind::Modified, 1, 1),
+            ("gone.txt", ChangeKind::Deleted, 0, 1),
+            ("src/new.txt", ChangeKind::Added, 3, 0),
+        ]
+    );
+    assert_eq!(diff.files_changed(), 3);
+    assert_eq!(diff.patch_lines(), 6);
+
+    let limits = Limits {
+        max_files_changed: Some(3),
+        max_patch_lines: Some(5),
+        max_command_runtime_sec: None,
+    };
+    let violations = check_diff_limits(&limits, &diff);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].limit, LimitKind::MaxPatchLines);
+    assert_eq!(violations[0].actual, 6);
+    assert_eq!(violations[0].max, 5);
+}
+
+#[test]
+fn render_patch_and_apply_round_trip_changes() {
+    let repo = tempfile::tempdir().unwrap();
+    let ws = tempfile::tempdir().unwrap();
+
+    write_file(&repo.path().join("edit.txt"), "a\nb\n");
+    write_file(&ws.path().join("edit.txt"), "a\nc\n");
+    write_file(&repo.path().join("gone.txt"), "x\n");
+    write_file(&ws.path().join("dir/new.txt"), "1\n");
+
+    let diff = diff_workspace(repo.path(), ws.path(), &WorkspaceDiffOptions::default()).unwrap();
+    let patch = render_patch(repo.path(), ws.path(), &diff).unwrap();
+    assert!(patch.contains("diff --git a/edit.txt b/edit.txt\n--- a/edit.txt\n+++ b/edit.txt\n"));
+    assert!(patch.contains(" a\n-b\n+c\n"));
+    assert!(patch.contains("new file mode 100644\n--- /dev/null\n+++ b/dir/new.txt\n"));
+    assert!(patch.contains("deleted file mode 100644\n--- a/gone.txt\n+++ /dev/null\n"));
+
+    let skipped = apply_workspace_changes(repo.path(), ws.path(), &diff, None).unwrap();
+    assert!(skipped.is_empty());
+    assert_eq!(
+        fs::read_to_string(repo.path().join("edit.txt")).unwrap(),
+        "a\nc\n"
+    );
+    assert_eq!(
+        fs::read_to_string(repo.path().join("dir/new.txt")).unwrap(),
+        "1\n"
+    );
+    assert!(!repo.path().join("gone.txt").exists());
+
+    let after = diff_workspace(repo.path(), ws.path(), &WorkspaceDiffOptions::default()).unwrap();
+    assert!(after.is_empty());
+}
+
+#[test]
+fn diff_workspace_skips_paths_ignored_by_the_repo() {
+    let repo = tempfile::tempdir().unwrap();
+    let ws = tempfile::tempdir().unwrap();
+
+    let gitignore = "# build output\ntarget/\n*.log\n!keep.log\n/node_modules\n";
+    write_file(&repo.path().join(".gitignore"), gitignore);
+    write_file(&ws.path().join(".gitignore"), gitignore);
+    write_file(&ws.path().join("target/debug/app"), "bin\n");
+    write_file(&ws.path().join("crates/x/target/out"), "bin\n");
+    write_file(&ws.path().join("logs/run.log"), "log\n");
+    write_file(&ws.path().join("keep.log"), "kept\n");
+    write_file(&ws.path().join("node_modules/pkg/index.js"), "js\n");
+    write_file(&ws.path().join("src/node_modules/ok.js"), "js\n");
+
+    let diff = diff_workspace(repo.path(), ws.path(), &WorkspaceDiffOptions::default()).unwrap();
+    let paths: Vec<&str> = diff.changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, vec!["keep.log", "src/node_modules/ok.js"]);
+
+    let ignore = RepoIgnore::load(repo.path());
+    assert!(ignore.is_ignored("target", true));
+    assert!(!ignore.is_ignored("target", false));
+    assert!(ignore.is_ignored(".git", true));
+}
+
+#[test]
+fn baseline_separates_repo_edits_from_agent_changes_and_blocks_overwrites() {
+    let repo = tempfile::tempdir().unwrap();
+    let ws = tempfile::tempdir().unwrap();
+
+    for root in [repo.path(), ws.path()] {
+        write_file(&root.join("agent.txt"), "v1\n");
+        write_file(&root.join("user.txt"), "v1\n");
+        write_file(&root.join("both.txt"), "v1\n");
+    }
+    let baseline = WorkspaceBaseline::capture(repo.path(), &BTreeSet::new()).unwrap();
+
+    // During the run the agent edits its files and the user edits the repo.
+    write_file(&ws.path().join("agent.txt"), "agent\n");
+    write_file(&ws.path().join("both.txt"), "agent\n");
+    write_file(&repo.path().join("user.txt"), "user\n");
+    write_file(&repo.path().join("both.txt"), "user\n");
+
+    let diff = diff_workspace(
+        repo.path(),
+        ws.path(),
+        &WorkspaceDiffOptions {
+            baseline: Some(baseline.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let summary: Vec<(&str, bool)> = diff
+        .changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.conflict))
+        .collect();
+    assert_eq!(summary, vec![("agent.txt", false), ("both.txt", true)]);
+
+    // The agent's own change also becomes a conflict if the repo moves on before applying.
+    write_file(&repo.path().join("agent.txt"), "user again\n");
+    let skipped = apply_workspace_changes(repo.path(), ws.path(), &diff, Some(&baseline)).unwrap();
+    assert_eq!(skipped, vec!["agent.txt", "both.txt"]);
+    assert_eq!(
+        fs::read_to_string(repo.path().join("both.txt")).unwrap(),
+        "user\n"
+    );
+    assert_eq!(
+        fs::read_to_string(repo.path().join("agent.txt")).unwrap(),
+        "user again\n"
+    );
+    assert_eq!(
+        fs::read_to_string(repo.path().join("user.txt")).unwrap(),
+        "user\n"
+    );
+}
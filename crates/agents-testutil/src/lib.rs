@@ -3,8 +3,8 @@ use std::path::{Path, PathBuf};
 
 use agents_core::driftx::unified_diff_for;
 use agents_core::loadag::{load_repo_config, LoaderOptions};
-use agents_core::outputs::{plan_outputs, render_planned_output};
-use agents_core::resolv::{ResolutionRequest, Resolver};
+use agents_core::pipeline::{Pipeline, PipelineError};
+use agents_core::resolv::ResolutionRequest;
 use agents_core::{fsutil, schemas};
 use serde::Deserialize;
 
@@ -88,12 +88,11 @@ pub fn run_fixture(
         },
     )
     .map_err(|e| TestError::Load(e.to_string()))?;
+    let repo = std::sync::Arc::new(repo);
 
     // Validate schemas best-effort.
     let _ = schemas::validate_repo(&repo_root);
 
-    let resolver = Resolver::new(repo.clone());
-
     let (cases, use_case_subdir) = load_matrix(&matrix_path)?;
 
     let mut agent_ids: Vec<String> = repo.manifest.enabled.adapters.clone();
@@ -114,23 +113,22 @@ pub fn run_fixture(
                 ..Default::default()
             };
 
-            let effective = resolver
-                .resolve(&req)
-                .map_err(|e| TestError::Resolve(e.to_string()))?;
-
-            let plan = plan_outputs(&repo_root, repo.clone(), &effective, &agent_id)
-                .map_err(|e| TestError::Plan(e.to_string()))?
-                .plan;
+            let rendered = Pipeline::for_agent(&repo_root, std::sync::Arc::clone(&repo), &agent_id)
+                .with_request(req)
+                .render()
+                .map_err(|e| match e {
+                    PipelineError::Resolve(e) => TestError::Resolve(e.to_string()),
+                    PipelineError::Plan(e) => TestError::Plan(e.to_string()),
+                    other => TestError::Render(other.to_string()),
+                })?;
 
             let tmp = fsutil::temp_generation_dir("agents-fixture").map_err(TestError::Fs)?;
             let tmp_path = tmp.path().to_path_buf();
 
             // Render outputs into temp dir.
-            for p in &plan.outputs {
-                let rendered = render_planned_output(&repo_root, p)
-                    .map_err(|e| TestError::Render(e.to_string()))?;
-                let dest = tmp_path.join(p.path.as_str());
-                fsutil::atomic_write(&dest, rendered.content_with_stamp.as_bytes())?;
+            for p in &rendered.outputs {
+                let dest = tmp_path.join(p.planned.path.as_str());
+                fsutil::atomic_write(&dest, &p.content_with_stamp)?;
             }
 
             let expect_dir = if use_case_subdir {